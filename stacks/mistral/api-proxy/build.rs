@@ -0,0 +1,30 @@
+use std::process::Command;
+
+/// Records the git commit the proxy is built from for `/api/version`. `GIT_COMMIT` takes
+/// precedence, for builds without a checkout such as the Docker image.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        println!("cargo:rerun-if-changed={git_dir}/HEAD");
+        println!("cargo:rerun-if-changed={git_dir}/refs");
+    }
+
+    let commit = std::env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.is_empty())
+        .or_else(|| git(&["rev-parse", "--short", "HEAD"]));
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=GIT_COMMIT={commit}");
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
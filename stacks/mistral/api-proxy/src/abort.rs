@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{AppError, Result};
+
+/// Completions in flight, keyed by request ID, so `POST /api/abort` can cancel them.
+#[derive(Debug, Default)]
+pub struct AbortRegistry {
+    active: Mutex<HashMap<String, (u64, CancellationToken)>>,
+    next_seq: AtomicU64,
+}
+
+impl AbortRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a request under `request_id` until the returned handle is dropped.
+    ///
+    /// A request reusing an ID replaces the earlier entry, which can then no longer be aborted.
+    pub fn register(self: &Arc<Self>, request_id: String) -> AbortHandle {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        self.active
+            .lock()
+            .unwrap()
+            .insert(request_id.clone(), (seq, token.clone()));
+        AbortHandle {
+            registry: self.clone(),
+            request_id,
+            seq,
+            token,
+        }
+    }
+
+    /// Cancels the request registered under `request_id`, returning whether there was one.
+    pub fn abort(&self, request_id: &str) -> bool {
+        match self.active.lock().unwrap().remove(request_id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, request_id: &str) -> bool {
+        self.active.lock().unwrap().contains_key(request_id)
+    }
+}
+
+/// Keeps a request abortable while held; dropping it removes the registry entry.
+#[derive(Debug)]
+pub struct AbortHandle {
+    registry: Arc<AbortRegistry>,
+    request_id: String,
+    seq: u64,
+    token: CancellationToken,
+}
+
+impl AbortHandle {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for AbortHandle {
+    fn drop(&mut self) {
+        let mut active = self.registry.active.lock().unwrap();
+        // Leave the entry alone if a later request took over the ID
+        if active
+            .get(&self.request_id)
+            .is_some_and(|(seq, _)| *seq == self.seq)
+        {
+            active.remove(&self.request_id);
+        }
+    }
+}
+
+/// Runs `fut` to completion, or drops it (aborting any in-flight backend call) once `token`
+/// is cancelled.
+pub async fn run_until_aborted<T, F>(token: Option<CancellationToken>, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match token {
+        Some(token) => tokio::select! {
+            _ = token.cancelled() => Err(AppError::Aborted),
+            result = fut => result,
+        },
+        None => fut.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abort_cancels_and_removes_entry() {
+        let registry = Arc::new(AbortRegistry::new());
+        let handle = registry.register("req-1".to_string());
+
+        assert!(registry.abort("req-1"));
+        assert!(handle.token().is_cancelled());
+        assert!(!registry.contains("req-1"));
+        assert!(!registry.abort("req-1"));
+    }
+
+    #[test]
+    fn test_dropped_handle_removes_entry() {
+        let registry = Arc::new(AbortRegistry::new());
+        drop(registry.register("req-1".to_string()));
+        assert!(!registry.contains("req-1"));
+    }
+
+    #[test]
+    fn test_reused_id_keeps_latest_entry() {
+        let registry = Arc::new(AbortRegistry::new());
+        let first = registry.register("req-1".to_string());
+        let second = registry.register("req-1".to_string());
+
+        drop(first);
+        assert!(registry.contains("req-1"));
+        assert!(registry.abort("req-1"));
+        assert!(second.token().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_run_until_aborted() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = run_until_aborted(Some(token), std::future::pending::<Result<()>>()).await;
+        assert!(matches!(result, Err(AppError::Aborted)));
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::metrics::BACKEND_HEALTHY;
+
+/// How strongly recent failures reduce a backend's share of traffic. One fresh failure cuts
+/// its weight to 1/11; the penalty halves every half-life as the failure ages.
+const FAILURE_PENALTY: f64 = 10.0;
+/// Decayed failure score at or above which a backend is reported unhealthy.
+const UNHEALTHY_SCORE: f64 = 0.5;
+/// Smoothing factor for the latency moving average.
+const LATENCY_ALPHA: f64 = 0.3;
+/// Floor on latencies so a backend answering instantly doesn't take all traffic.
+const MIN_LATENCY_SECS: f64 = 0.01;
+
+struct BackendHealth {
+    /// Failure count, decayed exponentially with the configured half-life.
+    failure_score: f64,
+    scored_at: Instant,
+    latency_secs: Option<f64>,
+    /// Smooth weighted round-robin counter.
+    current_weight: f64,
+    healthy: bool,
+}
+
+/// Spreads completions across backends, favouring ones that are succeeding and answering fast.
+///
+/// Health is tracked passively from real traffic: each failure raises a backend's failure
+/// score, which lowers its weight, and the score decays over `failure_half_life` so the
+/// backend is gradually restored. Weights are also inversely proportional to each backend's
+/// average latency. Selection is a smooth weighted round-robin, so traffic follows the
+/// weights without randomness.
+pub struct BackendSelector {
+    backends: Vec<String>,
+    failure_half_life: Duration,
+    health: Mutex<Vec<BackendHealth>>,
+}
+
+impl BackendSelector {
+    pub fn new(backends: Vec<String>, failure_half_life: Duration) -> Self {
+        assert!(!backends.is_empty(), "at least one backend is required");
+        let now = Instant::now();
+        let health = backends
+            .iter()
+            .map(|backend| {
+                BACKEND_HEALTHY.with_label_values(&[backend]).set(1);
+                BackendHealth {
+                    failure_score: 0.0,
+                    scored_at: now,
+                    latency_secs: None,
+                    current_weight: 0.0,
+                    healthy: true,
+                }
+            })
+            .collect();
+        BackendSelector {
+            backends,
+            failure_half_life,
+            health: Mutex::new(health),
+        }
+    }
+
+    /// Picks the backend for the next request.
+    pub fn select(&self) -> &str {
+        if self.backends.len() == 1 {
+            return &self.backends[0];
+        }
+
+        let mut health = self.health.lock().unwrap();
+        let now = Instant::now();
+        for (i, entry) in health.iter_mut().enumerate() {
+            self.decay(&self.backends[i], entry, now);
+        }
+
+        // Backends without a latency sample yet are assumed as fast as the fastest known one
+        let fastest = health
+            .iter()
+            .filter_map(|entry| entry.latency_secs)
+            .fold(None, |fastest: Option<f64>, l| {
+                Some(fastest.map_or(l, |f| f.min(l)))
+            })
+            .unwrap_or(1.0);
+
+        let weights: Vec<f64> = health
+            .iter()
+            .map(|entry| {
+                let latency = entry.latency_secs.unwrap_or(fastest).max(MIN_LATENCY_SECS);
+                1.0 / (latency * (1.0 + FAILURE_PENALTY * entry.failure_score))
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+
+        for (entry, weight) in health.iter_mut().zip(&weights) {
+            entry.current_weight += weight;
+        }
+        let chosen = (0..health.len())
+            .max_by(|&a, &b| {
+                health[a]
+                    .current_weight
+                    .total_cmp(&health[b].current_weight)
+            })
+            .unwrap_or(0);
+        health[chosen].current_weight -= total;
+
+        &self.backends[chosen]
+    }
+
+    /// Records a successful response and how long the backend took to start answering.
+    pub fn record_success(&self, backend: &str, latency: Duration) {
+        self.update(backend, |entry| {
+            let sample = latency.as_secs_f64();
+            entry.latency_secs = Some(match entry.latency_secs {
+                Some(average) => average + LATENCY_ALPHA * (sample - average),
+                None => sample,
+            });
+        });
+    }
+
+    /// Records a transport error or server error from `backend`.
+    pub fn record_failure(&self, backend: &str) {
+        self.update(backend, |entry| entry.failure_score += 1.0);
+    }
+
+    /// Whether `backend`'s recent failures have decayed below the unhealthy threshold.
+    pub fn is_healthy(&self, backend: &str) -> bool {
+        let Some(i) = self.position(backend) else {
+            return false;
+        };
+        let mut health = self.health.lock().unwrap();
+        self.decay(backend, &mut health[i], Instant::now());
+        health[i].healthy
+    }
+
+    fn position(&self, backend: &str) -> Option<usize> {
+        self.backends.iter().position(|b| b == backend)
+    }
+
+    fn update(&self, backend: &str, apply: impl FnOnce(&mut BackendHealth)) {
+        let Some(i) = self.position(backend) else {
+            return;
+        };
+        let mut health = self.health.lock().unwrap();
+        let entry = &mut health[i];
+        self.decay(backend, entry, Instant::now());
+        apply(entry);
+        self.refresh_healthy(backend, entry);
+    }
+
+    fn decay(&self, backend: &str, entry: &mut BackendHealth, now: Instant) {
+        let elapsed = now.duration_since(entry.scored_at).as_secs_f64();
+        let half_life = self.failure_half_life.as_secs_f64();
+        entry.failure_score = if half_life > 0.0 {
+            entry.failure_score * 0.5_f64.powf(elapsed / half_life)
+        } else {
+            0.0
+        };
+        entry.scored_at = now;
+        self.refresh_healthy(backend, entry);
+    }
+
+    fn refresh_healthy(&self, backend: &str, entry: &mut BackendHealth) {
+        let healthy = entry.failure_score < UNHEALTHY_SCORE;
+        if healthy == entry.healthy {
+            return;
+        }
+        entry.healthy = healthy;
+        if healthy {
+            info!("Backend {} is healthy again", backend);
+        } else {
+            warn!(
+                "Backend {} is failing, reducing its share of traffic",
+                backend
+            );
+        }
+        BACKEND_HEALTHY
+            .with_label_values(&[backend])
+            .set(i64::from(healthy));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(half_life: Duration) -> BackendSelector {
+        BackendSelector::new(
+            vec!["http://a".to_string(), "http://b".to_string()],
+            half_life,
+        )
+    }
+
+    fn share_of_a(selector: &BackendSelector, picks: usize) -> f64 {
+        let a = (0..picks)
+            .filter(|_| selector.select() == "http://a")
+            .count();
+        a as f64 / picks as f64
+    }
+
+    #[test]
+    fn test_equal_backends_share_traffic() {
+        let selector = selector(Duration::from_secs(60));
+        assert_eq!(share_of_a(&selector, 100), 0.5);
+    }
+
+    #[test]
+    fn test_failures_shift_traffic_away() {
+        let selector = selector(Duration::from_secs(60));
+        selector.record_failure("http://b");
+
+        assert!(!selector.is_healthy("http://b"));
+        assert!(selector.is_healthy("http://a"));
+        assert!(share_of_a(&selector, 100) > 0.9);
+    }
+
+    #[test]
+    fn test_faster_backend_gets_more_traffic() {
+        let selector = selector(Duration::from_secs(60));
+        selector.record_success("http://a", Duration::from_millis(100));
+        selector.record_success("http://b", Duration::from_millis(300));
+
+        let share = share_of_a(&selector, 100);
+        assert!((0.7..0.8).contains(&share), "share was {share}");
+    }
+
+    #[test]
+    fn test_failed_backend_gradually_restored() {
+        let selector = selector(Duration::from_millis(50));
+        selector.record_failure("http://b");
+        let degraded = 1.0 - share_of_a(&selector, 100);
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(selector.is_healthy("http://b"));
+        let recovering = 1.0 - share_of_a(&selector, 100);
+        assert!(recovering > degraded);
+    }
+
+    #[test]
+    fn test_single_backend_always_selected() {
+        let selector = BackendSelector::new(vec!["http://only".to_string()], Duration::ZERO);
+        selector.record_failure("http://only");
+        assert_eq!(selector.select(), "http://only");
+    }
+}
@@ -0,0 +1,37 @@
+//! Rejects request bodies whose declared `Content-Length` exceeds `MAX_REQUEST_BYTES`.
+//!
+//! This answers before any of the body is read, so an oversized upload costs nothing beyond its
+//! headers. Chunked bodies, which declare no length, are still capped by the body limit as the
+//! extractor reads them.
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::AppError;
+
+pub async fn reject_oversized_bodies(
+    State(limit): State<usize>,
+    req: Request,
+    next: Next,
+) -> Response {
+    // The limit applies to the decoded body, which a compressed length says nothing about
+    if req.headers().contains_key(header::CONTENT_ENCODING) {
+        return next.run(req).await;
+    }
+
+    let length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    match length {
+        Some(length) if length > limit as u64 => {
+            AppError::PayloadTooLarge { length, limit }.into_response()
+        }
+        _ => next.run(req).await,
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::metrics::CIRCUIT_STATE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Value reported on the `mistral_circuit_state` gauge.
+    fn gauge_value(self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::Open => 1,
+            CircuitState::HalfOpen => 2,
+        }
+    }
+}
+
+struct Inner {
+    state: CircuitState,
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+    /// Set while a half-open probe is outstanding; a probe that never reports back (e.g. its
+    /// request was cancelled) is superseded after another cooldown.
+    probe_started: Option<Instant>,
+}
+
+/// Stops sending requests to a backend that keeps failing.
+///
+/// After `failure_threshold` consecutive failures within `failure_window` the circuit opens and
+/// requests are rejected until `cooldown` has passed. A single probe is then let through; its
+/// outcome closes the circuit again or reopens it for another cooldown.
+pub struct CircuitBreaker {
+    backend: String,
+    failure_threshold: usize,
+    failure_window: Duration,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    /// A `failure_threshold` of 0 disables the breaker.
+    pub fn new(
+        backend: &str,
+        failure_threshold: usize,
+        failure_window: Duration,
+        cooldown: Duration,
+    ) -> Self {
+        CIRCUIT_STATE
+            .with_label_values(&[backend])
+            .set(CircuitState::Closed.gauge_value());
+        CircuitBreaker {
+            backend: backend.to_string(),
+            failure_threshold,
+            failure_window,
+            cooldown,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: VecDeque::new(),
+                opened_at: None,
+                probe_started: None,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.inner.lock().unwrap().state
+    }
+
+    /// Returns whether a request may be sent, or the time left before the next probe.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        if self.failure_threshold == 0 {
+            return Ok(());
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = inner.opened_at.map(|t| t.elapsed()).unwrap_or_default();
+                if elapsed < self.cooldown {
+                    return Err(self.cooldown - elapsed);
+                }
+                self.transition(&mut inner, CircuitState::HalfOpen);
+                inner.probe_started = Some(Instant::now());
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                if let Some(started) = inner.probe_started {
+                    let elapsed = started.elapsed();
+                    if elapsed < self.cooldown {
+                        return Err(self.cooldown - elapsed);
+                    }
+                }
+                inner.probe_started = Some(Instant::now());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.failures.clear();
+        inner.probe_started = None;
+        if inner.state != CircuitState::Closed {
+            self.transition(&mut inner, CircuitState::Closed);
+        }
+    }
+
+    pub fn record_failure(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        inner.probe_started = None;
+
+        match inner.state {
+            CircuitState::HalfOpen => self.open(&mut inner, now),
+            CircuitState::Open => {}
+            CircuitState::Closed => {
+                inner.failures.push_back(now);
+                while let Some(&oldest) = inner.failures.front() {
+                    if now.duration_since(oldest) <= self.failure_window {
+                        break;
+                    }
+                    inner.failures.pop_front();
+                }
+                if inner.failures.len() >= self.failure_threshold {
+                    self.open(&mut inner, now);
+                }
+            }
+        }
+    }
+
+    fn open(&self, inner: &mut Inner, now: Instant) {
+        inner.opened_at = Some(now);
+        inner.failures.clear();
+        self.transition(inner, CircuitState::Open);
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        match state {
+            CircuitState::Open => warn!(
+                "Circuit for {} opened, rejecting requests for {:?}",
+                self.backend, self.cooldown
+            ),
+            _ => info!("Circuit for {} is now {:?}", self.backend, state),
+        }
+        inner.state = state;
+        CIRCUIT_STATE
+            .with_label_values(&[&self.backend])
+            .set(state.gauge_value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new("http://test", 3, Duration::from_secs(60), cooldown)
+    }
+
+    #[test]
+    fn test_opens_after_threshold_failures() {
+        let breaker = breaker(Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.try_acquire().is_ok());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = breaker(Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_allows_single_probe() {
+        let breaker = breaker(Duration::from_millis(50));
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        std::thread::sleep(Duration::from_millis(60));
+
+        assert!(breaker.try_acquire().is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_on_success() {
+        let breaker = breaker(Duration::ZERO);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        assert!(breaker.try_acquire().is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe_reopens_on_failure() {
+        let breaker = breaker(Duration::ZERO);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        assert!(breaker.try_acquire().is_ok());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_breaker() {
+        let breaker = CircuitBreaker::new("http://test", 0, Duration::ZERO, Duration::ZERO);
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+
+        assert!(breaker.try_acquire().is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
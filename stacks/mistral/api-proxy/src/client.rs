@@ -0,0 +1,82 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use reqwest::Client;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Builds the HTTP client used for all backend traffic, identifying the proxy to the backend.
+///
+/// The client has no overall timeout; each call applies the sync or streaming limit instead.
+pub fn build_client(config: &Config) -> reqwest::Result<Client> {
+    info!(
+        "Backend client: sync_timeout={:?}, stream_timeout={:?}, pool_max_idle_per_host={}, pool_idle_timeout={:?}, tcp_nodelay={}",
+        config.sync_request_timeout(),
+        config.stream_total_timeout(),
+        config.pool_max_idle_per_host,
+        config.pool_idle_timeout(),
+        config.tcp_nodelay
+    );
+
+    Client::builder()
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout())
+        .tcp_nodelay(config.tcp_nodelay)
+        .user_agent(config.user_agent.as_str())
+        .default_headers(forward_headers(&config.backend_forward_headers))
+        .build()
+}
+
+fn forward_headers(headers: &[(String, String)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let parsed = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        );
+        match parsed {
+            (Ok(name), Ok(mut value)) => {
+                // Credentials the operator chose to forward are still kept out of debug output
+                if name == AUTHORIZATION || name == COOKIE {
+                    value.set_sensitive(true);
+                }
+                map.insert(name, value);
+            }
+            _ => warn!("Ignoring invalid backend header {:?}", name),
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_headers_skips_invalid_entries() {
+        let headers = forward_headers(&[
+            ("X-Proxy-Id".to_string(), "edge-1".to_string()),
+            ("Bad Header".to_string(), "x".to_string()),
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+        ]);
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-proxy-id"], "edge-1");
+        assert!(headers[AUTHORIZATION].is_sensitive());
+    }
+
+    #[test]
+    fn test_build_client_with_pool_settings() {
+        let mut config = Config::from_env();
+        for (max_idle, idle_timeout_secs, nodelay) in [
+            (0, 0, false),
+            (1, 1, true),
+            (64, 90, true),
+            (usize::MAX, 3600, false),
+        ] {
+            config.pool_max_idle_per_host = max_idle;
+            config.pool_idle_timeout_secs = idle_timeout_secs;
+            config.tcp_nodelay = nodelay;
+            assert!(build_client(&config).is_ok());
+        }
+    }
+}
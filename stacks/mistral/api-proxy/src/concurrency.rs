@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics::{AVAILABLE_PERMITS, PERMIT_WAIT_SECONDS};
+
+/// Caps how many completions are in flight against the backend at once.
+///
+/// Requests over the limit wait for a permit rather than being rejected; the wait time and the
+/// remaining permits are exported so operators can see when the proxy is saturated.
+pub struct ConcurrencyLimit {
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+impl ConcurrencyLimit {
+    /// A limit of 0 disables it.
+    pub fn new(max_concurrent: usize) -> Self {
+        if max_concurrent > 0 {
+            AVAILABLE_PERMITS.set(max_concurrent as i64);
+        }
+        ConcurrencyLimit {
+            semaphore: (max_concurrent > 0).then(|| Arc::new(Semaphore::new(max_concurrent))),
+        }
+    }
+
+    /// Waits for a permit, which is held until the returned guard is dropped.
+    pub async fn acquire(&self) -> Option<ConcurrencyPermit> {
+        let semaphore = self.semaphore.as_ref()?;
+
+        let timer = PERMIT_WAIT_SECONDS.start_timer();
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("concurrency semaphore is never closed");
+        timer.observe_duration();
+        AVAILABLE_PERMITS.set(semaphore.available_permits() as i64);
+
+        Some(ConcurrencyPermit {
+            permit: Some(permit),
+            semaphore: semaphore.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    permit: Option<OwnedSemaphorePermit>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        // Release first so the gauge reflects the returned permit
+        drop(self.permit.take());
+        AVAILABLE_PERMITS.set(self.semaphore.available_permits() as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disabled_limit_grants_no_permit() {
+        assert!(ConcurrencyLimit::new(0).acquire().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_permit_released_on_drop() {
+        let limit = ConcurrencyLimit::new(1);
+        let permit = limit.acquire().await.unwrap();
+        assert_eq!(limit.semaphore.as_ref().unwrap().available_permits(), 0);
+
+        drop(permit);
+        assert_eq!(limit.semaphore.as_ref().unwrap().available_permits(), 1);
+    }
+}
@@ -1,52 +1,825 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use std::time::Duration;
 
+use axum::http::HeaderMap;
+
+use crate::listener::BindAddress;
+use crate::metrics::{self, ModelLabels};
+use crate::response_headers;
+
+#[derive(Clone)]
 pub struct Config {
     pub mistral_url: String,
+    pub backend_urls: Vec<String>,
+    pub backend_failure_half_life_secs: f64,
+    pub backend_api_prefix: String,
+    pub backend_kind: String,
+    pub backend_completions_endpoint: bool,
+    pub backend_supports_stream_usage: bool,
+    /// Name the seed is sent under: Mistral's `random_seed`, or `seed` for OpenAI-compatible
+    /// servers.
+    pub backend_seed_field: String,
     pub bind_address: String,
     pub request_timeout_secs: u64,
+    pub sync_request_timeout_secs: u64,
+    pub stream_total_timeout_secs: u64,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    pub tcp_nodelay: bool,
     pub channel_buffer_size: usize,
+    pub max_concurrent_requests: usize,
     pub max_line_length: usize,
     pub cors_allowed_origins: Vec<String>,
+    pub cors_allow_credentials: bool,
+    pub max_request_bytes: usize,
+    pub log_format: LogFormat,
+    pub log_level: tracing::Level,
+    /// Keep prompt and reply text, including backend error bodies that may quote it, out of logs.
+    pub log_redact_prompts: bool,
+    /// Ollama model names and the backend models that serve them.
+    pub model_map: HashMap<String, String>,
+    /// Backend `finish_reason` values and the `done_reason` reported for each, on top of the
+    /// built-in ones.
+    pub done_reason_map: HashMap<String, String>,
+    pub system_prompts: HashMap<String, String>,
+    /// Per-model option values used where the client doesn't set them.
+    pub model_defaults: HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    /// Per-model templates for rendering conversations into raw completions prompts.
+    pub prompt_templates: HashMap<String, String>,
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+    pub max_tokens_cap: i32,
+    pub max_output_tokens: Option<i32>,
+    pub context_cache_size: usize,
+    /// Chat sessions kept for `X-Session-Id`; 0 disables sessions.
+    pub session_cache_size: usize,
+    pub max_session_messages: usize,
+    pub max_history_messages: Option<usize>,
+    pub max_stream_chunks: usize,
+    pub stream_idle_timeout_secs: u64,
+    pub stream_keepalive_secs: f64,
+    pub models_cache_ttl_secs: u64,
+    /// Budget for each attempt at listing the backend's models, kept short so clients
+    /// waiting on `/api/tags` fall back to the default list quickly.
+    pub models_list_timeout_secs: f64,
+    pub models_list_retries: u32,
+    /// How long deterministic completions are cached; 0 disables the response cache.
+    pub response_cache_ttl_secs: u64,
+    pub response_cache_size: usize,
+    pub readiness_cache_secs: u64,
+    pub readiness_require_warmup: bool,
+    pub warmup_models: Vec<String>,
+    pub keepalive_model: Option<String>,
+    pub keepalive_interval_secs: u64,
+    pub pull_coordinator_url: Option<String>,
+    pub default_model: Option<String>,
+    pub default_model_aliases: Vec<String>,
+    /// Ollama model names clients may request; `None` allows every model.
+    pub allowed_models: Option<Vec<String>>,
+    /// Ollama model names that accept images.
+    pub multimodal_models: Vec<String>,
+    pub circuit_failure_threshold: usize,
+    pub circuit_failure_window_secs: u64,
+    pub circuit_cooldown_secs: u64,
+    pub metrics_auth_token: Option<String>,
+    pub model_rate_limits: HashMap<String, f64>,
+    pub user_agent: String,
+    pub backend_forward_headers: Vec<(String, String)>,
+    pub forward_headers: Vec<String>,
+    pub expose_backend_header: bool,
+    /// Serve `/api/generate`; disabled routes answer 404 like any unknown path.
+    pub enable_generate: bool,
+    /// Serve `/api/chat`.
+    pub enable_chat: bool,
+    /// Serve the model management routes: copy, create, blobs, delete and pull.
+    pub enable_model_management: bool,
+    /// Honour `X-Include-Raw`, which returns the backend's untranslated reply, internal fields
+    /// and all, alongside the Ollama one.
+    pub debug_allow_raw: bool,
+    /// Headers added to every non-streaming response.
+    pub response_headers: HeaderMap,
+    pub metric_model_labels: ModelLabels,
+    /// Bucket boundaries replacing a histogram's defaults, keyed by setting name such as
+    /// `DECODE_DURATION_BUCKETS`.
+    pub histogram_buckets: HashMap<String, Vec<f64>>,
+    pub otel_endpoint: Option<String>,
+    pub otel_service_name: String,
+    /// Settings that couldn't be parsed and fell back to their defaults.
+    pub invalid_settings: Vec<String>,
+}
+
+/// Every problem `Config::validate` found, one per line.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Raw setting values, read from the environment with a config file as fallback.
+///
+/// File keys use the same names as the environment variables, in any case, so `mistral_url`
+/// in the file and `MISTRAL_URL` in the environment configure the same setting.
+#[derive(Default)]
+struct Settings {
+    /// Environment variables, which take precedence over the file.
+    env: HashMap<String, String>,
+    file: HashMap<String, String>,
+    /// Values that were set but couldn't be parsed, described for `Config::validate`.
+    invalid: RefCell<Vec<String>>,
+}
+
+impl Settings {
+    fn get(&self, key: &str) -> Option<String> {
+        self.env.get(key).or_else(|| self.file.get(key)).cloned()
+    }
+
+    /// The setting parsed as `T`, or `None` if it is unset or invalid. Invalid values are
+    /// recorded so startup can report them instead of quietly using the default.
+    fn parse<T>(&self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.get(key)?;
+        match value.trim().parse() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.invalid
+                    .borrow_mut()
+                    .push(format!("{key}={value:?} is invalid: {e}"));
+                None
+            }
+        }
+    }
+
+    /// The JSON file the setting names, deserialized, or `None` if it is unset or can't be
+    /// read or parsed.
+    fn json_file<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let path = self.get(key)?;
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read it: {e}"))
+            .and_then(|contents| {
+                serde_json::from_str(&contents).map_err(|e| format!("invalid JSON: {e}"))
+            });
+        match parsed {
+            Ok(value) => Some(value),
+            Err(reason) => {
+                self.invalid(key, &path, &reason);
+                None
+            }
+        }
+    }
+
+    /// The validated headers in the JSON file the setting names; none if it is unset or invalid.
+    fn response_headers(&self, key: &str) -> HeaderMap {
+        let Some(headers) = self.json_file(key) else {
+            return HeaderMap::new();
+        };
+        response_headers::header_map(&headers).unwrap_or_else(|e| {
+            self.invalid(key, &self.get(key).unwrap_or_default(), &e);
+            HeaderMap::new()
+        })
+    }
+
+    /// The setting as histogram bucket boundaries, or `None` if it is unset or invalid.
+    fn buckets(&self, key: &str) -> Option<Vec<f64>> {
+        let value = self.get(key)?;
+        let buckets = metrics::parse_buckets(&value);
+        if buckets.is_none() {
+            self.invalid(
+                key,
+                &value,
+                "must be comma-separated, strictly increasing numbers",
+            );
+        }
+        buckets
+    }
+
+    fn invalid(&self, key: &str, value: &str, reason: &str) {
+        self.invalid
+            .borrow_mut()
+            .push(format!("{key}={value:?} is invalid: {reason}"));
+    }
+}
+
+/// Flattens a TOML table into setting strings; arrays become the comma-separated form the
+/// list-valued environment variables use.
+fn toml_settings(table: toml::Table) -> HashMap<String, String> {
+    fn to_setting(value: toml::Value) -> String {
+        match value {
+            toml::Value::String(s) => s,
+            toml::Value::Array(values) => values
+                .into_iter()
+                .map(to_setting)
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        }
+    }
+
+    table
+        .into_iter()
+        .map(|(key, value)| (key.to_uppercase(), to_setting(value)))
+        .collect()
 }
 
 impl Config {
+    /// Reads configuration from the environment, layered over the TOML file named by
+    /// `CONFIG_FILE` when that is set.
     pub fn from_env() -> Self {
+        match env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(&path),
+            Err(_) => Self::from_settings(&Settings {
+                env: env::vars().collect(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Reads configuration from a TOML file, with environment variables taking precedence.
+    /// A file that can't be read or parsed is reported by `validate`.
+    pub fn from_file(path: &str) -> Self {
+        let settings = Settings {
+            env: env::vars().collect(),
+            ..Default::default()
+        };
+        let table = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read it: {e}"))
+            .and_then(|contents| {
+                toml::from_str::<toml::Table>(&contents).map_err(|e| format!("invalid TOML: {e}"))
+            });
+        match table {
+            Ok(table) => Self::from_settings(&Settings {
+                file: toml_settings(table),
+                ..settings
+            }),
+            Err(reason) => {
+                settings.invalid("CONFIG_FILE", path, &reason);
+                Self::from_settings(&settings)
+            }
+        }
+    }
+
+    fn from_settings(settings: &Settings) -> Self {
+        // Default for both the sync and streaming timeouts when they aren't set separately
+        let request_timeout_secs = settings.parse("REQUEST_TIMEOUT_SECS").unwrap_or(300);
+
         Config {
-            mistral_url: env::var("MISTRAL_URL")
-                .unwrap_or_else(|_| "http://mistral:8080".to_string()),
-            bind_address: env::var("BIND_ADDRESS").unwrap_or_else(|_| "0.0.0.0:11434".to_string()),
-            request_timeout_secs: env::var("REQUEST_TIMEOUT_SECS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(300),
-            channel_buffer_size: env::var("CHANNEL_BUFFER_SIZE")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(100),
-            max_line_length: env::var("MAX_LINE_LENGTH")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(1_000_000), // 1MB default max line length
-            cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
-                .ok()
+            mistral_url: settings
+                .get("MISTRAL_URL")
+                .unwrap_or_else(|| "http://mistral:8080".to_string()),
+            // Comma-separated backends completions are spread across; unset means MISTRAL_URL only
+            backend_urls: settings
+                .get("BACKEND_URLS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|url| url.trim().trim_end_matches('/').to_string())
+                        .filter(|url| !url.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            backend_failure_half_life_secs: settings
+                .parse("BACKEND_FAILURE_HALF_LIFE_SECS")
+                .unwrap_or(30.0),
+            backend_api_prefix: settings
+                .get("BACKEND_API_PREFIX")
+                .and_then(|prefix| {
+                    parse_api_prefix(&prefix)
+                        .map_err(|e| settings.invalid("BACKEND_API_PREFIX", &prefix, &e))
+                        .ok()
+                })
+                .unwrap_or_else(|| "/v1".to_string()),
+            // Reported by /api/version; the proxy speaks Mistral's API to any backend kind
+            backend_kind: settings
+                .get("BACKEND_KIND")
+                .unwrap_or_else(|| "mistral".to_string()),
+            // Whether the backend serves the plain-text /completions endpoint used by raw generate
+            backend_completions_endpoint: settings
+                .parse("BACKEND_COMPLETIONS_ENDPOINT")
+                .unwrap_or(false),
+            // Some OpenAI-compatible servers reject `stream_options`; their token counts are
+            // estimated instead
+            backend_supports_stream_usage: settings
+                .parse("BACKEND_SUPPORTS_STREAM_USAGE")
+                .unwrap_or(true),
+            backend_seed_field: settings
+                .get("BACKEND_SEED_FIELD")
+                .map(|field| field.trim().to_string())
+                .unwrap_or_else(|| DEFAULT_SEED_FIELD.to_string()),
+            bind_address: settings
+                .get("BIND_ADDRESS")
+                .unwrap_or_else(|| "0.0.0.0:11434".to_string()),
+            request_timeout_secs,
+            sync_request_timeout_secs: settings
+                .parse("SYNC_REQUEST_TIMEOUT_SECS")
+                .unwrap_or(request_timeout_secs), // 0 disables the timeout
+            stream_total_timeout_secs: settings
+                .parse("STREAM_TOTAL_TIMEOUT_SECS")
+                .unwrap_or(request_timeout_secs), // 0 disables the timeout
+            pool_max_idle_per_host: settings
+                .parse("POOL_MAX_IDLE_PER_HOST")
+                .unwrap_or(usize::MAX), // reqwest's default: no limit
+            pool_idle_timeout_secs: settings.parse("POOL_IDLE_TIMEOUT_SECS").unwrap_or(90), // 0 keeps idle connections open indefinitely
+            tcp_nodelay: settings.parse("TCP_NODELAY").unwrap_or(true),
+            channel_buffer_size: settings.parse("CHANNEL_BUFFER_SIZE").unwrap_or(100),
+            max_concurrent_requests: settings.parse("MAX_CONCURRENT_REQUESTS").unwrap_or(0), // 0 leaves concurrency unlimited
+            max_line_length: settings.parse("MAX_LINE_LENGTH").unwrap_or(1_000_000), // 1MB default max line length
+            cors_allowed_origins: settings
+                .get("CORS_ALLOWED_ORIGINS")
                 .map(|s| {
                     s.split(',')
                         .map(|origin| origin.trim().to_string())
                         .collect()
                 })
                 .unwrap_or_else(|| vec!["http://localhost:3000".to_string()]), // Default to Grafana
+            cors_allow_credentials: settings.parse("CORS_ALLOW_CREDENTIALS").unwrap_or(false),
+            max_request_bytes: settings
+                .parse("MAX_REQUEST_BYTES")
+                .unwrap_or(10 * 1024 * 1024), // 10MB default max request body
+            log_format: match settings.get("LOG_FORMAT").as_deref() {
+                Some("json") => LogFormat::Json,
+                None | Some("text") => LogFormat::Text,
+                Some(other) => {
+                    settings.invalid("LOG_FORMAT", other, "expected \"text\" or \"json\"");
+                    LogFormat::Text
+                }
+            },
+            log_level: settings.parse("LOG_LEVEL").unwrap_or(tracing::Level::INFO),
+            log_redact_prompts: settings.parse("LOG_REDACT_PROMPTS").unwrap_or(false),
+            // A configured map replaces the built-in names rather than extending them
+            model_map: settings
+                .json_file("MODEL_MAP")
+                .unwrap_or_else(default_model_map),
+            done_reason_map: settings.json_file("DONE_REASON_MAP").unwrap_or_default(),
+            system_prompts: settings.json_file("SYSTEM_PROMPTS").unwrap_or_default(),
+            model_defaults: settings.json_file("MODEL_DEFAULTS").unwrap_or_default(),
+            prompt_templates: settings.json_file("PROMPT_TEMPLATES").unwrap_or_default(),
+            min_temperature: settings.parse("MIN_TEMPERATURE").unwrap_or(0.0),
+            max_temperature: settings.parse("MAX_TEMPERATURE").unwrap_or(2.0),
+            max_tokens_cap: settings.parse("MAX_TOKENS_CAP").unwrap_or(32_768),
+            // Unlike MAX_TOKENS_CAP this also limits requests that don't set max_tokens
+            max_output_tokens: settings.parse("MAX_OUTPUT_TOKENS"),
+            context_cache_size: settings.parse("CONTEXT_CACHE_SIZE").unwrap_or(1000),
+            session_cache_size: settings.parse("SESSION_CACHE_SIZE").unwrap_or(1000),
+            max_session_messages: settings.parse("MAX_SESSION_MESSAGES").unwrap_or(100),
+            // Unset forwards conversations of any length
+            max_history_messages: settings.parse("MAX_HISTORY_MESSAGES"),
+            max_stream_chunks: settings.parse("MAX_STREAM_CHUNKS").unwrap_or(0), // 0 means unlimited
+            stream_idle_timeout_secs: settings.parse("STREAM_IDLE_TIMEOUT_SECS").unwrap_or(120), // 0 disables the idle timeout
+            stream_keepalive_secs: settings.parse("STREAM_KEEPALIVE_SECS").unwrap_or(15.0), // 0 disables keepalive comments
+            models_cache_ttl_secs: settings.parse("MODELS_CACHE_TTL_SECS").unwrap_or(30),
+            models_list_timeout_secs: settings.parse("MODELS_LIST_TIMEOUT_SECS").unwrap_or(3.0), // 0 uses the client's REQUEST_TIMEOUT_SECS
+            models_list_retries: settings.parse("MODELS_LIST_RETRIES").unwrap_or(1),
+            response_cache_ttl_secs: settings.parse("RESPONSE_CACHE_TTL_SECS").unwrap_or(0),
+            response_cache_size: settings.parse("RESPONSE_CACHE_SIZE").unwrap_or(1000),
+            readiness_cache_secs: settings.parse("READINESS_CACHE_SECS").unwrap_or(5),
+            readiness_require_warmup: settings.parse("READINESS_REQUIRE_WARMUP").unwrap_or(false),
+            warmup_models: settings
+                .get("WARMUP_MODELS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|model| model.trim().to_string())
+                        .filter(|model| !model.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            keepalive_model: settings.get("KEEPALIVE_MODEL"),
+            keepalive_interval_secs: settings.parse("KEEPALIVE_INTERVAL_SECS").unwrap_or(300), // 0 disables the keepalive task
+            // Unset answers /api/pull immediately, since the backend manages its own models
+            pull_coordinator_url: settings
+                .get("PULL_COORDINATOR_URL")
+                .filter(|url| !url.is_empty()),
+            default_model: settings
+                .get("DEFAULT_MODEL")
+                .filter(|model| !model.is_empty()),
+            // Placeholder names that mean "whatever the default is"
+            default_model_aliases: settings
+                .get("DEFAULT_MODEL_ALIASES")
+                .map(|s| {
+                    s.split(',')
+                        .map(|alias| alias.trim().to_string())
+                        .filter(|alias| !alias.is_empty())
+                        .collect()
+                })
+                .unwrap_or_else(|| vec!["default".to_string()]),
+            allowed_models: settings.get("ALLOWED_MODELS").map(|s| {
+                s.split(',')
+                    .map(|model| model.trim().to_string())
+                    .filter(|model| !model.is_empty())
+                    .collect()
+            }),
+            multimodal_models: settings
+                .get("MULTIMODAL_MODELS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|model| model.trim().to_string())
+                        .filter(|model| !model.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            circuit_failure_threshold: settings.parse("CIRCUIT_FAILURE_THRESHOLD").unwrap_or(5), // 0 disables the circuit breaker
+            circuit_failure_window_secs: settings
+                .parse("CIRCUIT_FAILURE_WINDOW_SECS")
+                .unwrap_or(60),
+            circuit_cooldown_secs: settings.parse("CIRCUIT_COOLDOWN_SECS").unwrap_or(30),
+            metrics_auth_token: settings
+                .get("METRICS_AUTH_TOKEN")
+                .filter(|token| !token.is_empty()),
+            model_rate_limits: settings.json_file("MODEL_RATE_LIMITS").unwrap_or_default(),
+            user_agent: settings
+                .get("USER_AGENT")
+                .unwrap_or_else(|| format!("mistral-ollama-proxy/{}", env!("CARGO_PKG_VERSION"))),
+            // Comma-separated `Name=value` pairs; nothing is forwarded unless configured
+            backend_forward_headers: settings
+                .get("BACKEND_FORWARD_HEADERS")
+                .map(|s| {
+                    s.split(',')
+                        .filter_map(|pair| pair.split_once('='))
+                        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            // Comma-separated names of client headers to pass through to the backend
+            forward_headers: settings
+                .get("FORWARD_HEADERS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|name| name.trim().to_ascii_lowercase())
+                        .filter(|name| !name.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            expose_backend_header: settings.parse("EXPOSE_BACKEND_HEADER").unwrap_or(false),
+            debug_allow_raw: settings.parse("DEBUG_ALLOW_RAW").unwrap_or(false),
+            enable_generate: settings.parse("ENABLE_GENERATE").unwrap_or(true),
+            enable_chat: settings.parse("ENABLE_CHAT").unwrap_or(true),
+            enable_model_management: settings.parse("ENABLE_MODEL_MANAGEMENT").unwrap_or(true),
+            response_headers: settings.response_headers("RESPONSE_HEADERS"),
+            // Unset records every model name as a metric label
+            metric_model_labels: settings
+                .get("METRICS_MODEL_ALLOWLIST")
+                .map(|s| {
+                    ModelLabels::allowlist(
+                        s.split(',')
+                            .map(str::trim)
+                            .filter(|model| !model.is_empty()),
+                    )
+                })
+                .unwrap_or_else(ModelLabels::unrestricted),
+            histogram_buckets: metrics::BUCKET_SETTINGS
+                .iter()
+                .filter_map(|&key| Some((key.to_string(), settings.buckets(key)?)))
+                .collect(),
+            // Trace export is off unless an OTLP collector is configured
+            otel_endpoint: settings
+                .get("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .filter(|endpoint| !endpoint.is_empty()),
+            otel_service_name: settings
+                .get("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|| "mistral-ollama-proxy".to_string()),
+            invalid_settings: settings.invalid.take(),
         }
     }
 
-    pub fn request_timeout(&self) -> Duration {
-        Duration::from_secs(self.request_timeout_secs)
+    /// Checks for settings that were invalid or would misbehave, so startup can fail with all
+    /// of them listed rather than run on defaults the operator didn't choose.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = self.invalid_settings.clone();
+
+        let urls = std::iter::once(("MISTRAL_URL", &self.mistral_url))
+            .chain(self.backend_urls.iter().map(|url| ("BACKEND_URLS", url)))
+            .chain(
+                self.pull_coordinator_url
+                    .iter()
+                    .map(|url| ("PULL_COORDINATOR_URL", url)),
+            );
+        for (key, url) in urls {
+            if url.trim().is_empty() {
+                problems.push(format!("{key} is empty"));
+            } else if let Err(e) = reqwest::Url::parse(url) {
+                problems.push(format!("{key}={url:?} is not a valid URL: {e}"));
+            }
+        }
+
+        if self.backend_seed_field.is_empty() {
+            problems.push("BACKEND_SEED_FIELD is empty".to_string());
+        }
+
+        if let Err(e) = self.bind_address.parse::<BindAddress>() {
+            problems.push(format!(
+                "BIND_ADDRESS={:?} is not a valid address: {e}",
+                self.bind_address
+            ));
+        }
+
+        for (key, value) in [
+            ("CHANNEL_BUFFER_SIZE", self.channel_buffer_size),
+            ("MAX_LINE_LENGTH", self.max_line_length),
+            ("MAX_REQUEST_BYTES", self.max_request_bytes),
+        ] {
+            if value == 0 {
+                problems.push(format!("{key} must be greater than 0"));
+            }
+        }
+
+        if self.min_temperature > self.max_temperature {
+            problems.push(format!(
+                "MIN_TEMPERATURE={} is above MAX_TEMPERATURE={}",
+                self.min_temperature, self.max_temperature
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { problems })
+        }
+    }
+
+    /// Limit on a whole non-streaming backend call, including reading its response.
+    pub fn sync_request_timeout(&self) -> Option<Duration> {
+        (self.sync_request_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.sync_request_timeout_secs))
+    }
+
+    /// Limit on a whole streaming backend call, until its last chunk has been received.
+    pub fn stream_total_timeout(&self) -> Option<Duration> {
+        (self.stream_total_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.stream_total_timeout_secs))
+    }
+
+    /// Backends completions are sent to, defaulting to `MISTRAL_URL` alone.
+    pub fn completion_backends(&self) -> Vec<String> {
+        if self.backend_urls.is_empty() {
+            vec![self.mistral_url.clone()]
+        } else {
+            self.backend_urls.clone()
+        }
+    }
+
+    pub fn backend_failure_half_life(&self) -> Duration {
+        Duration::from_secs_f64(self.backend_failure_half_life_secs.max(0.0))
+    }
+
+    pub fn pool_idle_timeout(&self) -> Option<Duration> {
+        (self.pool_idle_timeout_secs > 0).then(|| Duration::from_secs(self.pool_idle_timeout_secs))
+    }
+
+    /// Chunks a single stream may emit before it is ended, if limited.
+    pub fn max_stream_chunks(&self) -> Option<usize> {
+        (self.max_stream_chunks > 0).then_some(self.max_stream_chunks)
+    }
+
+    pub fn stream_idle_timeout(&self) -> Option<Duration> {
+        (self.stream_idle_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.stream_idle_timeout_secs))
+    }
+
+    /// Model to keep loaded and how often to ping it, if the keepalive task is enabled.
+    pub fn model_keepalive(&self) -> Option<(String, Duration)> {
+        let model = self.keepalive_model.clone()?;
+        (self.keepalive_interval_secs > 0)
+            .then(|| (model, Duration::from_secs(self.keepalive_interval_secs)))
+    }
+
+    pub fn models_list_timeout(&self) -> Option<Duration> {
+        (self.models_list_timeout_secs > 0.0)
+            .then(|| Duration::from_secs_f64(self.models_list_timeout_secs))
+    }
+
+    pub fn response_cache_ttl(&self) -> Option<Duration> {
+        (self.response_cache_ttl_secs > 0)
+            .then(|| Duration::from_secs(self.response_cache_ttl_secs))
+    }
+
+    pub fn stream_keepalive(&self) -> Option<Duration> {
+        (self.stream_keepalive_secs > 0.0)
+            .then(|| Duration::from_secs_f64(self.stream_keepalive_secs))
+    }
+
+    pub fn temperature_range(&self) -> RangeInclusive<f32> {
+        self.min_temperature..=self.max_temperature
     }
 }
 
+/// Normalizes a backend API path prefix such as `/openai/v1`, dropping any trailing slash.
+///
+/// An empty prefix mounts the API at the backend URL's root.
+pub fn parse_api_prefix(value: &str) -> Result<String, String> {
+    let prefix = value.trim().trim_end_matches('/');
+    if !prefix.is_empty() && !prefix.starts_with('/') {
+        return Err("must start with '/'".to_string());
+    }
+    if prefix.contains(|c: char| c == '?' || c == '#' || c.is_whitespace()) {
+        return Err("must be a plain path without a query, fragment or whitespace".to_string());
+    }
+    Ok(prefix.to_string())
+}
+
+/// Field Mistral's API reads the sampling seed from.
+pub const DEFAULT_SEED_FIELD: &str = "random_seed";
+
+/// Ollama names for the backend's stock models, used unless `MODEL_MAP` is set.
+pub fn default_model_map() -> HashMap<String, String> {
+    [
+        ("mistral:latest", "mistral-7b"),
+        ("mistral:7b", "mistral-7b"),
+        ("mixtral:latest", "mixtral-8x7b"),
+        ("mixtral:8x7b", "mixtral-8x7b"),
+    ]
+    .into_iter()
+    .map(|(ollama, backend)| (ollama.to_string(), backend.to_string()))
+    .collect()
+}
+
 pub mod model_sizes {
     pub const MODEL_7B_SIZE: i64 = 4_100_000_000;
     pub const MODEL_8X7B_SIZE: i64 = 47_000_000_000;
     pub const MODEL_70B_SIZE: i64 = 40_000_000_000;
     pub const DEFAULT_MODEL_SIZE: i64 = MODEL_7B_SIZE;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> Config {
+        Config::from_settings(&Settings {
+            file: pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn problems(pairs: &[(&str, &str)]) -> Vec<String> {
+        config(pairs).validate().unwrap_err().problems
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert!(config(&[]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_unparseable_values_reported() {
+        let config = config(&[
+            ("REQUEST_TIMEOUT_SECS", "5m"),
+            ("TCP_NODELAY", "yes"),
+            ("LOG_FORMAT", "yaml"),
+        ]);
+        // The defaults still apply, but startup is refused
+        assert_eq!(config.request_timeout_secs, 300);
+
+        assert_eq!(
+            config.validate().unwrap_err().problems,
+            [
+                "REQUEST_TIMEOUT_SECS=\"5m\" is invalid: invalid digit found in string",
+                "TCP_NODELAY=\"yes\" is invalid: provided string was not `true` or `false`",
+                "LOG_FORMAT=\"yaml\" is invalid: expected \"text\" or \"json\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bad_urls_and_bind_address_reported() {
+        let problems = problems(&[
+            ("MISTRAL_URL", ""),
+            ("BACKEND_URLS", "http://ok:8080,not a url"),
+            ("BIND_ADDRESS", "localhost"),
+        ]);
+
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[0], "MISTRAL_URL is empty");
+        assert!(problems[1].starts_with("BACKEND_URLS=\"not a url\" is not a valid URL"));
+        assert!(problems[2].starts_with("BIND_ADDRESS=\"localhost\" is not a valid address"));
+    }
+
+    #[test]
+    fn test_zero_sizes_and_inverted_ranges_reported() {
+        let problems = problems(&[
+            ("CHANNEL_BUFFER_SIZE", "0"),
+            ("MAX_REQUEST_BYTES", "0"),
+            ("MIN_TEMPERATURE", "1.5"),
+            ("MAX_TEMPERATURE", "1.0"),
+        ]);
+
+        assert_eq!(
+            problems,
+            [
+                "CHANNEL_BUFFER_SIZE must be greater than 0",
+                "MAX_REQUEST_BYTES must be greater than 0",
+                "MIN_TEMPERATURE=1.5 is above MAX_TEMPERATURE=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_lists_every_problem() {
+        let error = config(&[("MISTRAL_URL", ""), ("CHANNEL_BUFFER_SIZE", "none")])
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid configuration:\n  \
+             - CHANNEL_BUFFER_SIZE=\"none\" is invalid: invalid digit found in string\n  \
+             - MISTRAL_URL is empty"
+        );
+    }
+
+    #[test]
+    fn test_bad_prefix_and_missing_files_reported() {
+        let problems = problems(&[
+            ("BACKEND_API_PREFIX", "v1"),
+            ("MODEL_MAP", "/nonexistent/model-map.json"),
+        ]);
+
+        assert_eq!(problems.len(), 2);
+        assert_eq!(
+            problems[0],
+            "BACKEND_API_PREFIX=\"v1\" is invalid: must start with '/'"
+        );
+        assert!(problems[1].starts_with(
+            "MODEL_MAP=\"/nonexistent/model-map.json\" is invalid: failed to read it"
+        ));
+    }
+
+    #[test]
+    fn test_bucket_overrides_parsed_and_bad_lists_reported() {
+        let config = config(&[
+            ("DECODE_DURATION_BUCKETS", "0.01, 0.1, 1"),
+            ("PREFILL_DURATION_BUCKETS", "1,0.5"),
+        ]);
+
+        assert_eq!(
+            config.histogram_buckets,
+            HashMap::from([("DECODE_DURATION_BUCKETS".to_string(), vec![0.01, 0.1, 1.0])])
+        );
+        assert_eq!(
+            config.validate().unwrap_err().problems,
+            ["PREFILL_DURATION_BUCKETS=\"1,0.5\" is invalid: must be comma-separated, strictly increasing numbers"]
+        );
+    }
+
+    #[test]
+    fn test_bad_response_headers_reported() {
+        let path = env::temp_dir().join(format!("response-headers-{}.json", std::process::id()));
+        fs::write(&path, r#"{"Content-Length": "10"}"#).unwrap();
+        let problems = problems(&[("RESPONSE_HEADERS", path.to_str().unwrap())]);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("RESPONSE_HEADERS="));
+    }
+
+    #[test]
+    fn test_unreadable_config_file_reported() {
+        let problems = Config::from_file("/nonexistent/proxy.toml")
+            .validate()
+            .unwrap_err()
+            .problems;
+
+        assert!(problems.iter().any(|problem| problem
+            .starts_with("CONFIG_FILE=\"/nonexistent/proxy.toml\" is invalid: failed to read it")));
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let config = Config::from_settings(&Settings {
+            env: HashMap::from([("CHANNEL_BUFFER_SIZE".to_string(), "7".to_string())]),
+            file: HashMap::from([
+                ("CHANNEL_BUFFER_SIZE".to_string(), "5".to_string()),
+                ("MAX_TOKENS_CAP".to_string(), "64".to_string()),
+            ]),
+            ..Default::default()
+        });
+
+        assert_eq!(config.channel_buffer_size, 7);
+        assert_eq!(config.max_tokens_cap, 64);
+    }
+}
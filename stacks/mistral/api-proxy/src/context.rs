@@ -0,0 +1,134 @@
+//! Server-side emulation of Ollama's generate `context` field.
+//!
+//! Ollama returns the raw token IDs of a conversation so a client can continue it by sending
+//! them back. Mistral doesn't expose tokens, so instead the proxy keeps the conversation's
+//! messages in memory and hands out a short opaque array derived from a hash of them. When a
+//! client sends that array back the stored messages are replayed as chat history.
+//!
+//! This is an approximation: the returned values are not real token IDs, so clients that
+//! inspect or count them will see nonsense, and contexts are lost when the proxy restarts or
+//! the entry is evicted.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use crate::models::mistral::MistralMessage;
+
+#[derive(Debug)]
+pub struct ContextStore {
+    capacity: usize,
+    inner: Mutex<ContextStoreInner>,
+}
+
+#[derive(Debug, Default)]
+struct ContextStoreInner {
+    entries: HashMap<Vec<i32>, Vec<MistralMessage>>,
+    // Insertion order, oldest first, used to evict when over capacity
+    order: VecDeque<Vec<i32>>,
+}
+
+impl ContextStore {
+    pub fn new(capacity: usize) -> Self {
+        ContextStore {
+            capacity,
+            inner: Mutex::new(ContextStoreInner::default()),
+        }
+    }
+
+    /// Stores `messages` and returns the surrogate context that retrieves them.
+    pub fn store(&self, messages: Vec<MistralMessage>) -> Vec<i32> {
+        let context = surrogate_context(&messages);
+        if self.capacity == 0 {
+            return context;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.entries.insert(context.clone(), messages).is_none() {
+            inner.order.push_back(context.clone());
+        }
+
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.entries.remove(&oldest);
+            }
+        }
+
+        context
+    }
+
+    pub fn retrieve(&self, context: &[i32]) -> Option<Vec<MistralMessage>> {
+        self.inner.lock().unwrap().entries.get(context).cloned()
+    }
+}
+
+fn surrogate_context(messages: &[MistralMessage]) -> Vec<i32> {
+    let mut hasher = DefaultHasher::new();
+    for message in messages {
+        message.role.hash(&mut hasher);
+        message.content.hash(&mut hasher);
+    }
+    let hash = hasher.finish();
+
+    vec![(hash >> 32) as i32, hash as i32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(reply: &str) -> Vec<MistralMessage> {
+        vec![
+            MistralMessage {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+                ..Default::default()
+            },
+            MistralMessage {
+                role: "assistant".to_string(),
+                content: reply.to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_store_and_retrieve() {
+        let store = ContextStore::new(10);
+        let context = store.store(conversation("Hi there"));
+
+        let messages = store.retrieve(&context).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].content, "Hi there");
+    }
+
+    #[test]
+    fn test_distinct_conversations_get_distinct_contexts() {
+        let store = ContextStore::new(10);
+        let first = store.store(conversation("one"));
+        let second = store.store(conversation("two"));
+
+        assert_ne!(first, second);
+        assert_eq!(store.retrieve(&first).unwrap()[1].content, "one");
+        assert_eq!(store.retrieve(&second).unwrap()[1].content, "two");
+    }
+
+    #[test]
+    fn test_unknown_context_returns_none() {
+        let store = ContextStore::new(10);
+        assert!(store.retrieve(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn test_oldest_context_evicted_over_capacity() {
+        let store = ContextStore::new(2);
+        let first = store.store(conversation("one"));
+        let second = store.store(conversation("two"));
+        let third = store.store(conversation("three"));
+
+        assert!(store.retrieve(&first).is_none());
+        assert!(store.retrieve(&second).is_some());
+        assert!(store.retrieve(&third).is_some());
+    }
+}
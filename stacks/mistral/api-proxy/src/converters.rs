@@ -1,56 +1,178 @@
 use chrono::Utc;
 use serde_json::json;
+use std::collections::HashMap;
 
-use crate::models::mistral::MistralChatResponse;
+use crate::error::{AppError, Result};
+use crate::models::mistral::{MistralChatResponse, MistralMessage, MistralUsage};
 use crate::models::ollama::{OllamaChatResponse, OllamaGenerateResponse, OllamaMessage};
 
+impl From<&MistralMessage> for OllamaMessage {
+    fn from(msg: &MistralMessage) -> Self {
+        OllamaMessage {
+            role: msg.role.clone(),
+            content: msg.content.clone(),
+            thinking: msg.reasoning_content.clone(),
+            tool_calls: msg.tool_calls.as_deref().map(convert_tool_calls),
+        }
+    }
+}
+
+/// Converts Mistral tool calls, from a message or a stream delta, to Ollama's shape.
+pub fn convert_tool_calls(calls: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    calls.iter().map(convert_tool_call).collect()
+}
+
+/// Mistral encodes tool call arguments as a JSON string; Ollama clients expect an object.
+fn convert_tool_call(call: &serde_json::Value) -> serde_json::Value {
+    let mut call = call.clone();
+    let parsed_arguments = call
+        .pointer("/function/arguments")
+        .and_then(|args| args.as_str())
+        .and_then(|args| serde_json::from_str::<serde_json::Value>(args).ok());
+
+    if let Some(arguments) = parsed_arguments {
+        call["function"]["arguments"] = arguments;
+    }
+    call
+}
+
+/// Returns the first choice's message, or an error explaining why the backend produced none.
+///
+/// An empty reply is never passed off as a successful completion, since it usually means the
+/// backend filtered or failed the request.
+fn first_message(mistral_response: &MistralChatResponse) -> Result<&MistralMessage> {
+    let Some(choice) = mistral_response.choices.first() else {
+        return Err(AppError::empty_completion("backend returned no choices"));
+    };
+
+    match (choice.content_message(), choice.finish_reason.as_deref()) {
+        (Some(message), _) => Ok(message),
+        (None, Some("content_filter")) => Err(AppError::ContentFiltered),
+        (None, Some("error")) => Err(AppError::empty_completion(
+            "backend reported an error while generating",
+        )),
+        (None, reason) => Err(AppError::empty_completion(&format!(
+            "backend returned a choice without a message (finish_reason: {})",
+            reason.unwrap_or("none")
+        ))),
+    }
+}
+
+/// The first choice's token log probabilities, if the backend returned any.
+fn logprobs(mistral_response: &MistralChatResponse) -> Option<serde_json::Value> {
+    mistral_response.choices.first()?.logprobs.clone()
+}
+
+/// `finish_reason` values backends are known to send, and the `done_reason` Ollama reports for
+/// each.
+const BUILTIN_DONE_REASONS: &[(&str, &str)] = &[
+    ("stop", "stop"),
+    ("eos", "stop"),
+    ("end_turn", "stop"),
+    ("stop_sequence", "stop"),
+    // Ollama finishes a tool-calling turn with a normal stop
+    ("tool_calls", "stop"),
+    ("function_call", "stop"),
+    ("length", "length"),
+    ("model_length", "length"),
+    ("max_tokens", "length"),
+];
+
+/// Normalizes backends' `finish_reason` strings to Ollama's `done_reason` set (`stop`,
+/// `length`, `load`), passing values it doesn't know through unchanged.
+#[derive(Debug, Clone)]
+pub struct DoneReasons {
+    map: HashMap<String, String>,
+}
+
+impl DoneReasons {
+    /// The built-in mapping, with `overrides` added on top.
+    pub fn new(overrides: &HashMap<String, String>) -> Self {
+        let mut map: HashMap<String, String> = BUILTIN_DONE_REASONS
+            .iter()
+            .map(|(finish_reason, done_reason)| {
+                (finish_reason.to_string(), done_reason.to_string())
+            })
+            .collect();
+        map.extend(overrides.clone());
+        DoneReasons { map }
+    }
+
+    pub fn normalize(&self, finish_reason: &str) -> String {
+        self.map
+            .get(finish_reason)
+            .cloned()
+            .unwrap_or_else(|| finish_reason.to_string())
+    }
+}
+
+impl Default for DoneReasons {
+    fn default() -> Self {
+        DoneReasons::new(&HashMap::new())
+    }
+}
+
+/// The `done_reason` Ollama reports for the first choice's `finish_reason`.
+fn done_reason(
+    mistral_response: &MistralChatResponse,
+    done_reasons: &DoneReasons,
+) -> Option<String> {
+    let finish_reason = mistral_response.choices.first()?.finish_reason.as_deref()?;
+    Some(done_reasons.normalize(finish_reason))
+}
+
 pub fn convert_mistral_to_ollama_chat(
     mistral_response: MistralChatResponse,
     model_name: String,
-) -> OllamaChatResponse {
-    let message = mistral_response
-        .choices
-        .first()
-        .and_then(|c| c.message.as_ref())
-        .map(|m| OllamaMessage {
-            role: m.role.clone(),
-            content: m.content.clone(),
-        })
-        .unwrap_or_else(|| OllamaMessage {
-            role: "assistant".to_string(),
-            content: String::new(),
-        });
+    done_reasons: &DoneReasons,
+) -> Result<OllamaChatResponse> {
+    let message = OllamaMessage::from(first_message(&mistral_response)?);
 
-    OllamaChatResponse {
+    // Ollama has a single message, so extra candidates ride along in an extension field
+    let choices = (mistral_response.choices.len() > 1).then(|| {
+        mistral_response
+            .choices
+            .iter()
+            .skip(1)
+            .filter_map(|c| c.content_message())
+            .map(OllamaMessage::from)
+            .collect()
+    });
+
+    Ok(OllamaChatResponse {
         model: model_name,
         created_at: Utc::now().to_rfc3339(),
         message,
+        choices,
         done: true,
+        done_reason: done_reason(&mistral_response, done_reasons),
+        logprobs: logprobs(&mistral_response),
         total_duration: None,
         load_duration: None,
         prompt_eval_count: mistral_response.usage.as_ref().map(|u| u.prompt_tokens),
         prompt_eval_duration: None,
         eval_count: mistral_response.usage.as_ref().map(|u| u.completion_tokens),
         eval_duration: None,
-    }
+    })
 }
 
 pub fn convert_mistral_to_ollama_generate(
     mistral_response: MistralChatResponse,
     model_name: String,
-) -> OllamaGenerateResponse {
-    let content = mistral_response
-        .choices
-        .first()
-        .and_then(|c| c.message.as_ref())
-        .map(|m| m.content.clone())
-        .unwrap_or_default();
-
-    OllamaGenerateResponse {
+    done_reasons: &DoneReasons,
+) -> Result<OllamaGenerateResponse> {
+    let message = first_message(&mistral_response)?;
+    let content = message.content.clone();
+    let thinking = message.reasoning_content.clone();
+
+    Ok(OllamaGenerateResponse {
         model: model_name,
         created_at: Utc::now().to_rfc3339(),
         response: content,
+        thinking,
         done: true,
+        done_reason: done_reason(&mistral_response, done_reasons),
+        logprobs: logprobs(&mistral_response),
         context: None,
         total_duration: None,
         load_duration: None,
@@ -58,48 +180,72 @@ pub fn convert_mistral_to_ollama_generate(
         prompt_eval_duration: None,
         eval_count: mistral_response.usage.as_ref().map(|u| u.completion_tokens),
         eval_duration: None,
-    }
+    })
 }
 
+/// Builds an Ollama stream chunk, with `thinking` included only when the delta carried reasoning.
+///
+/// `created_at` is captured once per response so every chunk of a stream reports the same time.
 pub fn create_streaming_chunk(
     model_name: &str,
+    created_at: &str,
     content: &str,
+    thinking: Option<&str>,
     role: &str,
     is_chat: bool,
 ) -> serde_json::Value {
     if is_chat {
-        json!({
+        let mut chunk = json!({
             "model": model_name,
-            "created_at": Utc::now().to_rfc3339(),
+            "created_at": created_at,
             "message": {
                 "role": role,
                 "content": content
             },
             "done": false
-        })
+        });
+        if let Some(thinking) = thinking {
+            chunk["message"]["thinking"] = json!(thinking);
+        }
+        chunk
     } else {
-        json!({
+        let mut chunk = json!({
             "model": model_name,
-            "created_at": Utc::now().to_rfc3339(),
+            "created_at": created_at,
             "response": content,
             "done": false
-        })
+        });
+        if let Some(thinking) = thinking {
+            chunk["thinking"] = json!(thinking);
+        }
+        chunk
     }
 }
 
-pub fn create_done_chunk(model_name: &str) -> serde_json::Value {
-    json!({
+pub fn create_done_chunk(
+    model_name: &str,
+    created_at: &str,
+    usage: Option<&MistralUsage>,
+) -> serde_json::Value {
+    let mut chunk = json!({
         "done": true,
         "model": model_name,
-        "created_at": Utc::now().to_rfc3339(),
-    })
+        "created_at": created_at,
+    });
+
+    if let Some(usage) = usage {
+        chunk["prompt_eval_count"] = json!(usage.prompt_tokens);
+        chunk["eval_count"] = json!(usage.completion_tokens);
+    }
+
+    chunk
 }
 
 #[cfg(test)]
 #[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
-    use crate::models::mistral::{MistralChoice, MistralMessage, MistralUsage};
+    use crate::models::mistral::{MistralChoice, MistralMessage};
 
     #[test]
     fn test_convert_mistral_to_ollama_chat() {
@@ -113,9 +259,11 @@ mod tests {
                 message: Some(MistralMessage {
                     role: "assistant".to_string(),
                     content: "Hello!".to_string(),
+                    ..Default::default()
                 }),
                 delta: None,
                 finish_reason: Some("stop".to_string()),
+                logprobs: None,
             }],
             usage: Some(MistralUsage {
                 prompt_tokens: 10,
@@ -124,8 +272,12 @@ mod tests {
             }),
         };
 
-        let ollama_response =
-            convert_mistral_to_ollama_chat(mistral_response, "mistral:latest".to_string());
+        let ollama_response = convert_mistral_to_ollama_chat(
+            mistral_response,
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
 
         assert_eq!(ollama_response.model, "mistral:latest");
         assert_eq!(ollama_response.message.role, "assistant");
@@ -133,6 +285,47 @@ mod tests {
         assert!(ollama_response.done);
         assert_eq!(ollama_response.prompt_eval_count, Some(10));
         assert_eq!(ollama_response.eval_count, Some(5));
+        assert_eq!(ollama_response.done_reason.as_deref(), Some("stop"));
+        assert!(ollama_response.choices.is_none());
+
+        let json = serde_json::to_value(&ollama_response).unwrap();
+        assert!(json.get("choices").is_none());
+    }
+
+    #[test]
+    fn test_convert_mistral_to_ollama_chat_multiple_choices() {
+        let choice = |index: i32, content: &str| MistralChoice {
+            index,
+            message: Some(MistralMessage {
+                role: "assistant".to_string(),
+                content: content.to_string(),
+                ..Default::default()
+            }),
+            delta: None,
+            finish_reason: Some("stop".to_string()),
+            logprobs: None,
+        };
+        let mistral_response = MistralChatResponse {
+            id: "test-id".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1234567890,
+            model: "mistral-7b".to_string(),
+            choices: vec![choice(0, "First"), choice(1, "Second"), choice(2, "Third")],
+            usage: None,
+        };
+
+        let ollama_response = convert_mistral_to_ollama_chat(
+            mistral_response,
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
+
+        assert_eq!(ollama_response.message.content, "First");
+        let choices = ollama_response.choices.unwrap();
+        assert_eq!(choices.len(), 2);
+        assert_eq!(choices[0].content, "Second");
+        assert_eq!(choices[1].content, "Third");
     }
 
     #[test]
@@ -147,9 +340,11 @@ mod tests {
                 message: Some(MistralMessage {
                     role: "assistant".to_string(),
                     content: "Generated text".to_string(),
+                    ..Default::default()
                 }),
                 delta: None,
                 finish_reason: Some("stop".to_string()),
+                logprobs: None,
             }],
             usage: Some(MistralUsage {
                 prompt_tokens: 20,
@@ -158,29 +353,270 @@ mod tests {
             }),
         };
 
-        let ollama_response =
-            convert_mistral_to_ollama_generate(mistral_response, "mistral:latest".to_string());
+        let ollama_response = convert_mistral_to_ollama_generate(
+            mistral_response,
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
 
         assert_eq!(ollama_response.model, "mistral:latest");
         assert_eq!(ollama_response.response, "Generated text");
         assert!(ollama_response.done);
         assert_eq!(ollama_response.prompt_eval_count, Some(20));
         assert_eq!(ollama_response.eval_count, Some(15));
+        assert_eq!(ollama_response.done_reason.as_deref(), Some("stop"));
     }
 
+    #[test]
+    fn test_convert_mistral_to_ollama_chat_tool_call() {
+        let mistral_response = MistralChatResponse {
+            id: "test-id".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1234567890,
+            model: "mistral-7b".to_string(),
+            choices: vec![MistralChoice {
+                index: 0,
+                message: Some(MistralMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![json!({
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {
+                            "name": "get_weather",
+                            "arguments": "{\"city\": \"Paris\"}"
+                        }
+                    })]),
+                    ..Default::default()
+                }),
+                delta: None,
+                finish_reason: Some("tool_calls".to_string()),
+                logprobs: None,
+            }],
+            usage: None,
+        };
+
+        let ollama_response = convert_mistral_to_ollama_chat(
+            mistral_response,
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
+
+        let tool_calls = ollama_response.message.tool_calls.unwrap();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0]["function"]["name"], "get_weather");
+        assert_eq!(tool_calls[0]["function"]["arguments"]["city"], "Paris");
+    }
+
+    fn response_with_choices(choices: Vec<MistralChoice>) -> MistralChatResponse {
+        MistralChatResponse {
+            id: "test-id".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1234567890,
+            model: "mistral-7b".to_string(),
+            choices,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_empty_choices_is_an_error() {
+        let err = convert_mistral_to_ollama_chat(
+            response_with_choices(vec![]),
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.error_type(), "empty_completion");
+        assert!(err.to_string().contains("no choices"));
+
+        let err = convert_mistral_to_ollama_generate(
+            response_with_choices(vec![]),
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.error_type(), "empty_completion");
+    }
+
+    fn finished_with(finish_reason: Option<&str>) -> MistralChoice {
+        MistralChoice {
+            index: 0,
+            message: Some(MistralMessage {
+                role: "assistant".to_string(),
+                content: "Hello".to_string(),
+                ..Default::default()
+            }),
+            delta: None,
+            finish_reason: finish_reason.map(str::to_string),
+            logprobs: None,
+        }
+    }
+
+    #[test]
+    fn test_done_reason_from_finish_reason() {
+        for (finish_reason, expected) in [
+            (Some("stop"), Some("stop")),
+            (Some("length"), Some("length")),
+            (Some("model_length"), Some("length")),
+            (Some("tool_calls"), Some("stop")),
+            (Some("eos"), Some("stop")),
+            (Some("end_turn"), Some("stop")),
+            (Some("max_tokens"), Some("length")),
+            (Some("recitation"), Some("recitation")),
+            (None, None),
+        ] {
+            let chat = convert_mistral_to_ollama_chat(
+                response_with_choices(vec![finished_with(finish_reason)]),
+                "mistral:latest".to_string(),
+                &DoneReasons::default(),
+            )
+            .unwrap();
+            assert_eq!(chat.done_reason.as_deref(), expected);
+
+            let generate = convert_mistral_to_ollama_generate(
+                response_with_choices(vec![finished_with(finish_reason)]),
+                "mistral:latest".to_string(),
+                &DoneReasons::default(),
+            )
+            .unwrap();
+            assert_eq!(generate.done_reason.as_deref(), expected);
+        }
+    }
+
+    #[test]
+    fn test_done_reason_overrides_extend_builtins() {
+        let overrides = HashMap::from([
+            ("eos".to_string(), "length".to_string()),
+            ("loaded".to_string(), "load".to_string()),
+        ]);
+        let done_reasons = DoneReasons::new(&overrides);
+
+        assert_eq!(done_reasons.normalize("eos"), "length");
+        assert_eq!(done_reasons.normalize("loaded"), "load");
+        assert_eq!(done_reasons.normalize("max_tokens"), "length");
+        assert_eq!(done_reasons.normalize("unknown"), "unknown");
+    }
+
+    #[test]
+    fn test_delta_only_choice_used_when_message_absent() {
+        let delta_only = || MistralChoice {
+            index: 0,
+            message: None,
+            delta: Some(MistralMessage {
+                role: "assistant".to_string(),
+                content: "From delta".to_string(),
+                ..Default::default()
+            }),
+            finish_reason: Some("stop".to_string()),
+            logprobs: None,
+        };
+
+        let chat = convert_mistral_to_ollama_chat(
+            response_with_choices(vec![delta_only()]),
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
+        assert_eq!(chat.message.role, "assistant");
+        assert_eq!(chat.message.content, "From delta");
+
+        let generate = convert_mistral_to_ollama_generate(
+            response_with_choices(vec![delta_only()]),
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap();
+        assert_eq!(generate.response, "From delta");
+    }
+
+    #[test]
+    fn test_content_filter_finish_reason_is_an_error() {
+        let filtered = MistralChoice {
+            index: 0,
+            message: None,
+            delta: None,
+            finish_reason: Some("content_filter".to_string()),
+            logprobs: None,
+        };
+
+        let err = convert_mistral_to_ollama_chat(
+            response_with_choices(vec![filtered]),
+            "mistral:latest".to_string(),
+            &DoneReasons::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.error_type(), "content_filter");
+    }
+
+    #[test]
+    fn test_convert_message_without_tool_calls_omits_field() {
+        let message = OllamaMessage::from(&MistralMessage {
+            role: "assistant".to_string(),
+            content: "Hi".to_string(),
+            ..Default::default()
+        });
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert!(json.get("tool_calls").is_none());
+    }
+
+    const CREATED_AT: &str = "2024-01-01T00:00:00+00:00";
+
     #[test]
     fn test_create_streaming_chunk_chat() {
-        let chunk = create_streaming_chunk("mistral:latest", "Hello", "assistant", true);
+        let chunk = create_streaming_chunk(
+            "mistral:latest",
+            CREATED_AT,
+            "Hello",
+            None,
+            "assistant",
+            true,
+        );
 
         assert_eq!(chunk["model"], "mistral:latest");
         assert_eq!(chunk["message"]["role"], "assistant");
         assert_eq!(chunk["message"]["content"], "Hello");
+        assert_eq!(chunk["created_at"], CREATED_AT);
         assert_eq!(chunk["done"], false);
+        assert!(chunk["message"].get("thinking").is_none());
+    }
+
+    #[test]
+    fn test_create_streaming_chunk_with_thinking() {
+        let chat = create_streaming_chunk(
+            "mistral:latest",
+            CREATED_AT,
+            "",
+            Some("Hmm"),
+            "assistant",
+            true,
+        );
+        assert_eq!(chat["message"]["thinking"], "Hmm");
+
+        let generate = create_streaming_chunk(
+            "mistral:latest",
+            CREATED_AT,
+            "",
+            Some("Hmm"),
+            "assistant",
+            false,
+        );
+        assert_eq!(generate["thinking"], "Hmm");
     }
 
     #[test]
     fn test_create_streaming_chunk_generate() {
-        let chunk = create_streaming_chunk("mistral:latest", "Generated", "assistant", false);
+        let chunk = create_streaming_chunk(
+            "mistral:latest",
+            CREATED_AT,
+            "Generated",
+            None,
+            "assistant",
+            false,
+        );
 
         assert_eq!(chunk["model"], "mistral:latest");
         assert_eq!(chunk["response"], "Generated");
@@ -189,10 +625,24 @@ mod tests {
 
     #[test]
     fn test_create_done_chunk() {
-        let chunk = create_done_chunk("mistral:latest");
+        let chunk = create_done_chunk("mistral:latest", CREATED_AT, None);
 
         assert_eq!(chunk["model"], "mistral:latest");
         assert_eq!(chunk["done"], true);
-        assert!(chunk["created_at"].is_string());
+        assert_eq!(chunk["created_at"], CREATED_AT);
+        assert!(chunk.get("prompt_eval_count").is_none());
+    }
+
+    #[test]
+    fn test_create_done_chunk_with_usage() {
+        let usage = MistralUsage {
+            prompt_tokens: 12,
+            completion_tokens: 34,
+            total_tokens: 46,
+        };
+        let chunk = create_done_chunk("mistral:latest", CREATED_AT, Some(&usage));
+
+        assert_eq!(chunk["prompt_eval_count"], 12);
+        assert_eq!(chunk["eval_count"], 34);
     }
 }
@@ -0,0 +1,94 @@
+use axum::http::{HeaderMap, HeaderName};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::error::{AppError, Result};
+
+pub static DEADLINE_HEADER: HeaderName = HeaderName::from_static("x-deadline-ms");
+
+/// Overall time budget a client set for its request via `X-Deadline-Ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+    budget: Duration,
+}
+
+impl Deadline {
+    pub fn new(budget: Duration) -> Self {
+        Deadline {
+            at: Instant::now() + budget,
+            budget,
+        }
+    }
+
+    /// Reads the budget from the request headers, ignoring values that aren't a millisecond count.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get(&DEADLINE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|ms| Deadline::new(Duration::from_millis(ms)))
+    }
+
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    pub fn exceeded_error(&self) -> AppError {
+        AppError::deadline_exceeded(self.budget)
+    }
+}
+
+/// Runs `fut` to completion, or drops it (aborting any in-flight backend call) once the
+/// deadline passes.
+pub async fn run_with_deadline<T, F>(deadline: Option<Deadline>, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.at, fut)
+            .await
+            .unwrap_or_else(|_| Err(deadline.exceeded_error())),
+        None => fut.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Deadline::from_headers(&headers), None);
+
+        headers.insert(&DEADLINE_HEADER, HeaderValue::from_static("soon"));
+        assert_eq!(Deadline::from_headers(&headers), None);
+
+        headers.insert(&DEADLINE_HEADER, HeaderValue::from_static("250"));
+        let deadline = Deadline::from_headers(&headers).unwrap();
+        assert_eq!(deadline.budget, Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn test_run_with_deadline_exceeded() {
+        let deadline = Deadline::new(Duration::from_millis(10));
+        let result: Result<()> = run_with_deadline(Some(deadline), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+
+        assert_eq!(result.unwrap_err().error_type(), "deadline");
+    }
+
+    #[tokio::test]
+    async fn test_run_with_deadline_met() {
+        let deadline = Deadline::new(Duration::from_secs(5));
+        let result = run_with_deadline(Some(deadline), async { Ok(42) }).await;
+
+        assert_eq!(result.unwrap(), 42);
+    }
+}
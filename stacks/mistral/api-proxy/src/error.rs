@@ -1,11 +1,14 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::logging::redact;
+
 #[derive(Error, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum AppError {
@@ -27,12 +30,63 @@ pub enum AppError {
     #[error("Streaming error: {message} (endpoint: {endpoint})")]
     StreamingError { message: String, endpoint: String },
 
+    #[error("Backend returned {status}: {message} (URL: {url})")]
+    UpstreamStatus {
+        status: u16,
+        message: String,
+        url: String,
+    },
+
+    #[error("Backend returned an invalid response: {context}")]
+    InvalidBackendResponse { context: String },
+
     #[error("Internal server error: {context}")]
     InternalError { context: String },
+
+    #[error("Request deadline of {budget_ms}ms exceeded")]
+    DeadlineExceeded { budget_ms: u128 },
+
+    #[error("Backend circuit open, retry in {retry_after_secs}s")]
+    CircuitOpen { retry_after_secs: u64 },
+
+    #[error("Backend returned no completion: {reason}")]
+    EmptyCompletion { reason: String },
+
+    #[error("Completion blocked by the backend's content filter")]
+    ContentFiltered,
+
+    #[error("Rate limit exceeded")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("model '{model}' not found")]
+    ModelNotFound { model: String },
+
+    #[error("model '{model}' is not allowed")]
+    ModelNotAllowed { model: String },
+
+    #[error("model '{model}' does not support images")]
+    ImagesNotSupported { model: String },
+
+    #[error("Request body of {length} bytes exceeds the {limit} byte limit")]
+    PayloadTooLarge { length: u64, limit: usize },
+
+    #[error("Request aborted")]
+    Aborted,
+
+    #[error("no active request with ID '{request_id}'")]
+    RequestNotFound { request_id: String },
 }
 
+/// Non-standard status, borrowed from nginx, for requests cancelled on the client's behalf.
+const CLIENT_CLOSED_REQUEST: u16 = 499;
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let retry_after = match &self {
+            AppError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        };
+
         let (status, error_message) = match self {
             AppError::RequestError { message, url, .. } => (
                 StatusCode::BAD_GATEWAY,
@@ -46,17 +100,86 @@ impl IntoResponse for AppError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Streaming error: {message} (endpoint: {endpoint})"),
             ),
+            // The backend's body can quote the prompt and its URL is internal, so both stay in
+            // the logs. A backend 4xx usually means the proxy built a bad request, not the client.
+            AppError::UpstreamStatus { status, .. } if status >= 500 => (
+                StatusCode::BAD_GATEWAY,
+                "Mistral API returned non-success status".to_string(),
+            ),
+            AppError::UpstreamStatus { .. } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error: Mistral API returned non-success status".to_string(),
+            ),
+            AppError::InvalidBackendResponse { context } => (
+                StatusCode::BAD_GATEWAY,
+                format!("Backend returned an invalid response: {context}"),
+            ),
             AppError::InternalError { context } => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Internal server error: {context}"),
             ),
+            AppError::DeadlineExceeded { budget_ms } => (
+                StatusCode::GATEWAY_TIMEOUT,
+                format!("Request deadline of {budget_ms}ms exceeded"),
+            ),
+            AppError::CircuitOpen { retry_after_secs } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Backend is unavailable, retry in {retry_after_secs}s"),
+            ),
+            AppError::EmptyCompletion { reason } => (
+                StatusCode::BAD_GATEWAY,
+                format!("Backend returned no completion: {reason}"),
+            ),
+            AppError::ContentFiltered => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Completion blocked by the backend's content filter".to_string(),
+            ),
+            AppError::RateLimited { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Rate limit exceeded, retry later".to_string(),
+            ),
+            // Worded like Ollama's own error so clients matching on it keep working
+            AppError::ModelNotFound { model } => {
+                (StatusCode::NOT_FOUND, format!("model '{model}' not found"))
+            }
+            AppError::ModelNotAllowed { model } => (
+                StatusCode::FORBIDDEN,
+                format!("model '{model}' is not allowed"),
+            ),
+            AppError::ImagesNotSupported { model } => (
+                StatusCode::BAD_REQUEST,
+                format!("model '{model}' does not support images"),
+            ),
+            AppError::PayloadTooLarge { length, limit } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body of {length} bytes exceeds the {limit} byte limit"),
+            ),
+            AppError::Aborted => (
+                StatusCode::from_u16(CLIENT_CLOSED_REQUEST).expect("499 is a valid status code"),
+                "Request aborted".to_string(),
+            ),
+            AppError::RequestNotFound { request_id } => (
+                StatusCode::NOT_FOUND,
+                format!("no active request with ID '{request_id}'"),
+            ),
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
-        }));
+        });
+        if let Some(request_id) = crate::request_id::current() {
+            body["request_id"] = json!(request_id);
+        }
 
-        (status, body).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let Some(retry_after) = retry_after {
+            // Retry-After is whole seconds; round up so clients don't retry too early
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -83,11 +206,43 @@ impl AppError {
         }
     }
 
+    pub fn upstream_status(status: reqwest::StatusCode, message: String, url: &str) -> Self {
+        AppError::UpstreamStatus {
+            status: status.as_u16(),
+            message,
+            url: url.to_string(),
+        }
+    }
+
+    pub fn invalid_backend_response(context: &str) -> Self {
+        AppError::InvalidBackendResponse {
+            context: context.to_string(),
+        }
+    }
+
     pub fn internal_error(context: &str) -> Self {
         AppError::InternalError {
             context: context.to_string(),
         }
     }
+
+    pub fn deadline_exceeded(budget: Duration) -> Self {
+        AppError::DeadlineExceeded {
+            budget_ms: budget.as_millis(),
+        }
+    }
+
+    pub fn empty_completion(reason: &str) -> Self {
+        AppError::EmptyCompletion {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn circuit_open(retry_after: Duration) -> Self {
+        AppError::CircuitOpen {
+            retry_after_secs: retry_after.as_secs_f64().ceil() as u64,
+        }
+    }
 }
 
 impl From<reqwest::Error> for AppError {
@@ -105,20 +260,186 @@ impl From<serde_json::Error> for AppError {
 pub type Result<T> = std::result::Result<T, AppError>;
 
 impl AppError {
+    /// Cause of the error as recorded in the `error_type` metric label and logs.
     pub fn error_type(&self) -> &'static str {
         match self {
-            AppError::RequestError { source, .. } => {
-                if source.is_timeout() {
-                    "timeout"
-                } else if source.is_connect() {
-                    "connection"
-                } else {
-                    "request"
-                }
-            }
-            AppError::JsonError { .. } => "json_parse",
-            AppError::StreamingError { .. } => "streaming",
+            AppError::RequestError { source, .. } => classify_request_error(source),
+            AppError::JsonError { .. } => "parse_error",
+            AppError::StreamingError { .. } => "stream_error",
+            AppError::UpstreamStatus { status, .. } => classify_upstream_status(*status),
+            AppError::InvalidBackendResponse { .. } => "parse_error",
             AppError::InternalError { .. } => "internal",
+            AppError::DeadlineExceeded { .. } => "deadline",
+            AppError::CircuitOpen { .. } => "circuit_open",
+            AppError::EmptyCompletion { .. } => "empty_completion",
+            AppError::ContentFiltered => "content_filter",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::ModelNotFound { .. } => "model_not_found",
+            AppError::ModelNotAllowed { .. } => "model_not_allowed",
+            AppError::ImagesNotSupported { .. } => "images_not_supported",
+            AppError::PayloadTooLarge { .. } => "payload_too_large",
+            AppError::Aborted => "aborted",
+            AppError::RequestNotFound { .. } => "request_not_found",
+        }
+    }
+
+    /// The error as logged under `LOG_REDACT_PROMPTS`, with backend-supplied text, which can
+    /// quote the prompt, reduced to its size.
+    pub fn redacted(&self) -> String {
+        match self {
+            AppError::StreamingError { message, endpoint } => {
+                format!(
+                    "Streaming error: {} (endpoint: {endpoint})",
+                    redact(message)
+                )
+            }
+            AppError::UpstreamStatus {
+                status,
+                message,
+                url,
+            } => format!(
+                "Backend returned {status}: {} (URL: {url})",
+                redact(message)
+            ),
+            _ => self.to_string(),
+        }
+    }
+}
+
+fn classify_request_error(error: &reqwest::Error) -> &'static str {
+    if error.is_timeout() {
+        "timeout"
+    } else if error.is_connect() {
+        "connection_refused"
+    } else if let Some(status) = error.status() {
+        classify_upstream_status(status.as_u16())
+    } else if error.is_decode() {
+        "parse_error"
+    } else {
+        "request"
+    }
+}
+
+fn classify_upstream_status(status: u16) -> &'static str {
+    match status {
+        400..=499 => "upstream_4xx",
+        500..=599 => "upstream_5xx",
+        _ => "upstream_status",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn send_error(url: &str) -> reqwest::Error {
+        reqwest::Client::new()
+            .get(url)
+            .timeout(Duration::from_millis(50))
+            .send()
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_classified() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let err =
+            AppError::request_error(String::new(), send_error(&format!("http://{addr}")).await);
+        assert_eq!(err.error_type(), "connection_refused");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_classified() {
+        // Accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let err =
+            AppError::request_error(String::new(), send_error(&format!("http://{addr}")).await);
+        assert_eq!(err.error_type(), "timeout");
+        drop(listener);
+    }
+
+    #[test]
+    fn test_upstream_status_classified() {
+        let error = |status| AppError::upstream_status(status, String::new(), "http://backend");
+        assert_eq!(
+            error(reqwest::StatusCode::BAD_REQUEST).error_type(),
+            "upstream_4xx"
+        );
+        assert_eq!(
+            error(reqwest::StatusCode::NOT_FOUND).error_type(),
+            "upstream_4xx"
+        );
+        assert_eq!(
+            error(reqwest::StatusCode::SERVICE_UNAVAILABLE).error_type(),
+            "upstream_5xx"
+        );
+    }
+
+    #[test]
+    fn test_parse_and_stream_errors_classified() {
+        let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert_eq!(AppError::from(json).error_type(), "parse_error");
+        assert_eq!(
+            AppError::invalid_backend_response("truncated body").error_type(),
+            "parse_error"
+        );
+        assert_eq!(
+            AppError::streaming_error("reset".to_string(), "chat").error_type(),
+            "stream_error"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upstream_status_response_omits_backend_text() {
+        for (status, expected) in [
+            (
+                reqwest::StatusCode::BAD_REQUEST,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::BAD_GATEWAY,
+            ),
+        ] {
+            let response = AppError::upstream_status(
+                status,
+                "prompt 'my secret' is too long".to_string(),
+                "http://internal-backend:8080",
+            )
+            .into_response();
+            assert_eq!(response.status(), expected);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(!body.contains("my secret"), "{body}");
+            assert!(!body.contains("internal-backend"), "{body}");
         }
     }
+
+    #[test]
+    fn test_redacted_hides_backend_text() {
+        let err = AppError::upstream_status(
+            reqwest::StatusCode::BAD_REQUEST,
+            "prompt 'my secret' is too long".to_string(),
+            "http://backend",
+        );
+        let redacted = err.redacted();
+        assert!(!redacted.contains("my secret"));
+        assert!(redacted.contains("400"));
+        assert!(redacted.contains("http://backend"));
+
+        let err = AppError::ModelNotFound {
+            model: "mistral:latest".to_string(),
+        };
+        assert_eq!(err.redacted(), err.to_string());
+    }
 }
@@ -0,0 +1,93 @@
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+use crate::error::AppError;
+
+/// JSON body extractor that reports malformed bodies as a 400 [`AppError::JsonError`].
+///
+/// Axum's `Json` answers with a plain-text 422 that Ollama clients can't parse, and insists on
+/// a JSON `Content-Type` that Ollama itself doesn't require. This accepts any content type and
+/// names the offending field where it can, e.g. `messages[0].content: invalid type: ...`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for AppJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // Body limit rejections keep their own status
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        parse_json(&body)
+            .map(AppJson)
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(body: &[u8]) -> Result<T, AppError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let context = match e.path().to_string().as_str() {
+            // The root, or a key that failed to parse before it could be named
+            "." | "?" => e.inner().to_string(),
+            path => format!("{path}: {}", e.inner()),
+        };
+        AppError::json_error(&context, e.into_inner())
+    })?;
+    // Reject trailing data after the JSON value, as serde_json::from_slice does
+    deserializer
+        .end()
+        .map_err(|e| AppError::json_error(&e.to_string(), e))?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Body {
+        messages: Vec<Message>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Message {
+        content: String,
+    }
+
+    fn context(body: &str) -> String {
+        match parse_json::<Body>(body.as_bytes()) {
+            Err(AppError::JsonError { context, .. }) => context,
+            other => panic!("expected a JSON error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_error_names_field_path() {
+        let context = context(r#"{"messages": [{"content": 5}]}"#);
+        assert!(
+            context.starts_with("messages[0].content: invalid type"),
+            "{context}"
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_has_no_path() {
+        assert!(context("{not json").starts_with("key must be a string"));
+        assert!(context(r#"{"messages": []} trailing"#).starts_with("trailing characters"));
+    }
+}
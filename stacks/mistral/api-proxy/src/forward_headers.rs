@@ -0,0 +1,75 @@
+//! Passes selected client headers, such as tenant IDs or routing hints, through to the backend.
+//!
+//! Only headers named in `FORWARD_HEADERS` are copied. Hop-by-hop headers describe the client's
+//! connection rather than the request, so they are never forwarded even when listed.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use reqwest::RequestBuilder;
+use tracing::warn;
+
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "content-length",
+    "host",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+tokio::task_local! {
+    static FORWARDED_HEADERS: HeaderMap;
+}
+
+/// Parses the configured allowlist, dropping invalid names and hop-by-hop headers.
+pub fn allowlist(names: &[String]) -> Vec<HeaderName> {
+    names
+        .iter()
+        .filter_map(|name| match HeaderName::try_from(name.as_str()) {
+            Ok(name) if HOP_BY_HOP_HEADERS.contains(&name.as_str()) => {
+                warn!("Not forwarding hop-by-hop header: {}", name);
+                None
+            }
+            Ok(name) => Some(name),
+            Err(_) => {
+                warn!("Ignoring invalid forward header name: {:?}", name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Captures the allowlisted headers of the incoming request for [`apply`].
+pub async fn capture_forward_headers(
+    State(allowlist): State<Arc<Vec<HeaderName>>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut forwarded = HeaderMap::new();
+    for name in allowlist.iter() {
+        for value in req.headers().get_all(name) {
+            forwarded.append(name.clone(), value.clone());
+        }
+    }
+    FORWARDED_HEADERS.scope(forwarded, next.run(req)).await
+}
+
+/// Adds the current request's forwarded headers to a backend request.
+pub fn apply(builder: RequestBuilder) -> RequestBuilder {
+    let Ok(headers) = FORWARDED_HEADERS.try_with(HeaderMap::clone) else {
+        return builder;
+    };
+    headers.iter().fold(builder, |builder, (name, value)| {
+        builder.header(name.as_str(), value.as_bytes())
+    })
+}
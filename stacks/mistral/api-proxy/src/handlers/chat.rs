@@ -1,36 +1,411 @@
 use axum::{
     body::Body,
-    extract::State,
-    http::{header, HeaderMap, HeaderValue},
+    extract::{RawQuery, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
     response::{IntoResponse, Response},
     Json,
 };
-use futures::StreamExt;
-use reqwest::Client;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use prometheus::CounterVec;
+use reqwest::{Client, RequestBuilder};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::error::{SendError, TrySendError};
+use tokio::sync::mpsc::Sender;
 use tokio_stream::wrappers::ReceiverStream;
-use tracing::{error, info};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
+use crate::abort::{run_until_aborted, AbortHandle, AbortRegistry};
+use crate::backend_selector::BackendSelector;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::client::build_client;
+use crate::concurrency::{ConcurrencyLimit, ConcurrencyPermit};
+use crate::config::{Config, DEFAULT_SEED_FIELD};
+use crate::context::ContextStore;
 use crate::converters::{
-    convert_mistral_to_ollama_chat, convert_mistral_to_ollama_generate, create_done_chunk,
-    create_streaming_chunk,
+    convert_mistral_to_ollama_chat, convert_mistral_to_ollama_generate, convert_tool_calls,
+    create_done_chunk, create_streaming_chunk, DoneReasons,
 };
+use crate::deadline::{run_with_deadline, Deadline};
 use crate::error::{AppError, Result};
+use crate::extract::AppJson;
+use crate::forward_headers;
+use crate::handlers::models::ModelsCache;
+use crate::handlers::system::Readiness;
+use crate::logging::redact;
 use crate::metrics::{
-    ACTIVE_REQUESTS, GENERATE_DURATION_SECONDS, HTTP_REQUESTS_TOTAL, HTTP_REQUEST_DURATION_SECONDS,
-    STREAMING_CHUNKS_TOTAL,
+    batch_size_label, observe_tokens_per_second, ActiveStreamGuard, ModelLabels, StreamedBytes,
+    ACTIVE_REQUESTS, ACTIVE_STREAMS, BACKEND_DURATION_SECONDS, CACHE_HITS_TOTAL,
+    CACHE_MISSES_TOTAL, DECODE_DURATION_SECONDS, GENERATE_DURATION_SECONDS, GENERATE_TOKENS_TOTAL,
+    HISTORY_TRUNCATED_TOTAL, HTTP_REQUESTS_TOTAL, HTTP_REQUEST_DURATION_SECONDS,
+    MODEL_DENIED_TOTAL, PREFILL_DURATION_SECONDS, REQUESTED_CONTEXT_LENGTH, REQUEST_BYTES,
+    RESPONSE_BYTES, STREAMING_CHUNKS_TOTAL, STREAMS_TRUNCATED_TOTAL, STREAM_CHANNEL_FULL_TOTAL,
+    STREAM_PARSE_ERRORS_TOTAL,
 };
 use crate::models::mistral::{
-    MistralChatRequest, MistralChatResponse, MistralMessage, MistralStreamChunk,
+    MistralChatRequest, MistralChatResponse, MistralChoice, MistralCompletionRequest,
+    MistralFimRequest, MistralMessage, MistralStreamChunk, MistralTextRequest, MistralUsage,
 };
-use crate::models::ollama::{OllamaChatRequest, OllamaGenerateRequest, OllamaMessage};
+use crate::models::ollama::{
+    AbortRequest, OllamaChatRequest, OllamaGenerateRequest, OllamaMessage,
+};
+use crate::rate_limit::ModelRateLimiter;
+use crate::request_id::{self, REQUEST_ID_HEADER};
+use crate::response_cache::ResponseCache;
+use crate::session::{SessionStore, SessionTurn};
+use crate::sse::{data_payload, with_keepalive, LineBuffer};
+use crate::telemetry;
+use crate::templates::{render_prompt, DEFAULT_PROMPT_TEMPLATE};
+use crate::tokens::{estimate_tokens, estimate_usage};
+
+/// Names the backend that served a response, when `EXPOSE_BACKEND_HEADER` is enabled.
+pub static PROXY_BACKEND_HEADER: HeaderName = HeaderName::from_static("x-proxy-backend");
+
+/// Asks for the translated backend request to be returned instead of sent.
+pub static DRY_RUN_HEADER: HeaderName = HeaderName::from_static("x-dry-run");
+
+/// Asks for a streamed completion to be collected into a single JSON response.
+pub static AGGREGATE_STREAM_HEADER: HeaderName = HeaderName::from_static("x-aggregate-stream");
+
+/// Names a server-side chat session whose earlier messages are prepended to the request.
+pub static SESSION_ID_HEADER: HeaderName = HeaderName::from_static("x-session-id");
+
+/// Asks for the backend's untranslated reply under `_raw`, when `DEBUG_ALLOW_RAW` is enabled.
+pub static INCLUDE_RAW_HEADER: HeaderName = HeaderName::from_static("x-include-raw");
+
+const MAX_SESSION_ID_LENGTH: usize = 128;
 
 #[derive(Clone)]
 pub struct AppState {
     pub client: Client,
     pub mistral_url: String,
+    pub backends: Arc<BackendSelector>,
+    pub backend_api_prefix: String,
+    pub backend_kind: String,
+    pub backend_completions_endpoint: bool,
+    pub backend_supports_stream_usage: bool,
+    pub backend_seed_field: String,
     pub channel_buffer_size: usize,
     pub max_line_length: usize,
+    pub sync_request_timeout: Option<Duration>,
+    pub stream_total_timeout: Option<Duration>,
+    pub stream_idle_timeout: Option<Duration>,
+    pub stream_keepalive: Option<Duration>,
+    pub model_map: HashMap<String, String>,
+    pub done_reasons: Arc<DoneReasons>,
+    pub system_prompts: HashMap<String, String>,
+    pub model_defaults: HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    pub prompt_templates: HashMap<String, String>,
+    pub max_history_messages: Option<usize>,
+    pub max_stream_chunks: Option<usize>,
+    pub pull_coordinator_url: Option<String>,
+    pub default_model: Option<String>,
+    pub default_model_aliases: Vec<String>,
+    pub allowed_models: Option<HashSet<String>>,
+    pub multimodal_models: HashSet<String>,
+    pub parameter_limits: ParameterLimits,
+    pub context_store: Arc<ContextStore>,
+    pub sessions: Arc<SessionStore>,
+    pub models_cache: Arc<ModelsCache>,
+    pub models_list_timeout: Option<Duration>,
+    pub models_list_retries: u32,
+    pub response_cache: Arc<ResponseCache>,
+    pub readiness: Arc<Readiness>,
+    /// One breaker per completion backend, so a failing backend doesn't cut off the others.
+    pub circuit_breakers: Arc<HashMap<String, CircuitBreaker>>,
+    pub rate_limiter: Arc<ModelRateLimiter>,
+    pub concurrency_limit: Arc<ConcurrencyLimit>,
+    pub aborts: Arc<AbortRegistry>,
+    pub expose_backend_header: bool,
+    pub debug_allow_raw: bool,
+    pub log_redact_prompts: bool,
+    pub model_labels: ModelLabels,
+}
+
+impl AppState {
+    pub fn new(client: Client, config: &Config) -> Self {
+        AppState {
+            client,
+            mistral_url: config.mistral_url.clone(),
+            backends: Arc::new(BackendSelector::new(
+                config.completion_backends(),
+                config.backend_failure_half_life(),
+            )),
+            backend_api_prefix: config.backend_api_prefix.clone(),
+            backend_kind: config.backend_kind.clone(),
+            backend_completions_endpoint: config.backend_completions_endpoint,
+            backend_supports_stream_usage: config.backend_supports_stream_usage,
+            backend_seed_field: config.backend_seed_field.clone(),
+            channel_buffer_size: config.channel_buffer_size,
+            max_line_length: config.max_line_length,
+            sync_request_timeout: config.sync_request_timeout(),
+            stream_total_timeout: config.stream_total_timeout(),
+            stream_idle_timeout: config.stream_idle_timeout(),
+            stream_keepalive: config.stream_keepalive(),
+            model_map: config.model_map.clone(),
+            done_reasons: Arc::new(DoneReasons::new(&config.done_reason_map)),
+            system_prompts: config.system_prompts.clone(),
+            model_defaults: config.model_defaults.clone(),
+            prompt_templates: config.prompt_templates.clone(),
+            max_history_messages: config.max_history_messages,
+            max_stream_chunks: config.max_stream_chunks(),
+            pull_coordinator_url: config.pull_coordinator_url.clone(),
+            default_model: config.default_model.clone(),
+            default_model_aliases: config.default_model_aliases.clone(),
+            allowed_models: config
+                .allowed_models
+                .as_ref()
+                .map(|models| models.iter().cloned().collect()),
+            multimodal_models: config.multimodal_models.iter().cloned().collect(),
+            parameter_limits: ParameterLimits {
+                temperature: config.temperature_range(),
+                max_tokens: config.max_tokens_cap,
+                max_output_tokens: config.max_output_tokens,
+            },
+            context_store: Arc::new(ContextStore::new(config.context_cache_size)),
+            sessions: Arc::new(SessionStore::new(
+                config.session_cache_size,
+                config.max_session_messages,
+            )),
+            models_cache: Arc::new(ModelsCache::new(Duration::from_secs(
+                config.models_cache_ttl_secs,
+            ))),
+            models_list_timeout: config.models_list_timeout(),
+            models_list_retries: config.models_list_retries,
+            response_cache: Arc::new(ResponseCache::new(
+                config.response_cache_ttl(),
+                config.response_cache_size,
+            )),
+            readiness: Arc::new(Readiness::new(
+                Duration::from_secs(config.readiness_cache_secs),
+                config.readiness_require_warmup,
+            )),
+            circuit_breakers: Arc::new(
+                config
+                    .completion_backends()
+                    .into_iter()
+                    .map(|backend| {
+                        let breaker = CircuitBreaker::new(
+                            &backend,
+                            config.circuit_failure_threshold,
+                            Duration::from_secs(config.circuit_failure_window_secs),
+                            Duration::from_secs(config.circuit_cooldown_secs),
+                        );
+                        (backend, breaker)
+                    })
+                    .collect(),
+            ),
+            rate_limiter: Arc::new(ModelRateLimiter::new(
+                &config.model_rate_limits,
+                config.metric_model_labels.clone(),
+            )),
+            concurrency_limit: Arc::new(ConcurrencyLimit::new(config.max_concurrent_requests)),
+            aborts: Arc::new(AbortRegistry::new()),
+            expose_backend_header: config.expose_backend_header,
+            debug_allow_raw: config.debug_allow_raw,
+            log_redact_prompts: config.log_redact_prompts,
+            model_labels: config.metric_model_labels.clone(),
+        }
+    }
+
+    /// State for a proxy in front of the single backend at `mistral_url`, with every other
+    /// setting taken from `config`.
+    pub fn for_backend(config: &Config, mistral_url: &str) -> reqwest::Result<Self> {
+        let config = Config {
+            mistral_url: mistral_url.to_string(),
+            backend_urls: Vec::new(),
+            ..config.clone()
+        };
+        Ok(AppState::new(build_client(&config)?, &config))
+    }
+
+    /// The circuit breaker guarding `backend`, which must be one of the completion backends.
+    pub fn circuit_breaker(&self, backend: &str) -> &CircuitBreaker {
+        &self.circuit_breakers[backend]
+    }
+
+    /// URL of a backend API endpoint, given its path below the API prefix.
+    pub fn backend_url(&self, path: &str) -> String {
+        self.url_on(&self.mistral_url, path)
+    }
+
+    /// URL of a backend API endpoint on a specific backend.
+    pub fn url_on(&self, backend: &str, path: &str) -> String {
+        format!("{}{}{}", backend, self.backend_api_prefix, path)
+    }
+
+    /// Rejects a model outside `ALLOWED_MODELS`, when that is set.
+    pub fn check_model_allowed(&self, model: &str) -> Result<()> {
+        match &self.allowed_models {
+            Some(allowed) if !allowed.contains(model) => {
+                warn!(
+                    "Rejecting request for model {} outside ALLOWED_MODELS",
+                    model
+                );
+                MODEL_DENIED_TOTAL
+                    .with_label_values(&[self.model_label(model)])
+                    .inc();
+                Err(AppError::ModelNotAllowed {
+                    model: model.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects images sent to a model outside `MULTIMODAL_MODELS`.
+    pub fn check_images_supported(&self, model: &str, images: &[String]) -> Result<()> {
+        if images.is_empty() || self.multimodal_models.contains(model) {
+            return Ok(());
+        }
+        Err(AppError::ImagesNotSupported {
+            model: model.to_string(),
+        })
+    }
+
+    /// The backend model serving an Ollama model name.
+    pub fn translate_model(&self, ollama_name: &str) -> String {
+        translate_model_name(&self.model_map, ollama_name)
+    }
+
+    /// The label a requested model is recorded under in metrics.
+    pub fn model_label<'a>(&self, model: &'a str) -> &'a str {
+        self.model_labels.label(model)
+    }
+
+    /// Substitutes the configured default model for an empty or placeholder model name.
+    pub fn resolve_model(&self, requested: String) -> String {
+        match &self.default_model {
+            Some(default)
+                if requested.is_empty() || self.default_model_aliases.contains(&requested) =>
+            {
+                info!(
+                    "Using default model {} for requested model {:?}",
+                    default, requested
+                );
+                default.clone()
+            }
+            _ => requested,
+        }
+    }
+}
+
+/// Starts a backend POST, forwarding the current request ID for cross-service correlation
+/// along with any allowlisted client headers.
+pub(crate) fn backend_post(state: &AppState, url: &str) -> RequestBuilder {
+    with_request_id(state.client.post(url))
+}
+
+/// Bounds a whole backend call, including reading its response body.
+pub(crate) fn with_timeout(builder: RequestBuilder, timeout: Option<Duration>) -> RequestBuilder {
+    match timeout {
+        Some(timeout) => builder.timeout(timeout),
+        None => builder,
+    }
+}
+
+pub(crate) fn with_request_id(builder: RequestBuilder) -> RequestBuilder {
+    let builder = forward_headers::apply(telemetry::inject_trace_context(builder));
+    match request_id::current() {
+        Some(id) => builder.header(REQUEST_ID_HEADER.as_str(), id),
+        None => builder,
+    }
+}
+
+/// Serializes `req` as the backend expects it, with the seed under `BACKEND_SEED_FIELD`.
+pub(crate) fn backend_request_body<R: MistralCompletionRequest>(
+    req: &R,
+    seed_field: &str,
+) -> serde_json::Result<Vec<u8>> {
+    let body = serde_json::to_vec(req)?;
+    if seed_field == DEFAULT_SEED_FIELD {
+        return Ok(body);
+    }
+    // Reparsed rather than converted with `to_value`, which would widen `f32` parameters and
+    // send 0.2 as 0.20000000298023224
+    let mut fields: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&body)?;
+    if let Some(seed) = fields.remove(DEFAULT_SEED_FIELD) {
+        fields.insert(seed_field.to_string(), seed);
+    }
+    serde_json::to_vec(&fields)
+}
+
+/// Sends a completion request through the circuit breaker, failing fast while it is open.
+///
+/// Transport errors and 5xx responses count as backend failures. A 429 is surfaced as
+/// [`AppError::RateLimited`] so its `Retry-After` reaches the client; other statuses are left
+/// to the caller.
+async fn send_to_backend<R: MistralCompletionRequest>(
+    state: &AppState,
+    endpoint: &str,
+    backend: &str,
+    url: &str,
+    req: &R,
+    timeout: Option<Duration>,
+) -> Result<reqwest::Response> {
+    let circuit_breaker = state.circuit_breaker(backend);
+    circuit_breaker
+        .try_acquire()
+        .map_err(AppError::circuit_open)?;
+
+    let body = backend_request_body(req, &state.backend_seed_field)?;
+    REQUEST_BYTES
+        .with_label_values(&[endpoint])
+        .observe(body.len() as f64);
+
+    let span = info_span!("backend_request", url = %url, model = %req.model());
+    let started = Instant::now();
+    match with_timeout(backend_post(state, url), timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .instrument(span)
+        .await
+    {
+        Ok(response) => {
+            if response.status().is_server_error() {
+                circuit_breaker.record_failure();
+                state.backends.record_failure(backend);
+            } else {
+                circuit_breaker.record_success();
+                state.backends.record_success(backend, started.elapsed());
+            }
+
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_retry_after);
+                warn!(
+                    "Backend rate limited request (retry after {:?})",
+                    retry_after
+                );
+                return Err(AppError::RateLimited { retry_after });
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            circuit_breaker.record_failure();
+            state.backends.record_failure(backend);
+            Err(AppError::request_error(url.to_string(), e))
+        }
+    }
+}
+
+/// Parses a `Retry-After` value given either as delay seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let retry_at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (retry_at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
 }
 
 impl From<OllamaMessage> for MistralMessage {
@@ -38,197 +413,919 @@ impl From<OllamaMessage> for MistralMessage {
         MistralMessage {
             role: msg.role,
             content: msg.content,
+            tool_calls: msg.tool_calls,
+            ..Default::default()
         }
     }
 }
 
+/// Marks a trailing assistant message as a prefix, so the model continues it instead of
+/// starting a new turn.
+fn mark_continuation(messages: &mut [MistralMessage]) {
+    if let Some(last) = messages.last_mut().filter(|m| m.role == "assistant") {
+        debug!("Conversation ends with an assistant message, requesting continuation");
+        last.prefix = Some(true);
+    }
+}
+
+/// Prepends the configured system prompt unless the client already supplied a system message.
+fn apply_system_prompt(messages: &mut Vec<MistralMessage>, system_prompt: Option<&String>) {
+    let Some(system_prompt) = system_prompt else {
+        return;
+    };
+
+    if messages.iter().any(|m| m.role == "system") {
+        return;
+    }
+
+    messages.insert(
+        0,
+        MistralMessage {
+            role: "system".to_string(),
+            content: system_prompt.clone(),
+            ..Default::default()
+        },
+    );
+}
+
+/// Applies `MAX_HISTORY_MESSAGES`, counting and logging conversations that were cut short.
+fn limit_history(messages: &mut Vec<MistralMessage>, max_messages: Option<usize>) {
+    let Some(max_messages) = max_messages else {
+        return;
+    };
+
+    let original_len = messages.len();
+    if truncate_history(messages, max_messages) {
+        HISTORY_TRUNCATED_TOTAL.inc();
+        info!(
+            "Truncated message history from {} to {} messages",
+            original_len,
+            messages.len()
+        );
+    }
+}
+
+/// Drops the oldest messages until at most `max_messages` remain, returning whether any were
+/// dropped.
+///
+/// System messages and the latest user message are always kept, even if that exceeds the limit.
+fn truncate_history(messages: &mut Vec<MistralMessage>, max_messages: usize) -> bool {
+    if messages.len() <= max_messages {
+        return false;
+    }
+
+    let last_user = messages.iter().rposition(|m| m.role == "user");
+    let pinned = |i: usize, m: &MistralMessage| m.role == "system" || Some(i) == last_user;
+    let pinned_count = messages
+        .iter()
+        .enumerate()
+        .filter(|(i, m)| pinned(*i, m))
+        .count();
+
+    // Walk from newest to oldest, keeping the most recent unpinned messages that fit
+    let mut remaining = max_messages.saturating_sub(pinned_count);
+    let mut keep = vec![false; messages.len()];
+    for (i, message) in messages.iter().enumerate().rev() {
+        if pinned(i, message) {
+            keep[i] = true;
+        } else if remaining > 0 {
+            keep[i] = true;
+            remaining -= 1;
+        }
+    }
+
+    let original_len = messages.len();
+    let mut keep = keep.into_iter();
+    messages.retain(|_| keep.next().unwrap_or(true));
+    messages.len() < original_len
+}
+
+const TOP_P_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+/// Clamps a sampling parameter into `range`, logging when the client's value was out of bounds.
+fn clamp_parameter(name: &str, value: f32, range: &RangeInclusive<f32>) -> f32 {
+    let clamped = value.clamp(*range.start(), *range.end());
+    if clamped != value {
+        warn!(
+            "Clamped {} from {} to {} (allowed range {:?})",
+            name, value, clamped, range
+        );
+    }
+    clamped
+}
+
+/// Bounds applied to the sampling parameters clients send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterLimits {
+    pub temperature: RangeInclusive<f32>,
+    /// Largest `max_tokens` passed to the backend; larger requests are clamped down to it.
+    pub max_tokens: i32,
+    /// Operator ceiling on generated tokens, also applied to requests that set no limit.
+    pub max_output_tokens: Option<i32>,
+}
+
+/// Caps a requested completion length at `cap`, logging when the client asked for more.
+///
+/// Zero and negative values, such as Ollama's `-1` for "until done", set no limit.
+fn clamp_max_tokens(value: i64, cap: i32) -> Option<i32> {
+    if value <= 0 {
+        debug!("Treating max_tokens {} as no limit", value);
+        return None;
+    }
+    match i32::try_from(value) {
+        Ok(value) if value <= cap => Some(value),
+        _ => {
+            warn!("Clamped max_tokens from {} to {}", value, cap);
+            Some(cap)
+        }
+    }
+}
+
+/// Sampling parameters taken from an Ollama request's `options`.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct OllamaParameters {
+    temperature: Option<f32>,
+    top_p: Option<f32>,
+    max_tokens: Option<i32>,
+    random_seed: Option<i32>,
+    /// Number of candidate completions; only set when more than one is requested.
+    n: Option<i32>,
+    safe_prompt: Option<bool>,
+    stop: Option<Vec<String>>,
+    num_ctx: Option<u32>,
+    logprobs: Option<bool>,
+    top_logprobs: Option<i32>,
+}
+
+/// Fills options the client left unset from the model's configured defaults.
+///
+/// Options that aren't an object are passed through untouched for the parameter extraction to
+/// handle.
+fn apply_model_defaults(
+    options: Option<serde_json::Value>,
+    defaults: Option<&serde_json::Map<String, serde_json::Value>>,
+) -> Option<serde_json::Value> {
+    let Some(defaults) = defaults else {
+        return options;
+    };
+    match options {
+        None | Some(serde_json::Value::Null) => Some(serde_json::Value::Object(defaults.clone())),
+        Some(serde_json::Value::Object(mut options)) => {
+            for (key, value) in defaults {
+                options.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            Some(serde_json::Value::Object(options))
+        }
+        other => other,
+    }
+}
+
 fn extract_ollama_parameters(
     options: Option<serde_json::Value>,
-) -> (Option<f32>, Option<f32>, Option<i32>, Option<i32>) {
-    if let Some(opts) = options {
+    limits: &ParameterLimits,
+) -> OllamaParameters {
+    let mut params = if let Some(opts) = options {
         let temperature = opts
             .get("temperature")
             .and_then(|v| v.as_f64())
-            .map(|v| v as f32);
+            .map(|v| clamp_parameter("temperature", v as f32, &limits.temperature));
 
-        let top_p = opts.get("top_p").and_then(|v| v.as_f64()).map(|v| v as f32);
+        let top_p = opts
+            .get("top_p")
+            .and_then(|v| v.as_f64())
+            .map(|v| clamp_parameter("top_p", v as f32, &TOP_P_RANGE));
 
-        // Mistral doesn't support top_k directly, but we can use it to calculate max_tokens
+        // `num_predict` is Ollama's name and wins when a client sends both; `max_tokens` is
+        // accepted as a fallback for clients that use the OpenAI name.
         let max_tokens = opts
             .get("num_predict")
             .and_then(|v| v.as_i64())
-            .map(|v| v as i32);
+            .or_else(|| opts.get("max_tokens").and_then(|v| v.as_i64()))
+            .and_then(|v| clamp_max_tokens(v, limits.max_tokens));
 
         // Mistral uses random_seed instead of repeat_penalty
         let seed = opts.get("seed").and_then(|v| v.as_i64()).map(|v| v as i32);
 
-        (temperature, top_p, max_tokens, seed)
+        let n = opts
+            .get("n")
+            .and_then(|v| v.as_i64())
+            .filter(|&n| n > 1)
+            .map(|n| n as i32);
+
+        let safe_prompt = opts.get("safe_prompt").and_then(|v| v.as_bool());
+
+        // Ollama clients send either a single stop sequence or a list of them
+        let stop = match opts.get("stop") {
+            Some(serde_json::Value::String(s)) => Some(vec![s.clone()]),
+            Some(serde_json::Value::Array(values)) => Some(
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        // Alternatives are only reported alongside logprobs, so asking for them implies it
+        let top_logprobs = opts
+            .get("top_logprobs")
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32);
+        let logprobs = opts
+            .get("logprobs")
+            .and_then(|v| v.as_bool())
+            .or(top_logprobs.map(|_| true));
+
+        let num_ctx = opts
+            .get("num_ctx")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32);
+        if let Some(num_ctx) = num_ctx {
+            debug!(
+                "Backend has no per-request context window, ignoring num_ctx={}",
+                num_ctx
+            );
+            REQUESTED_CONTEXT_LENGTH.observe(f64::from(num_ctx));
+        }
+
+        OllamaParameters {
+            temperature,
+            top_p,
+            max_tokens,
+            random_seed: seed,
+            n,
+            safe_prompt,
+            stop,
+            num_ctx,
+            logprobs,
+            top_logprobs,
+        }
     } else {
-        (None, None, None, None)
+        OllamaParameters::default()
+    };
+
+    if let Some(max_output_tokens) = limits.max_output_tokens {
+        params.max_tokens = Some(apply_output_token_limit(
+            params.max_tokens,
+            max_output_tokens,
+        ));
+    }
+    params
+}
+
+/// Enforces the operator's output ceiling, which also applies when the client set no limit.
+/// Ollama's `-1` ("generate until done") and other non-positive values count as no limit.
+fn apply_output_token_limit(requested: Option<i32>, max_output_tokens: i32) -> i32 {
+    match requested.filter(|&requested| requested > 0) {
+        Some(requested) if requested <= max_output_tokens => requested,
+        Some(requested) => {
+            info!(
+                "Lowered max_tokens from {} to MAX_OUTPUT_TOKENS={}",
+                requested, max_output_tokens
+            );
+            max_output_tokens
+        }
+        None => {
+            debug!("Applying MAX_OUTPUT_TOKENS={}", max_output_tokens);
+            max_output_tokens
+        }
     }
 }
 
 pub async fn handle_generate(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<OllamaGenerateRequest>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    AppJson(mut req): AppJson<OllamaGenerateRequest>,
 ) -> Result<Response> {
+    req.model = state.resolve_model(req.model);
     info!("Handling generate request for model: {}", req.model);
+    state.check_model_allowed(&req.model)?;
+    let images = req.images.take().unwrap_or_default();
+    state.check_images_supported(&req.model, &images)?;
 
-    ACTIVE_REQUESTS.inc();
-    let _timer = HTTP_REQUEST_DURATION_SECONDS
-        .with_label_values(&["generate"])
-        .start_timer();
-    let _generate_timer = GENERATE_DURATION_SECONDS
-        .with_label_values(&[&req.model])
-        .start_timer();
+    let options = apply_model_defaults(
+        req.options,
+        state.model_defaults.get(&state.translate_model(&req.model)),
+    );
+    let params = extract_ollama_parameters(options, &state.parameter_limits);
+    let stream = wants_stream(req.stream, &headers);
+    let options = CompletionOptions {
+        stream,
+        deadline: Deadline::from_headers(&headers),
+        echo_prompt: req.echo.unwrap_or(false).then(|| req.prompt.clone()),
+        dry_run: dry_run_requested(&headers, query.as_deref()),
+        aggregate: stream && header_is_truthy(&headers, &AGGREGATE_STREAM_HEADER),
+        include_raw: state.debug_allow_raw && header_is_truthy(&headers, &INCLUDE_RAW_HEADER),
+        ..Default::default()
+    };
+
+    // A suffix means the client wants fill-in-the-middle completion rather than chat
+    if req.suffix.is_some() {
+        let fim_req = MistralFimRequest {
+            model: state.translate_model(&req.model),
+            prompt: req.prompt,
+            suffix: req.suffix,
+            stream: Some(stream),
+            temperature: params.temperature,
+            top_p: params.top_p,
+            max_tokens: params.max_tokens,
+            random_seed: params.random_seed,
+            stop: params.stop,
+        };
+        return run_completion("generate", &req.model, state, fim_req, options).await;
+    }
+
+    // Raw prompts skip chat templating, which needs the backend's plain completions endpoint,
+    // and that can't carry images
+    if req.raw.unwrap_or(false) && images.is_empty() {
+        if state.backend_completions_endpoint {
+            let model = state.translate_model(&req.model);
+
+            // Earlier turns are rendered with the model's template; the new prompt is appended
+            // verbatim, as raw mode promises
+            let mut messages = match &req.context {
+                Some(context) => state.context_store.retrieve(context).unwrap_or_else(|| {
+                    warn!("Unknown generate context, starting a new conversation");
+                    Vec::new()
+                }),
+                None => Vec::new(),
+            };
+            let template = state
+                .prompt_templates
+                .get(&model)
+                .map_or(DEFAULT_PROMPT_TEMPLATE, String::as_str);
+            let prompt = format!("{}{}", render_prompt(template, &messages), req.prompt);
+            messages.push(MistralMessage {
+                role: "user".to_string(),
+                content: req.prompt,
+                ..Default::default()
+            });
+
+            let text_req = MistralTextRequest {
+                model,
+                prompt,
+                stream: Some(stream),
+                temperature: params.temperature,
+                top_p: params.top_p,
+                max_tokens: params.max_tokens,
+                random_seed: params.random_seed,
+                stop: params.stop,
+                stream_options: None,
+            };
+            let options = CompletionOptions {
+                context_messages: Some(messages),
+                ..options
+            };
+            return run_completion("generate", &req.model, state, text_req, options).await;
+        }
+        debug!("Backend has no completions endpoint, wrapping raw prompt in a chat message");
+    }
 
-    let (temperature, top_p, max_tokens, random_seed) = extract_ollama_parameters(req.options);
+    let model = state.translate_model(&req.model);
+
+    // Replay the conversation the client's context refers to, if we still have it
+    let mut messages = match &req.context {
+        Some(context) => state.context_store.retrieve(context).unwrap_or_else(|| {
+            warn!("Unknown generate context, starting a new conversation");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    messages.push(MistralMessage {
+        role: "user".to_string(),
+        content: req.prompt,
+        images,
+        ..Default::default()
+    });
+    apply_system_prompt(&mut messages, state.system_prompts.get(&model));
+    limit_history(&mut messages, state.max_history_messages);
 
     let mistral_req = MistralChatRequest {
-        model: translate_model_name(&req.model),
-        messages: vec![MistralMessage {
-            role: "user".to_string(),
-            content: req.prompt.clone(),
-        }],
-        stream: req.stream,
-        temperature,
-        top_p,
-        max_tokens,
-        random_seed,
+        model,
+        messages,
+        stream: Some(stream),
+        temperature: params.temperature,
+        top_p: params.top_p,
+        max_tokens: params.max_tokens,
+        random_seed: params.random_seed,
+        stop: params.stop,
+        n: None,
+        tools: None,
+        tool_choice: None,
+        safe_prompt: params.safe_prompt,
+        logprobs: params.logprobs,
+        top_logprobs: params.top_logprobs,
+        stream_options: None,
     };
+    let options = CompletionOptions {
+        context_messages: Some(mistral_req.messages.clone()),
+        ..options
+    };
+    run_completion("generate", &req.model, state, mistral_req, options).await
+}
 
-    let result = if req.stream.unwrap_or(false) {
-        handle_streaming_request(state, mistral_req, false).await
-    } else {
-        handle_sync_request(state, mistral_req, false).await
+pub async fn handle_chat(
+    State(state): State<Arc<AppState>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    AppJson(mut req): AppJson<OllamaChatRequest>,
+) -> Result<Response> {
+    req.model = state.resolve_model(req.model);
+    info!("Handling chat request for model: {}", req.model);
+    state.check_model_allowed(&req.model)?;
+
+    let model = state.translate_model(&req.model);
+    let options = apply_model_defaults(req.options, state.model_defaults.get(&model));
+    let params = extract_ollama_parameters(options, &state.parameter_limits);
+    let stream = wants_stream(req.stream, &headers);
+
+    let mut messages: Vec<MistralMessage> = req.messages.into_iter().map(|m| m.into()).collect();
+
+    // System messages are resent by the client each turn, so only the exchange is remembered
+    let session = session_id(&headers)
+        .filter(|_| state.sessions.enabled())
+        .map(|id| {
+            let turn = messages
+                .iter()
+                .filter(|m| m.role != "system")
+                .cloned()
+                .collect();
+            let system_messages = messages.iter().take_while(|m| m.role == "system").count();
+            messages.splice(
+                system_messages..system_messages,
+                state.sessions.history(&id),
+            );
+            SessionTurn::new(state.sessions.clone(), id, turn)
+        });
+
+    apply_system_prompt(&mut messages, state.system_prompts.get(&model));
+    limit_history(&mut messages, state.max_history_messages);
+    mark_continuation(&mut messages);
+
+    let mistral_req = MistralChatRequest {
+        model,
+        messages,
+        stream: Some(stream),
+        temperature: params.temperature,
+        top_p: params.top_p,
+        max_tokens: params.max_tokens,
+        random_seed: params.random_seed,
+        stop: params.stop,
+        n: params.n,
+        tools: req.tools,
+        tool_choice: req.tool_choice,
+        safe_prompt: params.safe_prompt,
+        logprobs: params.logprobs,
+        top_logprobs: params.top_logprobs,
+        stream_options: None,
     };
 
-    ACTIVE_REQUESTS.dec();
+    let options = CompletionOptions {
+        stream,
+        is_chat: true,
+        deadline: Deadline::from_headers(&headers),
+        dry_run: dry_run_requested(&headers, query.as_deref()),
+        aggregate: stream && header_is_truthy(&headers, &AGGREGATE_STREAM_HEADER),
+        include_raw: state.debug_allow_raw && header_is_truthy(&headers, &INCLUDE_RAW_HEADER),
+        session,
+        ..Default::default()
+    };
+    run_completion("chat", &req.model, state, mistral_req, options).await
+}
 
-    match &result {
-        Ok(_) => HTTP_REQUESTS_TOTAL
-            .with_label_values(&["generate", "success", "none"])
-            .inc(),
-        Err(e) => HTTP_REQUESTS_TOTAL
-            .with_label_values(&["generate", "error", e.error_type()])
-            .inc(),
+/// Cancels an in-flight completion by the `X-Request-Id` it was sent with, aborting its backend
+/// call and closing its stream.
+pub async fn handle_abort(
+    State(state): State<Arc<AppState>>,
+    AppJson(req): AppJson<AbortRequest>,
+) -> Result<Json<serde_json::Value>> {
+    if !state.aborts.abort(&req.request_id) {
+        return Err(AppError::RequestNotFound {
+            request_id: req.request_id,
+        });
     }
+    info!("Aborted request {}", req.request_id);
+    Ok(Json(serde_json::json!({ "status": "aborted" })))
+}
 
-    result
+/// Whether to stream the response: the body's `stream` field when given, otherwise whether the
+/// client accepts an event stream.
+fn wants_stream(requested: Option<bool>, headers: &HeaderMap) -> bool {
+    requested.unwrap_or_else(|| {
+        headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|media_type| {
+                media_type
+                    .split(';')
+                    .next()
+                    .is_some_and(|t| t.trim().eq_ignore_ascii_case("text/event-stream"))
+            })
+    })
 }
 
-pub async fn handle_chat(
-    State(state): State<Arc<AppState>>,
-    Json(req): Json<OllamaChatRequest>,
+/// Whether the client asked, via `X-Dry-Run: true` or `?dry_run=1`, to see the translated
+/// backend request instead of having it sent.
+fn dry_run_requested(headers: &HeaderMap, query: Option<&str>) -> bool {
+    let param = query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .any(|(key, value)| key == "dry_run" && is_truthy(value));
+    header_is_truthy(headers, &DRY_RUN_HEADER) || param
+}
+
+/// The chat session the request names, ignoring empty or overlong ids.
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(&SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_SESSION_ID_LENGTH)
+        .map(str::to_string)
+}
+
+fn header_is_truthy(headers: &HeaderMap, name: &HeaderName) -> bool {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(is_truthy)
+}
+
+fn is_truthy(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+/// Sends a translated request under the endpoint's metrics and the client's deadline.
+///
+/// Dry runs return the request itself and are kept out of the metrics, since nothing is
+/// generated.
+async fn run_completion<R: MistralCompletionRequest>(
+    endpoint: &str,
+    ollama_model: &str,
+    state: Arc<AppState>,
+    req: R,
+    mut options: CompletionOptions,
 ) -> Result<Response> {
-    info!("Handling chat request for model: {}", req.model);
+    if options.dry_run {
+        let body = backend_request_body(&req, &state.backend_seed_field)?;
+        return Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response());
+    }
+
+    options.abort = request_id::current().map(|id| state.aborts.register(id));
+    let abort_token = options.abort.as_ref().map(AbortHandle::token);
 
     ACTIVE_REQUESTS.inc();
     let _timer = HTTP_REQUEST_DURATION_SECONDS
-        .with_label_values(&["chat"])
+        .with_label_values(&[endpoint])
         .start_timer();
+    let model_label = state.model_label(ollama_model).to_string();
     let _generate_timer = GENERATE_DURATION_SECONDS
-        .with_label_values(&[&req.model])
+        .with_label_values(&[&model_label])
         .start_timer();
+    options.model_label = model_label.clone();
 
-    let (temperature, top_p, max_tokens, random_seed) = extract_ollama_parameters(req.options);
-
-    let mistral_req = MistralChatRequest {
-        model: translate_model_name(&req.model),
-        messages: req.messages.into_iter().map(|m| m.into()).collect(),
-        stream: req.stream,
-        temperature,
-        top_p,
-        max_tokens,
-        random_seed,
-    };
-
-    let result = if req.stream.unwrap_or(false) {
-        handle_streaming_request(state, mistral_req, true).await
-    } else {
-        handle_sync_request(state, mistral_req, true).await
-    };
+    let deadline = options.deadline;
+    let redact_logs = state.log_redact_prompts;
+    let span = info_span!("completion", endpoint, model = %ollama_model);
+    let result = run_with_deadline(
+        deadline,
+        run_until_aborted(abort_token, send_completion_request(state, req, options)),
+    )
+    .instrument(span)
+    .await;
 
     ACTIVE_REQUESTS.dec();
 
     match &result {
         Ok(_) => HTTP_REQUESTS_TOTAL
-            .with_label_values(&["chat", "success", "none"])
-            .inc(),
-        Err(e) => HTTP_REQUESTS_TOTAL
-            .with_label_values(&["chat", "error", e.error_type()])
+            .with_label_values(&[endpoint, &model_label, "success", "none"])
             .inc(),
+        Err(e) => {
+            let message = if redact_logs {
+                e.redacted()
+            } else {
+                e.to_string()
+            };
+            warn!(
+                error_type = e.error_type(),
+                "Completion failed: {}", message
+            );
+            HTTP_REQUESTS_TOTAL
+                .with_label_values(&[endpoint, &model_label, "error", e.error_type()])
+                .inc()
+        }
     }
 
     result
 }
 
-async fn handle_sync_request(
-    state: Arc<AppState>,
-    req: MistralChatRequest,
+/// How a completion is sent to the backend and how its reply is shaped for the client.
+#[derive(Debug, Default)]
+struct CompletionOptions {
+    stream: bool,
     is_chat: bool,
-) -> Result<Response> {
-    let url = format!("{}/v1/chat/completions", state.mistral_url);
+    deadline: Option<Deadline>,
+    /// When set, the conversation including the reply is recorded in the context store and
+    /// its surrogate is returned as the generate response's `context`.
+    context_messages: Option<Vec<MistralMessage>>,
+    /// The chat session this exchange is recorded in once the reply is complete.
+    session: Option<SessionTurn>,
+    /// Prompt prepended to the generated text for clients that asked for `echo`.
+    echo_prompt: Option<String>,
+    /// Return the translated request instead of sending it.
+    dry_run: bool,
+    /// Stream from the backend but answer with one JSON response, for clients that send
+    /// `stream: true` yet can't read a stream.
+    aggregate: bool,
+    /// Include the backend's reply as it was received under `_raw`. Only non-streaming
+    /// responses fetched from the backend carry it; cached replies were parsed when stored.
+    include_raw: bool,
+    /// Held until the response, including a streamed body, is complete.
+    permit: Option<ConcurrencyPermit>,
+    /// Base URL of the backend chosen for this request.
+    backend: String,
+    /// Label for the client's model name, under which every model metric of this request is
+    /// recorded so they can be compared with one another.
+    model_label: String,
+    /// Keeps the request abortable via `POST /api/abort` until its response is complete.
+    abort: Option<AbortHandle>,
+}
 
-    let response = state
-        .client
-        .post(&url)
-        .json(&req)
-        .send()
-        .await
-        .map_err(|e| AppError::request_error(url.clone(), e))?;
+impl CompletionOptions {
+    /// The `endpoint` label recorded on metrics for this completion.
+    fn endpoint(&self) -> &'static str {
+        if self.is_chat {
+            "chat"
+        } else {
+            "generate"
+        }
+    }
+}
 
-    if !response.status().is_success() {
-        let error_text = response
-            .text()
+/// Parses a backend's JSON response, returning it with the body's size in bytes.
+///
+/// The size is taken from `Content-Length` when the backend sent one, so the body is only
+/// buffered separately from parsing when it didn't.
+async fn read_json_body<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    url: &str,
+    redact_logs: bool,
+) -> Result<(T, usize)> {
+    if let Some(len) = response.content_length() {
+        let value = response
+            .json()
             .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        error!("Mistral API error: {}", error_text);
-        return Err(AppError::internal_error(
-            "Mistral API returned non-success status",
-        ));
+            .map_err(|e| AppError::request_error(url.to_string(), e))?;
+        return Ok((value, len as usize));
     }
 
-    let mistral_response: MistralChatResponse = response
-        .json()
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| AppError::request_error(url.to_string(), e))?;
+    let value = serde_json::from_slice(&body).map_err(|e| {
+        // serde's messages quote the offending value, which may be generated text
+        if redact_logs {
+            error!(
+                "Backend returned invalid JSON at line {} column {}",
+                e.line(),
+                e.column()
+            );
+        } else {
+            error!("Backend returned invalid JSON: {}", e);
+        }
+        AppError::invalid_backend_response("body is not valid JSON")
+    })?;
+    Ok((value, body.len()))
+}
+
+/// Builds the error for a backend's non-success response, keeping its body for the logs only.
+///
+/// Backends often quote the offending prompt in error bodies, so the log redacts it when asked.
+async fn upstream_status_error(
+    response: reqwest::Response,
+    url: &str,
+    redact_logs: bool,
+) -> AppError {
+    let status = response.status();
+    let error_text = response
+        .text()
         .await
-        .map_err(|e| AppError::request_error(url.clone(), e))?;
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    if redact_logs {
+        error!("Mistral API error ({}): {}", status, redact(&error_text));
+    } else {
+        error!("Mistral API error ({}): {}", status, error_text);
+    }
+    AppError::upstream_status(status, error_text, url)
+}
+
+/// Sends `req` to the backend and converts the reply.
+async fn send_completion_request<R: MistralCompletionRequest>(
+    state: Arc<AppState>,
+    req: R,
+    mut options: CompletionOptions,
+) -> Result<Response> {
+    state
+        .rate_limiter
+        .check(req.model())
+        .map_err(|retry_after| AppError::RateLimited {
+            retry_after: Some(retry_after),
+        })?;
+
+    options.permit = state.concurrency_limit.acquire().await;
+
+    options.backend = state.backends.select().to_string();
+    let backend = options.backend.clone();
+    let expose_backend = state.expose_backend_header;
+
+    let mut response = if options.stream {
+        handle_streaming_request(state, req, options).await?
+    } else {
+        handle_sync_request(state, req, options).await?
+    };
+
+    if expose_backend {
+        if let Ok(value) = HeaderValue::from_str(&backend) {
+            response
+                .headers_mut()
+                .insert(PROXY_BACKEND_HEADER.clone(), value);
+        }
+    }
+    Ok(response)
+}
+
+async fn handle_sync_request<R: MistralCompletionRequest>(
+    state: Arc<AppState>,
+    req: R,
+    options: CompletionOptions,
+) -> Result<Response> {
+    let url = state.url_on(&options.backend, req.endpoint());
+
+    let endpoint = options.endpoint();
+    let cache_key = state.response_cache.key(options.stream, &req);
+    let cached = cache_key
+        .as_deref()
+        .and_then(|key| state.response_cache.get(key));
+    if cache_key.is_some() {
+        let counter: &CounterVec = if cached.is_some() {
+            &CACHE_HITS_TOTAL
+        } else {
+            &CACHE_MISSES_TOTAL
+        };
+        counter.with_label_values(&[endpoint]).inc();
+    }
+
+    // The backend's body as received, kept for `X-Include-Raw`. Cached replies no longer have
+    // one, so they are returned without `_raw`.
+    let mut raw = None;
+    // Unset for cached replies, which weren't generated by this request
+    let mut generated_in = None;
+    let mut mistral_response = match cached {
+        Some(response) => {
+            debug!("Serving completion from the response cache");
+            response
+        }
+        None => {
+            let started = Instant::now();
+            let backend_timer = BACKEND_DURATION_SECONDS
+                .with_label_values(&[endpoint])
+                .start_timer();
+            let response = send_to_backend(
+                &state,
+                endpoint,
+                &options.backend,
+                &url,
+                &req,
+                state.sync_request_timeout,
+            )
+            .await?;
+
+            if !response.status().is_success() {
+                return Err(upstream_status_error(response, &url, state.log_redact_prompts).await);
+            }
+
+            let (mistral_response, body_len): (MistralChatResponse, usize) = if options.include_raw
+            {
+                let (body, body_len): (serde_json::Value, usize) =
+                    read_json_body(response, &url, state.log_redact_prompts).await?;
+                let mistral_response = serde_json::from_value(body.clone()).map_err(|e| {
+                    // serde's messages quote the offending value, which may be generated text
+                    if state.log_redact_prompts {
+                        error!("Backend returned JSON that isn't a completion");
+                    } else {
+                        error!("Backend returned JSON that isn't a completion: {}", e);
+                    }
+                    AppError::invalid_backend_response("body is not a completion")
+                })?;
+                raw = Some(body);
+                (mistral_response, body_len)
+            } else {
+                read_json_body(response, &url, state.log_redact_prompts).await?
+            };
+            backend_timer.observe_duration();
+            generated_in = Some(started.elapsed());
+            RESPONSE_BYTES
+                .with_label_values(&[endpoint])
+                .observe(body_len as f64);
+            if let Some(key) = cache_key {
+                state.response_cache.store(key, mistral_response.clone());
+            }
+            mistral_response
+        }
+    };
+
+    let mut usage_estimated = false;
+    if mistral_response.usage.is_none() {
+        let completion = mistral_response
+            .choices
+            .first()
+            .and_then(|c| c.content_message())
+            .map(|m| m.content.as_str())
+            .unwrap_or_default();
+        mistral_response.usage = estimate_usage(estimate_tokens(&req.prompt_text()), completion);
+        usage_estimated = mistral_response.usage.is_some();
+    }
+    // Cached replies generated nothing, so only fresh completions are counted
+    if let Some((usage, elapsed)) = mistral_response.usage.as_ref().zip(generated_in) {
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&[&options.model_label])
+            .inc_by(f64::from(usage.completion_tokens));
+        observe_tokens_per_second(&options.model_label, usage.completion_tokens, elapsed);
+    }
 
-    let ollama_response = if is_chat {
-        serde_json::to_value(convert_mistral_to_ollama_chat(mistral_response, req.model))?
+    let model_name = req.model().to_string();
+    let mut ollama_response = if options.is_chat {
+        let chat_response =
+            convert_mistral_to_ollama_chat(mistral_response, model_name, &state.done_reasons)?;
+        if let Some(session) = options.session {
+            session.complete(chat_response.message.content.clone());
+        }
+        serde_json::to_value(chat_response)?
     } else {
-        serde_json::to_value(convert_mistral_to_ollama_generate(
-            mistral_response,
-            req.model,
-        ))?
+        let mut generate_response =
+            convert_mistral_to_ollama_generate(mistral_response, model_name, &state.done_reasons)?;
+        if let Some(mut messages) = options.context_messages {
+            messages.push(MistralMessage {
+                role: "assistant".to_string(),
+                content: generate_response.response.clone(),
+                ..Default::default()
+            });
+            generate_response.context = Some(state.context_store.store(messages));
+        }
+        // Only the text changes; eval_count still reflects the generated tokens alone
+        if let Some(prompt) = options.echo_prompt {
+            generate_response.response.insert_str(0, &prompt);
+        }
+        serde_json::to_value(generate_response)?
     };
+    if usage_estimated {
+        debug!("Backend omitted usage, returning estimated token counts");
+        ollama_response["token_counts_estimated"] = serde_json::json!(true);
+    }
+    if let Some(raw) = raw {
+        ollama_response["_raw"] = raw;
+    }
 
     Ok(Json(ollama_response).into_response())
 }
 
-async fn handle_streaming_request(
+async fn handle_streaming_request<R: MistralCompletionRequest>(
     state: Arc<AppState>,
-    req: MistralChatRequest,
-    is_chat: bool,
+    mut req: R,
+    options: CompletionOptions,
 ) -> Result<Response> {
-    let url = format!("{}/v1/chat/completions", state.mistral_url);
-    let model_name = req.model.clone();
+    let url = state.url_on(&options.backend, req.endpoint());
+    let model_name = req.model().to_string();
+    let started = Instant::now();
 
-    let response = state
-        .client
-        .post(&url)
-        .json(&req)
-        .send()
-        .await
-        .map_err(|e| AppError::request_error(url.clone(), e))?;
+    // Ask for a trailing usage chunk so token counts can be reported on the done chunk
+    if state.backend_supports_stream_usage {
+        req.request_stream_usage();
+    }
+
+    // Only until the backend starts streaming, matching the span the handler timer covers
+    let backend_timer = BACKEND_DURATION_SECONDS
+        .with_label_values(&[options.endpoint()])
+        .start_timer();
+    // Covers the whole stream, so a long generation needs the separate, larger budget
+    let response = send_to_backend(
+        &state,
+        options.endpoint(),
+        &options.backend,
+        &url,
+        &req,
+        state.stream_total_timeout,
+    )
+    .await?;
+    backend_timer.observe_duration();
 
     if !response.status().is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        error!("Mistral API error: {}", error_text);
-        return Err(AppError::streaming_error(error_text, &url));
+        return Err(upstream_status_error(response, &url, state.log_redact_prompts).await);
     }
 
     let mut headers = HeaderMap::new();
@@ -239,108 +1336,571 @@ async fn handle_streaming_request(
     headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
     headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
 
-    let stream = response.bytes_stream();
+    // Some backends ignore `stream: true` and answer with the whole completion at once, which
+    // would otherwise leave the client waiting for events that never come
+    let stream = if is_json_response(&response) {
+        warn!("Backend answered a streaming request with a complete JSON body");
+        let (mistral_response, _) =
+            read_json_body(response, &url, state.log_redact_prompts).await?;
+        let replay = replay_as_event_stream(mistral_response)?;
+        futures::stream::iter([Ok(replay)]).boxed()
+    } else {
+        response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(AppError::from))
+            .boxed()
+    };
     let (tx, rx) = tokio::sync::mpsc::channel(state.channel_buffer_size);
-    let max_line_length = state.max_line_length;
-
-    tokio::spawn(async move {
-        let mut buffer = String::new();
-        let mut stream = Box::pin(stream);
-
-        while let Some(chunk_result) = stream.next().await {
-            match chunk_result {
-                Ok(chunk) => {
-                    let chunk_str = String::from_utf8_lossy(&chunk);
-                    buffer.push_str(&chunk_str);
-
-                    // Check buffer size to prevent overflow
-                    if buffer.len() > max_line_length {
-                        error!(
-                            "Stream buffer exceeded maximum line length of {} bytes",
-                            max_line_length
-                        );
-                        let _ = tx.send(Err("Stream buffer overflow".to_string())).await;
-                        break;
-                    }
 
-                    while let Some(line_end) = buffer.find('\n') {
-                        let line = buffer.drain(..=line_end).collect::<String>();
-                        let line = line.trim();
+    let is_chat = options.is_chat;
+    let aggregate = options.aggregate;
+    let stream_guard = ActiveStreamGuard::new();
+    let settings = StreamSettings {
+        deadline: options.deadline,
+        echo_prompt: options.echo_prompt,
+        context_messages: options.context_messages,
+        session: options.session,
+        estimated_prompt_tokens: estimate_tokens(&req.prompt_text()),
+        started,
+        // Counted after registering this stream, so it includes itself
+        batch_size: batch_size_label(ACTIVE_STREAMS.get()),
+        ..StreamSettings::from_state(&state, options.model_label)
+    };
+    let permit = options.permit;
+    let abort = options.abort;
 
-                        if let Some(json_str) = line.strip_prefix("data: ") {
-                            if json_str == "[DONE]" {
-                                let _ = tx
-                                    .send(Ok(create_done_chunk(&model_name).to_string()))
-                                    .await;
-                                break;
-                            }
+    tokio::spawn(
+        async move {
+            // Dropped when forwarding ends, whether by completion, error, or client disconnect
+            let _stream_guard = stream_guard;
+            let _permit = permit;
+            let abort_tx = tx.clone();
+            let forward = async {
+                forward_mistral_stream(stream, tx, model_name, is_chat, settings).await;
+                Ok(())
+            };
+            // Cancelling drops the backend stream and closes the client's
+            if let Err(err) =
+                run_until_aborted(abort.as_ref().map(AbortHandle::token), forward).await
+            {
+                info!("Stream aborted");
+                let error_chunk = serde_json::json!({ "error": err.to_string() });
+                let _ = abort_tx.send(Ok(error_chunk.to_string())).await;
+            }
+        }
+        .instrument(tracing::Span::current()),
+    );
+
+    if aggregate {
+        return aggregate_stream(rx, is_chat, &url).await;
+    }
+
+    let events = ReceiverStream::new(rx).map(|result| {
+        result
+            .map(|data| format!("data: {data}\n\n"))
+            .map_err(std::io::Error::other)
+    });
+    let body = Body::from_stream(with_keepalive(events, state.stream_keepalive));
+
+    Ok((headers, body).into_response())
+}
+
+/// Collects a forwarded stream's chunks into the single response a non-streaming request would
+/// have received.
+async fn aggregate_stream(
+    mut rx: tokio::sync::mpsc::Receiver<std::result::Result<String, String>>,
+    is_chat: bool,
+    url: &str,
+) -> Result<Response> {
+    let mut chunks = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        let chunk: serde_json::Value =
+            serde_json::from_str(&chunk.map_err(|e| AppError::streaming_error(e, url))?)?;
+        if let Some(error) = chunk.get("error").and_then(|e| e.as_str()) {
+            return Err(AppError::streaming_error(error.to_string(), url));
+        }
+        chunks.push(chunk);
+    }
+    Ok(Json(aggregate_chunks(chunks, is_chat)).into_response())
+}
+
+/// Folds streamed Ollama chunks into one response: the done chunk's counts and reason, with
+/// the content, thinking and tool calls of every chunk before it.
+fn aggregate_chunks(chunks: Vec<serde_json::Value>, is_chat: bool) -> serde_json::Value {
+    let mut role = "assistant".to_string();
+    let mut content = String::new();
+    let mut thinking = String::new();
+    let mut tool_calls = Vec::new();
+    let mut response = serde_json::json!({ "done": true });
+    let text_field = if is_chat { "content" } else { "response" };
+
+    for chunk in chunks {
+        if chunk["done"] == true {
+            response = chunk;
+            continue;
+        }
+        let part = if is_chat { &chunk["message"] } else { &chunk };
+        content.push_str(part[text_field].as_str().unwrap_or_default());
+        thinking.push_str(part["thinking"].as_str().unwrap_or_default());
+        if let Some(calls) = part["tool_calls"].as_array() {
+            tool_calls.extend(calls.iter().cloned());
+        }
+        if let Some(chunk_role) = part["role"].as_str().filter(|r| !r.is_empty()) {
+            role = chunk_role.to_string();
+        }
+    }
+
+    let mut reply = serde_json::Map::new();
+    if is_chat {
+        reply.insert("role".to_string(), serde_json::json!(role));
+    }
+    reply.insert(text_field.to_string(), serde_json::json!(content));
+    if !thinking.is_empty() {
+        reply.insert("thinking".to_string(), serde_json::json!(thinking));
+    }
+    if !tool_calls.is_empty() {
+        reply.insert("tool_calls".to_string(), serde_json::json!(tool_calls));
+    }
+
+    if is_chat {
+        response["message"] = serde_json::Value::Object(reply);
+    } else if let Some(fields) = response.as_object_mut() {
+        fields.extend(reply);
+    }
+    response
+}
+
+fn is_json_response(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
 
-                            if let Ok(chunk) = serde_json::from_str::<MistralStreamChunk>(json_str)
+/// Re-encodes a complete response as a single stream event followed by `[DONE]`, so it can be
+/// forwarded like any other stream.
+fn replay_as_event_stream(response: MistralChatResponse) -> Result<Bytes> {
+    let chunk = MistralStreamChunk {
+        id: response.id,
+        object: "chat.completion.chunk".to_string(),
+        created: response.created,
+        model: response.model,
+        choices: response
+            .choices
+            .into_iter()
+            .map(|choice| MistralChoice {
+                delta: choice.message.or(choice.delta),
+                message: None,
+                ..choice
+            })
+            .collect(),
+        usage: response.usage,
+    };
+    Ok(Bytes::from(format!(
+        "data: {}\n\ndata: [DONE]\n\n",
+        serde_json::to_string(&chunk)?
+    )))
+}
+
+/// Limits and per-request adjustments applied while forwarding a single backend stream.
+#[derive(Debug, Clone)]
+struct StreamSettings {
+    max_line_length: usize,
+    idle_timeout: Option<Duration>,
+    /// The client's overall budget, which keeps applying after response headers are sent.
+    deadline: Option<Deadline>,
+    /// Prepended to the first content chunk.
+    echo_prompt: Option<String>,
+    /// Used for the done chunk's counts if the backend never reports usage.
+    estimated_prompt_tokens: Option<i32>,
+    /// Conversation recorded with the streamed reply once it completes, whose surrogate is
+    /// returned as the done chunk's `context`.
+    context_messages: Option<Vec<MistralMessage>>,
+    context_store: Arc<ContextStore>,
+    done_reasons: Arc<DoneReasons>,
+    /// Log unparseable lines by size only, since they may carry generated text.
+    redact_logs: bool,
+    /// Chat session the streamed reply is recorded in once it completes.
+    session: Option<SessionTurn>,
+    /// Label for the client's model name, rather than the backend's, on model metrics.
+    model_label: String,
+    /// Chunks after which the stream is ended early, against runaway generations.
+    max_chunks: Option<usize>,
+    /// When the backend request was sent, from which prefill is measured.
+    started: Instant,
+    /// `batch_size` label for the streams generating alongside this one.
+    batch_size: &'static str,
+}
+
+impl StreamSettings {
+    fn from_state(state: &AppState, model_label: String) -> Self {
+        StreamSettings {
+            max_line_length: state.max_line_length,
+            idle_timeout: state.stream_idle_timeout,
+            deadline: None,
+            echo_prompt: None,
+            estimated_prompt_tokens: None,
+            context_messages: None,
+            context_store: state.context_store.clone(),
+            done_reasons: state.done_reasons.clone(),
+            redact_logs: state.log_redact_prompts,
+            session: None,
+            model_label,
+            max_chunks: state.max_stream_chunks,
+            started: Instant::now(),
+            batch_size: batch_size_label(1),
+        }
+    }
+}
+
+/// Sends a chunk to the client, counting sends that had to wait because the client hasn't
+/// drained the channel buffer yet.
+async fn send_chunk(
+    tx: &Sender<std::result::Result<String, String>>,
+    chunk: String,
+    endpoint: &str,
+) -> std::result::Result<(), SendError<std::result::Result<String, String>>> {
+    match tx.try_send(Ok(chunk)) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(chunk)) => {
+            STREAM_CHANNEL_FULL_TOTAL
+                .with_label_values(&[endpoint])
+                .inc();
+            tx.send(chunk).await
+        }
+        Err(TrySendError::Closed(chunk)) => Err(SendError(chunk)),
+    }
+}
+
+/// Reads Mistral SSE events from `stream` and forwards them to `tx` as Ollama chunks.
+///
+/// Lines that fail to parse are counted and skipped so one corrupt event doesn't end the stream.
+async fn forward_mistral_stream<S, E>(
+    stream: S,
+    tx: Sender<std::result::Result<String, String>>,
+    model_name: String,
+    is_chat: bool,
+    mut settings: StreamSettings,
+) where
+    S: Stream<Item = std::result::Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    let endpoint = if is_chat { "chat" } else { "generate" };
+    // Observed however forwarding ends, including when the client disconnects
+    let mut response_bytes = StreamedBytes::new(RESPONSE_BYTES.with_label_values(&[endpoint]));
+    let mut buffer = LineBuffer::new();
+    let mut stream = Box::pin(stream);
+    let mut usage: Option<MistralUsage> = None;
+    let mut finish_reason: Option<String> = None;
+    let mut estimated_completion_tokens = Some(0);
+    let mut sent_first_chunk = false;
+    let mut chunks_sent = 0;
+    // Prefill ends at the first content chunk; each later chunk's gap is one token's decode time
+    let prefill_seconds =
+        PREFILL_DURATION_SECONDS.with_label_values(&[&settings.model_label, settings.batch_size]);
+    let decode_seconds =
+        DECODE_DURATION_SECONDS.with_label_values(&[&settings.model_label, settings.batch_size]);
+    let mut last_content_at: Option<Instant> = None;
+    // Only accumulated when the reply has to be recorded for `context` or a session
+    let mut reply =
+        (settings.context_messages.is_some() || settings.session.is_some()).then(String::new);
+    // Ollama clients expect one `created_at` across all chunks of a response
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    let max_line_length = settings.max_line_length;
+
+    loop {
+        let idle_until = settings
+            .idle_timeout
+            .map(|idle_timeout| tokio::time::Instant::now() + idle_timeout);
+        let wait_until = match (idle_until, settings.deadline.map(|d| d.at())) {
+            (Some(idle), Some(deadline)) => Some(idle.min(deadline)),
+            (idle, deadline) => idle.or(deadline),
+        };
+
+        let next = match wait_until {
+            Some(wait_until) => match tokio::time::timeout_at(wait_until, stream.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    let err = match settings.deadline {
+                        Some(deadline) if deadline.at() == wait_until => deadline.exceeded_error(),
+                        _ => AppError::streaming_error(
+                            format!(
+                                "no data from backend for {}s",
+                                settings.idle_timeout.unwrap_or_default().as_secs_f64()
+                            ),
+                            endpoint,
+                        ),
+                    };
+                    error!("{}", err);
+                    let error_chunk = serde_json::json!({ "error": err.to_string() });
+                    let _ = tx.send(Ok(error_chunk.to_string())).await;
+                    return;
+                }
+            },
+            None => stream.next().await,
+        };
+        let Some(chunk_result) = next else {
+            break;
+        };
+
+        match chunk_result {
+            Ok(chunk) => {
+                response_bytes.add(chunk.len());
+                buffer.extend(&chunk);
+
+                // Only a single unterminated line is bounded; complete lines are drained below
+                if buffer.partial_line_len() > max_line_length {
+                    error!(
+                        "Stream line exceeded maximum length of {} bytes",
+                        max_line_length
+                    );
+                    let _ = tx.send(Err("Stream buffer overflow".to_string())).await;
+                    break;
+                }
+
+                while let Some(line) = buffer.next_line() {
+                    if let Some(payload) = data_payload(&line) {
+                        if payload == b"[DONE]" {
+                            let done_chunk = create_final_chunk(
+                                &model_name,
+                                &created_at,
+                                usage.take(),
+                                estimated_completion_tokens,
+                                finish_reason.take(),
+                                reply.take(),
+                                &mut settings,
+                            );
+                            if send_chunk(&tx, done_chunk.to_string(), endpoint)
+                                .await
+                                .is_err()
                             {
-                                if let Some(choice) = chunk.choices.first() {
-                                    if let Some(delta) = &choice.delta {
-                                        let ollama_chunk = create_streaming_chunk(
-                                            &model_name,
-                                            &delta.content,
-                                            &delta.role,
-                                            is_chat,
-                                        );
-
-                                        let _ = tx.send(Ok(ollama_chunk.to_string())).await;
-                                        STREAMING_CHUNKS_TOTAL
-                                            .with_label_values(&[if is_chat {
-                                                "chat"
-                                            } else {
-                                                "generate"
-                                            }])
-                                            .inc();
+                                debug!("Client disconnected before done chunk");
+                                return;
+                            }
+                            break;
+                        }
+
+                        let chunk = match serde_json::from_slice::<MistralStreamChunk>(payload) {
+                            Ok(chunk) => chunk,
+                            Err(e) => {
+                                STREAM_PARSE_ERRORS_TOTAL
+                                    .with_label_values(&[endpoint])
+                                    .inc();
+                                let line = String::from_utf8_lossy(payload);
+                                if settings.redact_logs {
+                                    debug!("Skipping unparseable stream chunk: {}", redact(&line));
+                                } else {
+                                    debug!(
+                                        "Skipping unparseable stream chunk ({}): {}",
+                                        e,
+                                        truncate_for_log(&line, MAX_LOGGED_LINE_CHARS)
+                                    );
+                                }
+                                continue;
+                            }
+                        };
+
+                        if let Some(choice) = chunk.choices.first() {
+                            if choice.finish_reason.is_some() {
+                                finish_reason.clone_from(&choice.finish_reason);
+                            }
+                            if let Some(delta) = &choice.delta {
+                                // Summed per delta, since the full text is never buffered
+                                estimated_completion_tokens = estimated_completion_tokens
+                                    .zip(estimate_tokens(&delta.content))
+                                    .map(|(total, tokens)| total + tokens);
+                                if let Some(reply) = &mut reply {
+                                    reply.push_str(&delta.content);
+                                }
+                                let content = match settings.echo_prompt.take() {
+                                    Some(prompt) => format!("{}{}", prompt, delta.content),
+                                    None => delta.content.clone(),
+                                };
+                                let mut ollama_chunk = create_streaming_chunk(
+                                    &model_name,
+                                    &created_at,
+                                    &content,
+                                    delta.reasoning_content.as_deref(),
+                                    &delta.role,
+                                    is_chat,
+                                );
+
+                                if let Some(logprobs) = &choice.logprobs {
+                                    ollama_chunk["logprobs"] = logprobs.clone();
+                                }
+
+                                if is_chat {
+                                    if let Some(tool_calls) = &delta.tool_calls {
+                                        ollama_chunk["message"]["tool_calls"] =
+                                            serde_json::json!(convert_tool_calls(tool_calls));
                                     }
                                 }
+
+                                // Some backends report usage on every chunk; surface the prompt
+                                // count as early as it is known.
+                                if !sent_first_chunk {
+                                    if let Some(chunk_usage) = &chunk.usage {
+                                        ollama_chunk["prompt_eval_count"] =
+                                            serde_json::json!(chunk_usage.prompt_tokens);
+                                    }
+                                    sent_first_chunk = true;
+                                }
+
+                                if !delta.content.is_empty() {
+                                    let now = Instant::now();
+                                    match last_content_at {
+                                        Some(previous) => decode_seconds
+                                            .observe(now.duration_since(previous).as_secs_f64()),
+                                        None => prefill_seconds.observe(
+                                            now.duration_since(settings.started).as_secs_f64(),
+                                        ),
+                                    }
+                                    last_content_at = Some(now);
+                                }
+
+                                if send_chunk(&tx, ollama_chunk.to_string(), endpoint)
+                                    .await
+                                    .is_err()
+                                {
+                                    debug!("Client disconnected, stopping stream");
+                                    return;
+                                }
+                                STREAMING_CHUNKS_TOTAL.with_label_values(&[endpoint]).inc();
+                                chunks_sent += 1;
+
+                                if settings.max_chunks.is_some_and(|max| chunks_sent >= max) {
+                                    warn!(
+                                        "Stream reached the limit of {} chunks, ending it",
+                                        chunks_sent
+                                    );
+                                    STREAMS_TRUNCATED_TOTAL.with_label_values(&[endpoint]).inc();
+                                    let mut done_chunk = create_final_chunk(
+                                        &model_name,
+                                        &created_at,
+                                        usage.take(),
+                                        estimated_completion_tokens,
+                                        None,
+                                        reply.take(),
+                                        &mut settings,
+                                    );
+                                    done_chunk["done_reason"] = serde_json::json!("length");
+                                    let _ = send_chunk(&tx, done_chunk.to_string(), endpoint).await;
+                                    return;
+                                }
                             }
                         }
+
+                        // The final chunk may carry only usage and no delta.
+                        if let Some(chunk_usage) = chunk.usage {
+                            usage = Some(chunk_usage);
+                        }
                     }
                 }
-                Err(e) => {
-                    error!("Stream error: {}", e);
-                    let _ = tx.send(Err(e.to_string())).await;
-                    break;
-                }
+            }
+            Err(e) => {
+                error!("Stream error: {}", e);
+                let _ = tx.send(Err(e.to_string())).await;
+                break;
             }
         }
-    });
-
-    let stream = ReceiverStream::new(rx);
-    let body = Body::from_stream(stream.map(|result| {
-        result
-            .map(|data| format!("data: {data}\n\n"))
-            .map_err(std::io::Error::other)
-    }));
+    }
+}
 
-    Ok((headers, body).into_response())
+/// Builds the done chunk ending a stream, with usage estimated when the backend reported none,
+/// and records the conversation when the client asked for `context` or named a session.
+fn create_final_chunk(
+    model_name: &str,
+    created_at: &str,
+    mut usage: Option<MistralUsage>,
+    estimated_completion_tokens: Option<i32>,
+    finish_reason: Option<String>,
+    reply: Option<String>,
+    settings: &mut StreamSettings,
+) -> serde_json::Value {
+    let usage_estimated = usage.is_none();
+    if usage_estimated {
+        usage = settings
+            .estimated_prompt_tokens
+            .zip(estimated_completion_tokens)
+            .map(|(prompt_tokens, completion_tokens)| MistralUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            });
+    }
+    if let Some(usage) = &usage {
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&[&settings.model_label])
+            .inc_by(f64::from(usage.completion_tokens));
+        observe_tokens_per_second(
+            &settings.model_label,
+            usage.completion_tokens,
+            settings.started.elapsed(),
+        );
+    }
+    let mut done_chunk = create_done_chunk(model_name, created_at, usage.as_ref());
+    if usage_estimated && usage.is_some() {
+        done_chunk["token_counts_estimated"] = serde_json::json!(true);
+    }
+    if let Some(finish_reason) = finish_reason {
+        done_chunk["done_reason"] =
+            serde_json::json!(settings.done_reasons.normalize(&finish_reason));
+    }
+    if let Some(session) = settings.session.take() {
+        session.complete(reply.clone().unwrap_or_default());
+    }
+    if let Some(mut messages) = settings.context_messages.take() {
+        messages.push(MistralMessage {
+            role: "assistant".to_string(),
+            content: reply.unwrap_or_default(),
+            ..Default::default()
+        });
+        done_chunk["context"] = serde_json::json!(settings.context_store.store(messages));
+    }
+    done_chunk
 }
 
-fn translate_model_name(ollama_name: &str) -> String {
-    match ollama_name {
-        "mistral:latest" => "mistral-7b".to_string(),
-        "mistral:7b" => "mistral-7b".to_string(),
-        "mixtral:latest" => "mixtral-8x7b".to_string(),
-        "mixtral:8x7b" => "mixtral-8x7b".to_string(),
-        name => name.to_string(),
+const MAX_LOGGED_LINE_CHARS: usize = 200;
+
+fn truncate_for_log(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}...", &s[..idx]),
+        None => s.to_string(),
     }
 }
 
+/// Looks up `ollama_name` in the model map, passing unmapped names through unchanged.
+fn translate_model_name(model_map: &HashMap<String, String>, ollama_name: &str) -> String {
+    model_map
+        .get(ollama_name)
+        .cloned()
+        .unwrap_or_else(|| ollama_name.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    fn limits(temperature: RangeInclusive<f32>) -> ParameterLimits {
+        ParameterLimits {
+            temperature,
+            max_tokens: 32_768,
+            max_output_tokens: None,
+        }
+    }
+
     #[test]
     fn test_translate_model_name() {
-        assert_eq!(translate_model_name("mistral:latest"), "mistral-7b");
-        assert_eq!(translate_model_name("mistral:7b"), "mistral-7b");
-        assert_eq!(translate_model_name("mixtral:latest"), "mixtral-8x7b");
-        assert_eq!(translate_model_name("mixtral:8x7b"), "mixtral-8x7b");
-        assert_eq!(translate_model_name("custom-model"), "custom-model");
+        let map = crate::config::default_model_map();
+        assert_eq!(translate_model_name(&map, "mistral:latest"), "mistral-7b");
+        assert_eq!(translate_model_name(&map, "mistral:7b"), "mistral-7b");
+        assert_eq!(translate_model_name(&map, "mixtral:latest"), "mixtral-8x7b");
+        assert_eq!(translate_model_name(&map, "mixtral:8x7b"), "mixtral-8x7b");
+        assert_eq!(translate_model_name(&map, "custom-model"), "custom-model");
     }
 
     #[test]
@@ -352,20 +1912,295 @@ mod tests {
             "seed": 42
         }));
 
-        let (temp, top_p, max_tokens, seed) = extract_ollama_parameters(options);
-        assert_eq!(temp, Some(0.7));
-        assert_eq!(top_p, Some(0.9));
-        assert_eq!(max_tokens, Some(100));
-        assert_eq!(seed, Some(42));
+        let params = extract_ollama_parameters(options, &limits(0.0..=2.0));
+        assert_eq!(params.temperature, Some(0.7));
+        assert_eq!(params.top_p, Some(0.9));
+        assert_eq!(params.max_tokens, Some(100));
+        assert_eq!(params.random_seed, Some(42));
+        assert_eq!(params.n, None);
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_clamps_out_of_range() {
+        let options = Some(json!({
+            "temperature": 3.5,
+            "top_p": 1.7
+        }));
+
+        let params = extract_ollama_parameters(options, &limits(0.0..=1.5));
+        assert_eq!(params.temperature, Some(1.5));
+        assert_eq!(params.top_p, Some(1.0));
+
+        let options = Some(json!({
+            "temperature": -0.5,
+            "top_p": -0.1
+        }));
+
+        let params = extract_ollama_parameters(options, &limits(0.0..=1.5));
+        assert_eq!(params.temperature, Some(0.0));
+        assert_eq!(params.top_p, Some(0.0));
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_in_range_passes_through() {
+        let options = Some(json!({
+            "temperature": 1.2,
+            "top_p": 0.5
+        }));
+
+        let params = extract_ollama_parameters(options, &limits(0.0..=2.0));
+        assert_eq!(params.temperature, Some(1.2));
+        assert_eq!(params.top_p, Some(0.5));
+    }
+
+    #[test]
+    fn test_apply_model_defaults() {
+        let defaults = json!({"temperature": 0.2, "top_p": 0.9});
+        let defaults = defaults.as_object();
+
+        assert_eq!(
+            apply_model_defaults(None, defaults),
+            Some(json!({"temperature": 0.2, "top_p": 0.9}))
+        );
+        assert_eq!(
+            apply_model_defaults(Some(json!({"temperature": 1.0})), defaults),
+            Some(json!({"temperature": 1.0, "top_p": 0.9}))
+        );
+        assert_eq!(
+            apply_model_defaults(Some(json!({"seed": 1})), None),
+            Some(json!({"seed": 1}))
+        );
     }
 
     #[test]
     fn test_extract_ollama_parameters_none() {
-        let (temp, top_p, max_tokens, seed) = extract_ollama_parameters(None);
-        assert_eq!(temp, None);
-        assert_eq!(top_p, None);
-        assert_eq!(max_tokens, None);
-        assert_eq!(seed, None);
+        let params = extract_ollama_parameters(None, &limits(0.0..=2.0));
+        assert_eq!(params, OllamaParameters::default());
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_n() {
+        let params = extract_ollama_parameters(Some(json!({"n": 3})), &limits(0.0..=2.0));
+        assert_eq!(params.n, Some(3));
+
+        // A single completion is the default, so it isn't forwarded
+        let params = extract_ollama_parameters(Some(json!({"n": 1})), &limits(0.0..=2.0));
+        assert_eq!(params.n, None);
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_safe_prompt() {
+        let params =
+            extract_ollama_parameters(Some(json!({"safe_prompt": true})), &limits(0.0..=2.0));
+        assert_eq!(params.safe_prompt, Some(true));
+
+        let params =
+            extract_ollama_parameters(Some(json!({"safe_prompt": false})), &limits(0.0..=2.0));
+        assert_eq!(params.safe_prompt, Some(false));
+
+        let params =
+            extract_ollama_parameters(Some(json!({"temperature": 0.5})), &limits(0.0..=2.0));
+        assert_eq!(params.safe_prompt, None);
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_logprobs() {
+        let params = extract_ollama_parameters(Some(json!({"logprobs": true})), &limits(0.0..=2.0));
+        assert_eq!(params.logprobs, Some(true));
+        assert_eq!(params.top_logprobs, None);
+
+        // top_logprobs alone turns logprobs on
+        let params =
+            extract_ollama_parameters(Some(json!({"top_logprobs": 3})), &limits(0.0..=2.0));
+        assert_eq!(params.logprobs, Some(true));
+        assert_eq!(params.top_logprobs, Some(3));
+
+        let params = extract_ollama_parameters(None, &limits(0.0..=2.0));
+        assert_eq!(params.logprobs, None);
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_stop_string() {
+        let params = extract_ollama_parameters(Some(json!({"stop": "\n\n"})), &limits(0.0..=2.0));
+        assert_eq!(params.stop, Some(vec!["\n\n".to_string()]));
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_stop_array() {
+        let params =
+            extract_ollama_parameters(Some(json!({"stop": ["</s>", "User:"]})), &limits(0.0..=2.0));
+        assert_eq!(
+            params.stop,
+            Some(vec!["</s>".to_string(), "User:".to_string()])
+        );
+
+        let params =
+            extract_ollama_parameters(Some(json!({"temperature": 0.5})), &limits(0.0..=2.0));
+        assert_eq!(params.stop, None);
+    }
+
+    fn contents(messages: &[MistralMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_system_and_latest_user() {
+        let mut messages = vec![
+            message("system", "sys"),
+            message("user", "u1"),
+            message("assistant", "a1"),
+            message("user", "u2"),
+            message("assistant", "a2"),
+            message("user", "u3"),
+        ];
+
+        assert!(truncate_history(&mut messages, 3));
+        assert_eq!(contents(&messages), ["sys", "a2", "u3"]);
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_pinned_messages_beyond_limit() {
+        let mut messages = vec![
+            message("system", "sys"),
+            message("user", "u1"),
+            message("assistant", "a1"),
+            message("user", "u2"),
+        ];
+
+        assert!(truncate_history(&mut messages, 1));
+        assert_eq!(contents(&messages), ["sys", "u2"]);
+    }
+
+    #[test]
+    fn test_short_history_untouched() {
+        let mut messages = vec![message("system", "sys"), message("user", "u1")];
+
+        assert!(!truncate_history(&mut messages, 2));
+        assert_eq!(contents(&messages), ["sys", "u1"]);
+
+        let truncated_before = HISTORY_TRUNCATED_TOTAL.get();
+        limit_history(&mut messages, None);
+        assert_eq!(contents(&messages), ["sys", "u1"]);
+        assert_eq!(HISTORY_TRUNCATED_TOTAL.get(), truncated_before);
+    }
+
+    #[test]
+    fn test_extract_ollama_parameters_num_ctx() {
+        let observed_before = REQUESTED_CONTEXT_LENGTH.get_sample_count();
+
+        let params = extract_ollama_parameters(Some(json!({"num_ctx": 8192})), &limits(0.0..=2.0));
+        assert_eq!(params.num_ctx, Some(8192));
+        assert!(REQUESTED_CONTEXT_LENGTH.get_sample_count() > observed_before);
+
+        let params =
+            extract_ollama_parameters(Some(json!({"temperature": 0.5})), &limits(0.0..=2.0));
+        assert_eq!(params.num_ctx, None);
+    }
+
+    #[test]
+    fn test_num_predict_wins_over_max_tokens() {
+        let params = extract_ollama_parameters(
+            Some(json!({"num_predict": 100, "max_tokens": 200})),
+            &limits(0.0..=2.0),
+        );
+        assert_eq!(params.max_tokens, Some(100));
+    }
+
+    #[test]
+    fn test_max_tokens_used_without_num_predict() {
+        let params =
+            extract_ollama_parameters(Some(json!({"max_tokens": 200})), &limits(0.0..=2.0));
+        assert_eq!(params.max_tokens, Some(200));
+
+        let params =
+            extract_ollama_parameters(Some(json!({"num_predict": 50})), &limits(0.0..=2.0));
+        assert_eq!(params.max_tokens, Some(50));
+    }
+
+    fn output_limited(max_output_tokens: i32) -> ParameterLimits {
+        ParameterLimits {
+            max_output_tokens: Some(max_output_tokens),
+            ..limits(0.0..=2.0)
+        }
+    }
+
+    #[test]
+    fn test_max_output_tokens_leaves_lower_requests_unchanged() {
+        let params =
+            extract_ollama_parameters(Some(json!({"num_predict": 100})), &output_limited(512));
+        assert_eq!(params.max_tokens, Some(100));
+    }
+
+    #[test]
+    fn test_max_output_tokens_clamps_higher_requests() {
+        let params =
+            extract_ollama_parameters(Some(json!({"num_predict": 2048})), &output_limited(512));
+        assert_eq!(params.max_tokens, Some(512));
+    }
+
+    #[test]
+    fn test_max_output_tokens_applied_when_omitted() {
+        let params =
+            extract_ollama_parameters(Some(json!({"temperature": 0.5})), &output_limited(512));
+        assert_eq!(params.max_tokens, Some(512));
+
+        let params = extract_ollama_parameters(None, &output_limited(512));
+        assert_eq!(params.max_tokens, Some(512));
+    }
+
+    #[test]
+    fn test_max_output_tokens_applied_to_unlimited_requests() {
+        for num_predict in [-1, -2, 0] {
+            let params = extract_ollama_parameters(
+                Some(json!({"num_predict": num_predict})),
+                &output_limited(512),
+            );
+            assert_eq!(params.max_tokens, Some(512), "num_predict {num_predict}");
+        }
+    }
+
+    #[test]
+    fn test_max_tokens_clamped_to_cap() {
+        let limits = ParameterLimits {
+            max_tokens: 4096,
+            ..limits(0.0..=2.0)
+        };
+        let params = extract_ollama_parameters(Some(json!({"num_predict": 1_000_000})), &limits);
+        assert_eq!(params.max_tokens, Some(4096));
+
+        let params =
+            extract_ollama_parameters(Some(json!({"max_tokens": 10_000_000_000i64})), &limits);
+        assert_eq!(params.max_tokens, Some(4096));
+    }
+
+    #[test]
+    fn test_non_positive_max_tokens_sets_no_limit() {
+        let limits = ParameterLimits {
+            max_tokens: 4096,
+            ..limits(0.0..=2.0)
+        };
+        for num_predict in [-3_000_000_000i64, -1, 0] {
+            let params =
+                extract_ollama_parameters(Some(json!({"num_predict": num_predict})), &limits);
+            assert_eq!(params.max_tokens, None, "num_predict {num_predict}");
+        }
+    }
+
+    #[test]
+    fn test_safe_prompt_omitted_from_request_when_absent() {
+        let req = MistralChatRequest {
+            model: "mistral-7b".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert!(json.get("safe_prompt").is_none());
+
+        let req = MistralChatRequest {
+            safe_prompt: Some(true),
+            ..req
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["safe_prompt"], true);
     }
 
     #[test]
@@ -373,10 +2208,317 @@ mod tests {
         let ollama_msg = OllamaMessage {
             role: "user".to_string(),
             content: "Hello, world!".to_string(),
+            ..Default::default()
         };
 
         let mistral_msg: MistralMessage = ollama_msg.into();
         assert_eq!(mistral_msg.role, "user");
         assert_eq!(mistral_msg.content, "Hello, world!");
     }
+
+    fn message(role: &str, content: &str) -> MistralMessage {
+        MistralMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trailing_assistant_message_marked_as_prefix() {
+        let mut messages = vec![
+            message("user", "Write a haiku"),
+            message("assistant", "Autumn moonlight -"),
+        ];
+        mark_continuation(&mut messages);
+        assert_eq!(messages[0].prefix, None);
+        assert_eq!(messages[1].prefix, Some(true));
+
+        let mut messages = vec![message("assistant", "Hi"), message("user", "Hello")];
+        mark_continuation(&mut messages);
+        assert!(messages.iter().all(|m| m.prefix.is_none()));
+    }
+
+    #[test]
+    fn test_apply_system_prompt_injects_when_absent() {
+        let mut messages = vec![message("user", "Hi")];
+        apply_system_prompt(&mut messages, Some(&"Be concise.".to_string()));
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[0].content, "Be concise.");
+        assert_eq!(messages[1].content, "Hi");
+    }
+
+    #[test]
+    fn test_apply_system_prompt_keeps_client_system_message() {
+        let mut messages = vec![message("system", "Client persona"), message("user", "Hi")];
+        apply_system_prompt(&mut messages, Some(&"Be concise.".to_string()));
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Client persona");
+    }
+
+    #[test]
+    fn test_apply_system_prompt_without_configured_prompt() {
+        let mut messages = vec![message("user", "Hi")];
+        apply_system_prompt(&mut messages, None);
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, "user");
+    }
+
+    fn sse_event(content: &str) -> String {
+        format!(
+            "data: {}\n",
+            json!({
+                "id": "chunk",
+                "object": "chat.completion.chunk",
+                "created": 1234567890,
+                "model": "mistral-7b",
+                "choices": [{
+                    "index": 0,
+                    "delta": {"role": "assistant", "content": content},
+                    "finish_reason": null
+                }]
+            })
+        )
+    }
+
+    fn test_settings() -> StreamSettings {
+        StreamSettings {
+            max_line_length: 1_000_000,
+            idle_timeout: None,
+            deadline: None,
+            echo_prompt: None,
+            estimated_prompt_tokens: None,
+            context_messages: None,
+            context_store: Arc::new(ContextStore::new(0)),
+            done_reasons: Arc::default(),
+            redact_logs: false,
+            session: None,
+            model_label: "mistral:latest".to_string(),
+            max_chunks: None,
+            started: Instant::now(),
+            batch_size: batch_size_label(1),
+        }
+    }
+
+    async fn collect_forwarded(events: Vec<String>) -> Vec<std::result::Result<String, String>> {
+        collect_forwarded_with(events, test_settings()).await
+    }
+
+    async fn collect_forwarded_with(
+        events: Vec<String>,
+        settings: StreamSettings,
+    ) -> Vec<std::result::Result<String, String>> {
+        let stream = futures::stream::iter(
+            events
+                .into_iter()
+                .map(|e| Ok::<_, std::io::Error>(Bytes::from(e))),
+        );
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        forward_mistral_stream(stream, tx, "mistral-7b".to_string(), true, settings).await;
+
+        let mut out = Vec::new();
+        while let Some(item) = rx.recv().await {
+            out.push(item);
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_many_short_lines_in_one_chunk_within_line_limit() {
+        let event = sse_event("Hi");
+        let settings = StreamSettings {
+            max_line_length: event.len() * 2,
+            ..test_settings()
+        };
+        // One network chunk far larger than the limit, made of complete short lines
+        let mut chunk = event.repeat(50);
+        chunk.push_str("data: [DONE]\n");
+
+        let forwarded = collect_forwarded_with(vec![chunk], settings).await;
+
+        assert_eq!(forwarded.len(), 51);
+        assert!(forwarded.iter().all(|item| item.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_unterminated_line_ends_stream() {
+        let settings = StreamSettings {
+            max_line_length: 1024,
+            ..test_settings()
+        };
+        let giant = format!("data: {}", "x".repeat(2048));
+
+        let forwarded =
+            collect_forwarded_with(vec![sse_event("Hi"), giant, sse_event("late")], settings).await;
+
+        assert_eq!(forwarded.len(), 2);
+        assert!(forwarded[0].is_ok());
+        assert_eq!(forwarded[1], Err("Stream buffer overflow".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stream_continues_past_malformed_chunk() {
+        let parse_errors_before = STREAM_PARSE_ERRORS_TOTAL.with_label_values(&["chat"]).get();
+
+        let forwarded = collect_forwarded(vec![
+            sse_event("Hello"),
+            "data: {not valid json\n".to_string(),
+            sse_event(" world"),
+            "data: [DONE]\n".to_string(),
+        ])
+        .await;
+
+        let chunks: Vec<serde_json::Value> = forwarded
+            .into_iter()
+            .map(|r| serde_json::from_str(&r.unwrap()).unwrap())
+            .collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0]["message"]["content"], "Hello");
+        assert_eq!(chunks[1]["message"]["content"], " world");
+        assert_eq!(chunks[2]["done"], true);
+
+        let parse_errors_after = STREAM_PARSE_ERRORS_TOTAL.with_label_values(&["chat"]).get();
+        assert!(parse_errors_after >= parse_errors_before + 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_accepts_usage_only_chunk() {
+        let usage_event = format!(
+            "data: {}\n",
+            json!({
+                "id": "chunk",
+                "object": "chat.completion.chunk",
+                "created": 1234567890,
+                "model": "mistral-7b",
+                "choices": [],
+                "usage": {"prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12}
+            })
+        );
+
+        let forwarded = collect_forwarded(vec![
+            sse_event("Hi"),
+            usage_event,
+            "data: [DONE]\n".to_string(),
+        ])
+        .await;
+
+        assert_eq!(forwarded.len(), 2);
+        let done: serde_json::Value = serde_json::from_str(forwarded[1].as_ref().unwrap()).unwrap();
+        assert_eq!(done["done"], true);
+    }
+
+    #[tokio::test]
+    async fn test_stream_finish_reason_normalized_on_done_chunk() {
+        let finish_event = format!(
+            "data: {}\n",
+            json!({
+                "id": "chunk",
+                "object": "chat.completion.chunk",
+                "created": 1234567890,
+                "model": "mistral-7b",
+                "choices": [{
+                    "index": 0,
+                    "delta": {"role": "assistant", "content": ""},
+                    "finish_reason": "max_tokens"
+                }]
+            })
+        );
+
+        let forwarded = collect_forwarded(vec![
+            sse_event("Hi"),
+            finish_event,
+            "data: [DONE]\n".to_string(),
+        ])
+        .await;
+
+        let done: serde_json::Value =
+            serde_json::from_str(forwarded.last().unwrap().as_ref().unwrap()).unwrap();
+        assert_eq!(done["done"], true);
+        assert_eq!(done["done_reason"], "length");
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_ends_after_idle_timeout() {
+        let stream = futures::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from(sse_event(
+            "Hello",
+        )))])
+        .chain(futures::stream::pending());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let settings = StreamSettings {
+            idle_timeout: Some(Duration::from_millis(100)),
+            ..test_settings()
+        };
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            forward_mistral_stream(stream, tx, "mistral-7b".to_string(), true, settings),
+        )
+        .await
+        .expect("stream should end once the idle timeout elapses");
+
+        let first: serde_json::Value =
+            serde_json::from_str(&rx.recv().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["message"]["content"], "Hello");
+
+        let last: serde_json::Value =
+            serde_json::from_str(&rx.recv().await.unwrap().unwrap()).unwrap();
+        assert!(last["error"]
+            .as_str()
+            .unwrap()
+            .contains("no data from backend"));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_when_deadline_passes() {
+        let stream = futures::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from(sse_event(
+            "Hello",
+        )))])
+        .chain(futures::stream::pending());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let settings = StreamSettings {
+            idle_timeout: Some(Duration::from_secs(60)),
+            deadline: Some(Deadline::new(Duration::from_millis(100))),
+            ..test_settings()
+        };
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            forward_mistral_stream(stream, tx, "mistral-7b".to_string(), true, settings),
+        )
+        .await
+        .expect("stream should end once the deadline passes");
+
+        let first: serde_json::Value =
+            serde_json::from_str(&rx.recv().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["message"]["content"], "Hello");
+
+        let last: serde_json::Value =
+            serde_json::from_str(&rx.recv().await.unwrap().unwrap()).unwrap();
+        assert!(last["error"].as_str().unwrap().contains("deadline"));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let future = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let parsed = parse_retry_after(&future).unwrap();
+        assert!(parsed > Duration::from_secs(25) && parsed <= Duration::from_secs(30));
+
+        // A date in the past means there's no need to wait
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_truncate_for_log() {
+        assert_eq!(truncate_for_log("short", 10), "short");
+        assert_eq!(truncate_for_log("abcdef", 3), "abc...");
+        assert_eq!(truncate_for_log("héllo", 2), "hé...");
+    }
 }
@@ -1,2 +1,3 @@
 pub mod chat;
 pub mod models;
+pub mod system;
@@ -1,43 +1,232 @@
-use axum::{extract::State, response::IntoResponse, Json};
-use std::sync::Arc;
-use tracing::info;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
 use crate::error::{AppError, Result};
-use crate::handlers::chat::AppState;
+use crate::extract::AppJson;
+use crate::handlers::chat::{with_request_id, with_timeout, AppState};
 use crate::models::mistral::MistralModelsResponse;
-use crate::models::ollama::{OllamaListResponse, OllamaModel};
+use crate::models::ollama::{
+    OllamaCopyRequest, OllamaCreateRequest, OllamaDeleteRequest, OllamaListResponse, OllamaModel,
+    OllamaPullRequest, OllamaStatusResponse,
+};
+
+const MODELS_LIST_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Last successful model listing, served until it is older than the TTL.
+pub struct ModelsCache {
+    ttl: Duration,
+    entry: RwLock<Option<(Instant, OllamaListResponse)>>,
+}
+
+impl ModelsCache {
+    pub fn new(ttl: Duration) -> Self {
+        ModelsCache {
+            ttl,
+            entry: RwLock::new(None),
+        }
+    }
+
+    /// Returns the cached listing if it hasn't expired.
+    pub fn fresh(&self) -> Option<OllamaListResponse> {
+        self.entry
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.ttl)
+            .map(|(_, models)| models.clone())
+    }
+
+    /// Returns the cached listing regardless of age.
+    pub fn last_good(&self) -> Option<OllamaListResponse> {
+        self.entry
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(_, models)| models.clone())
+    }
+
+    pub fn store(&self, models: OllamaListResponse) {
+        *self.entry.write().unwrap() = Some((Instant::now(), models));
+    }
+}
 
 pub async fn handle_list_models(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse> {
     info!("Listing available models");
 
-    let url = format!("{}/v1/models", state.mistral_url);
+    Ok(Json(list_models(&state).await))
+}
 
-    let response = state
-        .client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| AppError::request_error(url.clone(), e))?;
+/// Models are managed by the backend, so copying is acknowledged without doing anything.
+pub async fn handle_copy(AppJson(req): AppJson<OllamaCopyRequest>) -> StatusCode {
+    info!(
+        "Ignoring request to copy model {} to {}",
+        req.source, req.destination
+    );
+    StatusCode::OK
+}
 
-    if !response.status().is_success() {
-        let default_models = vec![
-            OllamaModel {
-                name: "mistral:latest".to_string(),
-                modified_at: chrono::Utc::now().to_rfc3339(),
-                size: crate::config::model_sizes::MODEL_7B_SIZE,
-                digest: "default".to_string(),
-            },
-            OllamaModel {
-                name: "mistral:7b".to_string(),
-                modified_at: chrono::Utc::now().to_rfc3339(),
-                size: crate::config::model_sizes::MODEL_7B_SIZE,
-                digest: "default".to_string(),
-            },
-        ];
+/// Models are managed by the backend, so creating one is acknowledged without doing anything.
+pub async fn handle_create(
+    AppJson(req): AppJson<OllamaCreateRequest>,
+) -> Json<OllamaStatusResponse> {
+    info!("Ignoring request to create model {}", req.model);
+    Json(OllamaStatusResponse {
+        status: "success".to_string(),
+    })
+}
+
+/// Reports every blob as already present so clients skip uploading model weights the
+/// backend would never use.
+pub async fn handle_blob_exists(Path(digest): Path<String>) -> StatusCode {
+    if !is_valid_digest(&digest) {
+        return StatusCode::BAD_REQUEST;
+    }
+    StatusCode::OK
+}
+
+/// Accepts and discards a blob upload, answering `201 Created` as Ollama does.
+pub async fn handle_blob_upload(Path(digest): Path<String>) -> StatusCode {
+    if !is_valid_digest(&digest) {
+        return StatusCode::BAD_REQUEST;
+    }
+    info!("Ignoring upload of blob {}", digest);
+    StatusCode::CREATED
+}
+
+/// Ollama addresses blobs as `sha256:<64 hex digits>`.
+fn is_valid_digest(digest: &str) -> bool {
+    digest
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Acknowledges deletion of a model the backend serves without removing it, and reports
+/// unknown models as not found like Ollama does.
+pub async fn handle_delete(
+    State(state): State<Arc<AppState>>,
+    AppJson(req): AppJson<OllamaDeleteRequest>,
+) -> Result<StatusCode> {
+    let models = list_models(&state).await;
+    let known = models
+        .models
+        .iter()
+        .any(|m| m.name == req.model || m.name == format!("{}:latest", req.model));
+    if !known {
+        return Err(AppError::ModelNotFound { model: req.model });
+    }
+
+    info!("Ignoring request to delete model {}", req.model);
+    Ok(StatusCode::OK)
+}
+
+/// Streams pull progress from the configured download coordinator, or reports success at once
+/// when there is none so clients waiting on progress don't hang.
+pub async fn handle_pull(
+    State(state): State<Arc<AppState>>,
+    AppJson(req): AppJson<serde_json::Value>,
+) -> Result<Response> {
+    let Some(url) = &state.pull_coordinator_url else {
+        let model = serde_json::from_value::<OllamaPullRequest>(req)
+            .map(|r| r.model)
+            .unwrap_or_default();
+        info!(
+            "No pull coordinator configured, reporting {} as pulled",
+            model
+        );
+        let status = serde_json::to_string(&OllamaStatusResponse {
+            status: "success".to_string(),
+        })?;
+        return Ok(ndjson_response(StatusCode::OK, Body::from(status + "\n")));
+    };
+
+    // Progress streams for as long as the download takes
+    let response = with_timeout(
+        with_request_id(state.client.post(url)),
+        state.stream_total_timeout,
+    )
+    .json(&req)
+    .send()
+    .await
+    .map_err(|e| AppError::request_error(url.clone(), e))?;
 
-        return Ok(Json(OllamaListResponse {
-            models: default_models,
-        }));
+    let status =
+        StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    Ok(ndjson_response(
+        status,
+        Body::from_stream(response.bytes_stream()),
+    ))
+}
+
+fn ndjson_response(status: StatusCode, body: Body) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        body,
+    )
+        .into_response()
+}
+
+async fn list_models(state: &AppState) -> OllamaListResponse {
+    if let Some(models) = state.models_cache.fresh() {
+        return models;
+    }
+
+    let fetched = fetch_models_with_retry(state).await;
+    if let Ok(Some(models)) = &fetched {
+        state.models_cache.store(models.clone());
+        return models.clone();
+    }
+
+    // Prefer the last real listing over the hardcoded defaults when the backend is unhealthy
+    if let Some(models) = state.models_cache.last_good() {
+        warn!("Model listing failed, serving cached models");
+        return models;
+    }
+
+    if let Err(e) = fetched {
+        warn!("Model listing failed, serving default models: {}", e);
+    }
+    default_models(state)
+}
+
+/// Retries a listing that timed out or couldn't connect, since clients often list models at
+/// startup while the backend is still coming up. A non-success answer is not retried.
+async fn fetch_models_with_retry(state: &AppState) -> Result<Option<OllamaListResponse>> {
+    let mut attempt = 0;
+    loop {
+        match fetch_models(state).await {
+            Err(e) if attempt < state.models_list_retries => {
+                attempt += 1;
+                debug!("Model listing attempt {} failed, retrying: {}", attempt, e);
+                tokio::time::sleep(MODELS_LIST_RETRY_DELAY).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Fetches the backend's models, returning `Ok(None)` if it answered with a non-success status.
+async fn fetch_models(state: &AppState) -> Result<Option<OllamaListResponse>> {
+    let url = state.backend_url("/models");
+
+    let response = with_timeout(
+        with_request_id(state.client.get(&url)),
+        state.models_list_timeout,
+    )
+    .send()
+    .await
+    .map_err(|e| AppError::request_error(url.clone(), e))?;
+
+    if !response.status().is_success() {
+        return Ok(None);
     }
 
     let mistral_models: MistralModelsResponse = response
@@ -64,11 +253,27 @@ pub async fn handle_list_models(State(state): State<Arc<AppState>>) -> Result<im
         })
         .collect();
 
-    Ok(Json(OllamaListResponse {
+    Ok(Some(OllamaListResponse {
         models: ollama_models,
     }))
 }
 
+/// Lists the models the proxy knows how to translate, for when the backend can't say.
+fn default_models(state: &AppState) -> OllamaListResponse {
+    let mut models: Vec<OllamaModel> = state
+        .model_map
+        .iter()
+        .map(|(name, backend_model)| OllamaModel {
+            name: name.clone(),
+            modified_at: chrono::Utc::now().to_rfc3339(),
+            size: estimate_model_size(backend_model),
+            digest: "default".to_string(),
+        })
+        .collect();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    OllamaListResponse { models }
+}
+
 fn estimate_model_size(model_id: &str) -> i64 {
     use crate::config::model_sizes::*;
 
@@ -79,3 +284,46 @@ fn estimate_model_size(model_id: &str) -> i64 {
         _ => DEFAULT_MODEL_SIZE,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(name: &str) -> OllamaListResponse {
+        OllamaListResponse {
+            models: vec![OllamaModel {
+                name: name.to_string(),
+                modified_at: "2024-01-01T00:00:00Z".to_string(),
+                size: 0,
+                digest: "test".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_is_valid_digest() {
+        assert!(is_valid_digest(&format!("sha256:{}", "a".repeat(64))));
+        assert!(!is_valid_digest(&format!("sha256:{}", "a".repeat(63))));
+        assert!(!is_valid_digest(&format!("sha256:{}", "g".repeat(64))));
+        assert!(!is_valid_digest(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn test_models_cache_hit() {
+        let cache = ModelsCache::new(Duration::from_secs(60));
+        assert!(cache.fresh().is_none());
+
+        cache.store(listing("cached:latest"));
+        assert_eq!(cache.fresh().unwrap().models[0].name, "cached:latest");
+    }
+
+    #[test]
+    fn test_models_cache_expiry() {
+        let cache = ModelsCache::new(Duration::from_millis(20));
+        cache.store(listing("cached:latest"));
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(cache.fresh().is_none());
+        assert_eq!(cache.last_good().unwrap().models[0].name, "cached:latest");
+    }
+}
@@ -0,0 +1,239 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::handlers::chat::{with_request_id, with_timeout, AppState};
+use crate::metrics;
+
+/// Tracks whether the proxy should receive traffic: the backend must answer and, when
+/// required, startup warmup must have finished.
+pub struct Readiness {
+    cache_window: Duration,
+    require_warmup: bool,
+    warmup_complete: AtomicBool,
+    last_check: Mutex<Option<(Instant, bool)>>,
+}
+
+impl Readiness {
+    pub fn new(cache_window: Duration, require_warmup: bool) -> Self {
+        Readiness {
+            cache_window,
+            require_warmup,
+            warmup_complete: AtomicBool::new(false),
+            last_check: Mutex::new(None),
+        }
+    }
+
+    pub fn mark_warmup_complete(&self) {
+        self.warmup_complete.store(true, Ordering::SeqCst);
+    }
+
+    fn warmup_satisfied(&self) -> bool {
+        !self.require_warmup || self.warmup_complete.load(Ordering::SeqCst)
+    }
+
+    fn cached_backend_check(&self) -> Option<bool> {
+        self.last_check
+            .lock()
+            .unwrap()
+            .filter(|(checked_at, _)| checked_at.elapsed() < self.cache_window)
+            .map(|(_, ready)| ready)
+    }
+
+    fn record_backend_check(&self, ready: bool) {
+        *self.last_check.lock().unwrap() = Some((Instant::now(), ready));
+    }
+}
+
+pub async fn handle_health() -> &'static str {
+    "Ollama is running"
+}
+
+pub async fn handle_readiness(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let readiness = &state.readiness;
+
+    let backend_ready = match readiness.cached_backend_check() {
+        Some(ready) => ready,
+        None => {
+            let ready = check_backend(&state).await;
+            readiness.record_backend_check(ready);
+            ready
+        }
+    };
+    let warmup_ready = readiness.warmup_satisfied();
+
+    let status = if backend_ready && warmup_ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "ready": status == StatusCode::OK,
+            "backend": backend_ready,
+            "warmup": warmup_ready,
+        })),
+    )
+}
+
+async fn check_backend(state: &AppState) -> bool {
+    let url = state.backend_url("/models");
+
+    let request = with_timeout(
+        with_request_id(state.client.get(&url)),
+        state.sync_request_timeout,
+    );
+    match request.send().await {
+        Ok(response) => response.status().is_success(),
+        Err(e) => {
+            tracing::debug!("Readiness check against {} failed: {}", url, e);
+            false
+        }
+    }
+}
+
+pub async fn handle_version(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let mut version = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "backend": state.backend_kind,
+    });
+    if let Some(commit) = option_env!("GIT_COMMIT") {
+        version["commit"] = serde_json::json!(commit);
+    }
+    Json(version)
+}
+
+/// Serves metrics in the Prometheus text format, or OpenMetrics when the scraper asks for it.
+pub async fn handle_metrics(headers: HeaderMap) -> impl IntoResponse {
+    let (content_type, metrics) = if accepts_openmetrics(&headers) {
+        (
+            metrics::OPENMETRICS_CONTENT_TYPE,
+            metrics::export_openmetrics(),
+        )
+    } else {
+        ("text/plain", metrics::export_metrics())
+    };
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, content_type)],
+        metrics,
+    )
+}
+
+/// Whether `Accept` lists `application/openmetrics-text` without refusing it via `q=0`.
+fn accepts_openmetrics(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            parts
+                .next()
+                .is_some_and(|media| media.eq_ignore_ascii_case("application/openmetrics-text"))
+                && !parts.any(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        .is_some_and(|q| q == 0.0)
+                })
+        })
+}
+
+/// Rejects requests that don't carry `Authorization: Bearer <token>` for the configured token.
+pub async fn require_bearer_token(
+    State(token): State<Arc<String>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes()));
+
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(serde_json::json!({ "error": "Missing or invalid bearer token" })),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Compares secrets without short-circuiting on the first mismatched byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_check_cached_within_window() {
+        let readiness = Readiness::new(Duration::from_secs(60), false);
+        assert_eq!(readiness.cached_backend_check(), None);
+
+        readiness.record_backend_check(true);
+        assert_eq!(readiness.cached_backend_check(), Some(true));
+    }
+
+    #[test]
+    fn test_backend_check_expires() {
+        let readiness = Readiness::new(Duration::ZERO, false);
+        readiness.record_backend_check(true);
+
+        assert_eq!(readiness.cached_backend_check(), None);
+    }
+
+    #[test]
+    fn test_warmup_only_required_when_configured() {
+        assert!(Readiness::new(Duration::ZERO, false).warmup_satisfied());
+
+        let readiness = Readiness::new(Duration::ZERO, true);
+        assert!(!readiness.warmup_satisfied());
+        readiness.mark_warmup_complete();
+        assert!(readiness.warmup_satisfied());
+    }
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, header::HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_accepts_openmetrics() {
+        assert!(accepts_openmetrics(&accept("application/openmetrics-text")));
+        assert!(accepts_openmetrics(&accept(
+            "text/plain;q=0.5, application/openmetrics-text; version=1.0.0"
+        )));
+        assert!(!accepts_openmetrics(&accept(
+            "application/openmetrics-text;q=0"
+        )));
+        assert!(!accepts_openmetrics(&accept("text/plain")));
+        assert!(!accepts_openmetrics(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
@@ -1,6 +1,29 @@
+pub mod abort;
+pub mod backend_selector;
+pub mod body_limit;
+pub mod circuit_breaker;
+pub mod client;
+pub mod concurrency;
 pub mod config;
+pub mod context;
 pub mod converters;
+pub mod deadline;
 pub mod error;
+pub mod extract;
+pub mod forward_headers;
 pub mod handlers;
+pub mod listener;
+pub mod logging;
 pub mod metrics;
 pub mod models;
+pub mod rate_limit;
+pub mod request_id;
+pub mod response_cache;
+pub mod response_headers;
+pub mod server;
+pub mod session;
+pub mod sse;
+pub mod telemetry;
+pub mod templates;
+pub mod tokens;
+pub mod warmup;
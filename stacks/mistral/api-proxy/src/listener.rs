@@ -0,0 +1,138 @@
+use axum::{extract::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::{conn::auto, graceful::GracefulShutdown};
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tokio::net::UnixListener;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+const UNIX_PREFIX: &str = "unix:";
+
+/// Where the proxy listens, parsed from `BIND_ADDRESS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindAddress {
+    Tcp(SocketAddr),
+    /// `unix:/path/to.sock`
+    Unix(PathBuf),
+}
+
+impl std::str::FromStr for BindAddress {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(UNIX_PREFIX) {
+            Some(path) => Ok(BindAddress::Unix(PathBuf::from(path))),
+            None => s.parse().map(BindAddress::Tcp),
+        }
+    }
+}
+
+/// Resolves when the process receives Ctrl-C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("Shutdown signal received");
+}
+
+/// Binds a Unix socket at `path`, replacing a stale socket file left by a previous run.
+pub fn bind_unix(path: &Path) -> io::Result<UnixListener> {
+    match std::fs::remove_file(path) {
+        Ok(()) => info!("Removed stale socket {}", path.display()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    UnixListener::bind(path)
+}
+
+/// Serves `app` on a Unix socket until `shutdown` resolves, then drains open connections and
+/// removes the socket file.
+pub async fn serve_unix<F>(listener: UnixListener, app: Router, shutdown: F) -> io::Result<()>
+where
+    F: Future<Output = ()>,
+{
+    let path = listener.local_addr()?.as_pathname().map(Path::to_path_buf);
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let socket = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let service = TowerToHyperService::new(
+            app.clone()
+                .map_request(|req: Request<Incoming>| req.map(axum::body::Body::new)),
+        );
+        let connection = auto::Builder::new(TokioExecutor::new())
+            .serve_connection_with_upgrades(TokioIo::new(socket), service)
+            .into_owned();
+        let connection = graceful.watch(connection);
+
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                debug!("Connection closed with error: {}", e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    if let Some(path) = path {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bind_address() {
+        assert_eq!(
+            "127.0.0.1:11434".parse::<BindAddress>().unwrap(),
+            BindAddress::Tcp("127.0.0.1:11434".parse().unwrap())
+        );
+        assert_eq!(
+            "unix:/run/proxy.sock".parse::<BindAddress>().unwrap(),
+            BindAddress::Unix(PathBuf::from("/run/proxy.sock"))
+        );
+        assert!("not-an-address".parse::<BindAddress>().is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use opentelemetry_sdk::trace::Tracer;
+use tracing::{subscriber::SetGlobalDefaultError, Level, Subscriber};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::LogFormat;
+
+/// Builds the log subscriber, also exporting spans through `tracer` when one is given.
+pub fn build_subscriber(
+    format: LogFormat,
+    level: Level,
+    tracer: Option<Tracer>,
+) -> Box<dyn Subscriber + Send + Sync> {
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+    match format {
+        LogFormat::Text => Box::new(builder.finish().with(otel_layer(tracer))),
+        LogFormat::Json => Box::new(builder.json().finish().with(otel_layer(tracer))),
+    }
+}
+
+fn otel_layer<S>(tracer: Option<Tracer>) -> Option<OpenTelemetryLayer<S, Tracer>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Stands in for text that may contain prompt content when `LOG_REDACT_PROMPTS` is on, keeping
+/// only its size.
+pub fn redact(text: &str) -> String {
+    format!("[{} bytes redacted]", text.len())
+}
+
+pub fn init(
+    format: LogFormat,
+    level: Level,
+    tracer: Option<Tracer>,
+) -> Result<(), SetGlobalDefaultError> {
+    tracing::subscriber::set_global_default(build_subscriber(format, level, tracer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_keeps_only_size() {
+        assert_eq!(redact("my secret"), "[9 bytes redacted]");
+    }
+
+    #[test]
+    fn test_build_subscriber_text() {
+        let subscriber = build_subscriber(LogFormat::Text, Level::DEBUG, None);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("text subscriber works");
+        });
+    }
+
+    #[test]
+    fn test_build_subscriber_json() {
+        let subscriber = build_subscriber(LogFormat::Json, Level::WARN, None);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!("json subscriber works");
+        });
+    }
+}
@@ -1,103 +1,80 @@
-use axum::{
-    http::{header, Method},
-    routing::{get, post},
-    Router,
-};
-use std::{net::SocketAddr, sync::Arc};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use std::sync::Arc;
 use tracing::info;
 
-mod config;
-mod converters;
-mod error;
-mod handlers;
-mod metrics;
-mod models;
-
-use config::Config;
-use handlers::chat::{handle_chat, handle_generate, AppState};
-use handlers::models::handle_list_models;
+use mistral_ollama_proxy::client;
+use mistral_ollama_proxy::config::Config;
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::listener::{self, BindAddress};
+use mistral_ollama_proxy::logging;
+use mistral_ollama_proxy::metrics;
+use mistral_ollama_proxy::server::build_router;
+use mistral_ollama_proxy::telemetry;
+use mistral_ollama_proxy::warmup;
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
-
     let config = Config::from_env();
+    // Logging isn't set up yet, and its own settings may be among the invalid ones
+    if let Err(e) = config.validate() {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    metrics::set_bucket_overrides(config.histogram_buckets.clone());
+
+    let tracer = config.otel_endpoint.as_deref().map(|endpoint| {
+        telemetry::init_tracer(endpoint, &config.otel_service_name)
+            .expect("Failed to initialize OpenTelemetry export")
+    });
+    logging::init(config.log_format, config.log_level, tracer)
+        .expect("Failed to initialize logging");
 
     info!("Starting Mistral-Ollama API proxy");
     info!("Mistral backend: {}", config.mistral_url);
+    if !config.backend_urls.is_empty() {
+        info!("Completion backends: {}", config.backend_urls.join(", "));
+    }
     info!("Listening on: {}", config.bind_address);
 
-    let client = reqwest::Client::builder()
-        .timeout(config.request_timeout())
-        .build()
-        .expect("Failed to build HTTP client");
+    let client = client::build_client(&config).expect("Failed to build HTTP client");
 
-    let state = Arc::new(AppState {
-        client,
-        mistral_url: config.mistral_url.clone(),
-        channel_buffer_size: config.channel_buffer_size,
-        max_line_length: config.max_line_length,
-    });
+    let state = Arc::new(AppState::new(client, &config));
+
+    let app = build_router(&config, state.clone());
 
-    let mut cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
-
-    // Configure allowed origins
-    for origin in &config.cors_allowed_origins {
-        cors = cors.allow_origin(
-            origin
-                .parse::<axum::http::HeaderValue>()
-                .unwrap_or_else(|_| panic!("Invalid CORS origin: {origin}")),
-        );
+    if let Some((model, interval)) = config.model_keepalive() {
+        tokio::spawn(warmup::keep_model_resident(state.clone(), model, interval));
     }
 
-    let app = Router::new()
-        .route("/api/generate", post(handle_generate))
-        .route("/api/chat", post(handle_chat))
-        .route("/api/tags", get(handle_list_models))
-        .route("/api/models", get(handle_list_models))
-        .route("/api/version", get(handle_version))
-        .route("/api/metrics", get(handle_metrics))
-        .route("/metrics", get(handle_metrics))
-        .route("/", get(handle_health))
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
-
-    let addr: SocketAddr = config.bind_address.parse().expect("Invalid bind address");
-
-    info!("Server starting on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .expect("Failed to bind to address");
-
-    axum::serve(listener, app)
-        .await
-        .expect("Server failed to start");
-}
+    let bind_address: BindAddress = config.bind_address.parse().expect("Invalid bind address");
 
-async fn handle_health() -> &'static str {
-    "Ollama is running"
-}
+    match bind_address {
+        BindAddress::Tcp(addr) => {
+            info!("Server starting on {}", addr);
 
-async fn handle_version() -> axum::Json<serde_json::Value> {
-    axum::Json(serde_json::json!({
-        "version": "0.1.0-mistral-proxy"
-    }))
-}
+            let tcp_listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .expect("Failed to bind to address");
+
+            // Warm up in the background so a slow model load doesn't delay serving
+            tokio::spawn(warmup::warmup_models(state, config.warmup_models.clone()));
 
-async fn handle_metrics() -> impl axum::response::IntoResponse {
-    use axum::http::StatusCode;
+            axum::serve(tcp_listener, app)
+                .with_graceful_shutdown(listener::shutdown_signal())
+                .await
+                .expect("Server failed to start");
+        }
+        BindAddress::Unix(path) => {
+            info!("Server starting on unix socket {}", path.display());
+
+            let unix_listener = listener::bind_unix(&path).expect("Failed to bind to socket");
+
+            tokio::spawn(warmup::warmup_models(state, config.warmup_models.clone()));
+
+            listener::serve_unix(unix_listener, app, listener::shutdown_signal())
+                .await
+                .expect("Server failed");
+        }
+    }
 
-    let metrics = metrics::export_metrics();
-    (
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "text/plain")],
-        metrics,
-    )
+    telemetry::shutdown();
 }
@@ -1,20 +1,101 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
 use lazy_static::lazy_static;
+use prometheus::proto::{MetricFamily, MetricType};
 use prometheus::{
-    register_counter_vec, register_gauge_vec, register_histogram_vec, register_int_gauge,
-    CounterVec, GaugeVec, HistogramVec, IntGauge, TextEncoder,
+    register_counter_vec, register_gauge_vec, register_histogram, register_histogram_vec,
+    register_int_counter, register_int_gauge, register_int_gauge_vec, CounterVec, GaugeVec,
+    Histogram, HistogramVec, IntCounter, IntGauge, IntGaugeVec, TextEncoder,
 };
 
+// LLM latencies span milliseconds (per-token decode) to minutes (long generations), so the
+// Prometheus default buckets (5ms-10s) are replaced with ranges suited to each phase.
+pub const HTTP_REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+pub const GENERATE_DURATION_BUCKETS: &[f64] = &[
+    0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0,
+];
+pub const PREFILL_DURATION_BUCKETS: &[f64] =
+    &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+pub const DECODE_DURATION_BUCKETS: &[f64] =
+    &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+pub const TOKENS_PER_SECOND_BUCKETS: &[f64] = &[
+    1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 50.0, 75.0, 100.0, 150.0, 250.0, 500.0,
+];
+pub const PERMIT_WAIT_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0,
+];
+pub const PAYLOAD_BYTES_BUCKETS: &[f64] = &[
+    256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0,
+];
+pub const REQUESTED_CONTEXT_LENGTH_BUCKETS: &[f64] = &[
+    512.0, 1024.0, 2048.0, 4096.0, 8192.0, 16384.0, 32768.0, 65536.0, 131072.0,
+];
+
+/// Parses comma-separated, strictly increasing bucket boundaries.
+pub fn parse_buckets(value: &str) -> Option<Vec<f64>> {
+    let buckets = value
+        .split(',')
+        .map(|b| b.trim().parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+
+    let increasing = buckets.windows(2).all(|w| w[0] < w[1]);
+    if buckets.is_empty() || !increasing {
+        return None;
+    }
+
+    Some(buckets)
+}
+
+/// Settings that replace a histogram's default buckets, read by `Config`.
+pub const BUCKET_SETTINGS: &[&str] = &[
+    "HTTP_REQUEST_DURATION_BUCKETS",
+    "GENERATE_DURATION_BUCKETS",
+    "PAYLOAD_BYTES_BUCKETS",
+    "PREFILL_DURATION_BUCKETS",
+    "DECODE_DURATION_BUCKETS",
+    "TOKENS_PER_SECOND_BUCKETS",
+];
+
+static BUCKET_OVERRIDES: OnceLock<HashMap<String, Vec<f64>>> = OnceLock::new();
+
+/// Installs the configured bucket overrides, keyed by setting name. Histograms are registered
+/// on first use, so this must run before any is observed; later calls are ignored.
+pub fn set_bucket_overrides(overrides: HashMap<String, Vec<f64>>) {
+    let _ = BUCKET_OVERRIDES.set(overrides);
+}
+
+fn buckets(setting: &str, default: &[f64]) -> Vec<f64> {
+    BUCKET_OVERRIDES
+        .get()
+        .and_then(|overrides| overrides.get(setting))
+        .cloned()
+        .unwrap_or_else(|| default.to_vec())
+}
+
 lazy_static! {
     pub static ref HTTP_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
         "mistral_http_requests_total",
         "Total number of HTTP requests",
-        &["endpoint", "status", "error_type"]
+        &["endpoint", "model", "status", "error_type"]
     )
     .unwrap();
     pub static ref HTTP_REQUEST_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
         "mistral_http_request_duration_seconds",
         "HTTP request latency in seconds",
-        &["endpoint"]
+        &["endpoint"],
+        buckets("HTTP_REQUEST_DURATION_BUCKETS", HTTP_REQUEST_DURATION_BUCKETS)
+    )
+    .unwrap();
+    // Shares the handler's buckets so the two can be compared to isolate proxy overhead
+    pub static ref BACKEND_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "mistral_backend_duration_seconds",
+        "Time spent waiting on the backend in seconds, until its response is read or starts streaming",
+        &["endpoint"],
+        buckets("HTTP_REQUEST_DURATION_BUCKETS", HTTP_REQUEST_DURATION_BUCKETS)
     )
     .unwrap();
     pub static ref GENERATE_TOKENS_TOTAL: CounterVec = register_counter_vec!(
@@ -26,7 +107,29 @@ lazy_static! {
     pub static ref GENERATE_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
         "mistral_generate_duration_seconds",
         "Time spent generating responses in seconds",
-        &["model"]
+        &["model"],
+        buckets("GENERATE_DURATION_BUCKETS", GENERATE_DURATION_BUCKETS)
+    )
+    .unwrap();
+    pub static ref TOKENS_PER_SECOND: HistogramVec = register_histogram_vec!(
+        "mistral_tokens_per_second",
+        "Completion tokens generated per second of generation time",
+        &["model"],
+        buckets("TOKENS_PER_SECOND_BUCKETS", TOKENS_PER_SECOND_BUCKETS)
+    )
+    .unwrap();
+    pub static ref REQUEST_BYTES: HistogramVec = register_histogram_vec!(
+        "mistral_request_bytes",
+        "Size of completion request bodies sent to the backend in bytes",
+        &["endpoint"],
+        buckets("PAYLOAD_BYTES_BUCKETS", PAYLOAD_BYTES_BUCKETS)
+    )
+    .unwrap();
+    pub static ref RESPONSE_BYTES: HistogramVec = register_histogram_vec!(
+        "mistral_response_bytes",
+        "Size of completion response bodies received from the backend in bytes",
+        &["endpoint"],
+        buckets("PAYLOAD_BYTES_BUCKETS", PAYLOAD_BYTES_BUCKETS)
     )
     .unwrap();
     pub static ref ACTIVE_REQUESTS: IntGauge = register_int_gauge!(
@@ -34,6 +137,11 @@ lazy_static! {
         "Number of active requests being processed"
     )
     .unwrap();
+    pub static ref ACTIVE_STREAMS: IntGauge = register_int_gauge!(
+        "mistral_active_streams",
+        "Number of streaming responses currently being forwarded"
+    )
+    .unwrap();
     pub static ref MODEL_LOAD_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
         "mistral_model_load_duration_seconds",
         "Time taken to load models in seconds",
@@ -46,6 +154,83 @@ lazy_static! {
         &["endpoint"]
     )
     .unwrap();
+    pub static ref STREAM_PARSE_ERRORS_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_stream_parse_errors_total",
+        "Total number of streaming chunks from the backend that failed to parse",
+        &["endpoint"]
+    )
+    .unwrap();
+    pub static ref STREAMS_TRUNCATED_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_streams_truncated_total",
+        "Total number of streams ended early for reaching MAX_STREAM_CHUNKS",
+        &["endpoint"]
+    )
+    .unwrap();
+    pub static ref STREAM_CHANNEL_FULL_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_stream_channel_full_total",
+        "Total number of streamed chunks that waited for space in the CHANNEL_BUFFER_SIZE buffer",
+        &["endpoint"]
+    )
+    .unwrap();
+    pub static ref RATE_LIMITED_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_rate_limited_total",
+        "Total number of requests rejected by per-model rate limits",
+        &["model"]
+    )
+    .unwrap();
+    pub static ref MODEL_DENIED_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_model_denied_total",
+        "Total number of requests rejected for a model outside ALLOWED_MODELS",
+        &["model"]
+    )
+    .unwrap();
+    pub static ref CACHE_HITS_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_cache_hits_total",
+        "Total number of completions served from the response cache",
+        &["endpoint"]
+    )
+    .unwrap();
+    pub static ref CACHE_MISSES_TOTAL: CounterVec = register_counter_vec!(
+        "mistral_cache_misses_total",
+        "Total number of cacheable completions not found in the response cache",
+        &["endpoint"]
+    )
+    .unwrap();
+    pub static ref AVAILABLE_PERMITS: IntGauge = register_int_gauge!(
+        "mistral_available_permits",
+        "Completion permits still available under MAX_CONCURRENT_REQUESTS"
+    )
+    .unwrap();
+    pub static ref PERMIT_WAIT_SECONDS: Histogram = register_histogram!(
+        "mistral_permit_wait_seconds",
+        "Time requests spent waiting for a completion permit",
+        PERMIT_WAIT_BUCKETS.to_vec()
+    )
+    .unwrap();
+    pub static ref HISTORY_TRUNCATED_TOTAL: IntCounter = register_int_counter!(
+        "mistral_history_truncated_total",
+        "Requests whose message history was truncated to MAX_HISTORY_MESSAGES"
+    )
+    .unwrap();
+    pub static ref BACKEND_HEALTHY: IntGaugeVec = register_int_gauge_vec!(
+        "mistral_backend_healthy",
+        "Whether the backend's recent failures have decayed (1 = healthy, 0 = de-weighted)",
+        &["backend"]
+    )
+    .unwrap();
+    pub static ref CIRCUIT_STATE: IntGaugeVec = register_int_gauge_vec!(
+        "mistral_circuit_state",
+        "Backend circuit breaker state (0 = closed, 1 = open, 2 = half-open)",
+        &["backend"]
+    )
+    .unwrap();
+    // Mistral's API has no per-request context window, so `num_ctx` is only observed here
+    pub static ref REQUESTED_CONTEXT_LENGTH: Histogram = register_histogram!(
+        "mistral_requested_context_length",
+        "Context window sizes requested by clients via options.num_ctx",
+        REQUESTED_CONTEXT_LENGTH_BUCKETS.to_vec()
+    )
+    .unwrap();
 
     // Metal-specific performance metrics
     pub static ref METAL_MEMORY_USAGE_BYTES: GaugeVec = register_gauge_vec!(
@@ -68,17 +253,241 @@ lazy_static! {
     pub static ref PREFILL_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
         "mistral_prefill_duration_seconds",
         "Time spent in prefill phase",
-        &["model", "batch_size"]
+        &["model", "batch_size"],
+        buckets("PREFILL_DURATION_BUCKETS", PREFILL_DURATION_BUCKETS)
     )
     .unwrap();
     pub static ref DECODE_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
         "mistral_decode_duration_seconds",
         "Time spent in decode phase per token",
-        &["model", "batch_size"]
+        &["model", "batch_size"],
+        buckets("DECODE_DURATION_BUCKETS", DECODE_DURATION_BUCKETS)
     )
     .unwrap();
 }
 
+/// Records a completed generation's throughput. Generations with no tokens or no measurable
+/// duration are skipped rather than recorded as zero or infinite rates.
+pub fn observe_tokens_per_second(model_label: &str, completion_tokens: i32, elapsed: Duration) {
+    let seconds = elapsed.as_secs_f64();
+    if completion_tokens <= 0 || seconds <= 0.0 {
+        return;
+    }
+    TOKENS_PER_SECOND
+        .with_label_values(&[model_label])
+        .observe(f64::from(completion_tokens) / seconds);
+}
+
+/// Maps model names to the values recorded in `model` metric labels.
+///
+/// Clients can send arbitrary model strings, so with an allowlist configured only listed
+/// names are recorded as-is and every other model shares the `other` label.
+#[derive(Debug, Clone, Default)]
+pub struct ModelLabels {
+    allowlist: Option<Arc<HashSet<String>>>,
+}
+
+impl ModelLabels {
+    /// Records only `models` under their own names.
+    pub fn allowlist<I, S>(models: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ModelLabels {
+            allowlist: Some(Arc::new(models.into_iter().map(Into::into).collect())),
+        }
+    }
+
+    /// Records every model under its own name.
+    pub fn unrestricted() -> Self {
+        ModelLabels::default()
+    }
+
+    pub fn label<'a>(&self, model: &'a str) -> &'a str {
+        match &self.allowlist {
+            Some(allowlist) if !allowlist.contains(model) => "other",
+            _ => model,
+        }
+    }
+}
+
+/// Totals the bytes of a streamed body, observing the sum into a histogram when dropped.
+pub struct StreamedBytes {
+    histogram: Histogram,
+    bytes: usize,
+}
+
+impl StreamedBytes {
+    pub fn new(histogram: Histogram) -> Self {
+        StreamedBytes {
+            histogram,
+            bytes: 0,
+        }
+    }
+
+    pub fn add(&mut self, bytes: usize) {
+        self.bytes += bytes;
+    }
+}
+
+impl Drop for StreamedBytes {
+    fn drop(&mut self) {
+        self.histogram.observe(self.bytes as f64);
+    }
+}
+
+/// Coarse `batch_size` label for the number of streams the backend is generating at once, so
+/// per-token latencies can be compared across load levels without a label per count.
+pub fn batch_size_label(concurrent_streams: i64) -> &'static str {
+    match concurrent_streams {
+        ..=1 => "1",
+        2..=4 => "2-4",
+        5..=8 => "5-8",
+        _ => "9+",
+    }
+}
+
+/// Holds `ACTIVE_STREAMS` incremented for as long as it is alive.
+pub struct ActiveStreamGuard;
+
+impl ActiveStreamGuard {
+    pub fn new() -> Self {
+        ACTIVE_STREAMS.inc();
+        ActiveStreamGuard
+    }
+}
+
+impl Default for ActiveStreamGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ActiveStreamGuard {
+    fn drop(&mut self) {
+        ACTIVE_STREAMS.dec();
+    }
+}
+
+/// Content type of [`export_openmetrics`] output.
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encodes all registered metrics in the OpenMetrics text format.
+///
+/// The Prometheus crate only ships the classic text encoder, so this writes the few parts that
+/// differ: counter families are named without their `_total` suffix, which every counter sample
+/// carries, and the exposition ends with `# EOF`.
+pub fn export_openmetrics() -> String {
+    let mut out = String::new();
+    for family in prometheus::gather() {
+        write_openmetrics_family(&mut out, &family);
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+fn write_openmetrics_family(out: &mut String, family: &MetricFamily) {
+    use std::fmt::Write;
+
+    let name = family.get_name();
+    let (family_name, kind) = match family.get_field_type() {
+        MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+        MetricType::GAUGE => (name, "gauge"),
+        MetricType::HISTOGRAM => (name, "histogram"),
+        MetricType::SUMMARY => (name, "summary"),
+        MetricType::UNTYPED => (name, "unknown"),
+    };
+    let _ = writeln!(out, "# TYPE {family_name} {kind}");
+    let _ = writeln!(
+        out,
+        "# HELP {family_name} {}",
+        escape_openmetrics(family.get_help())
+    );
+
+    for metric in family.get_metric() {
+        let labels: Vec<(&str, String)> = metric
+            .get_label()
+            .iter()
+            .map(|pair| (pair.get_name(), escape_openmetrics(pair.get_value())))
+            .collect();
+        let mut sample = |suffix: &str, extra: Option<(&str, String)>, value: f64| {
+            let mut pairs: Vec<String> = labels
+                .iter()
+                .map(|(name, value)| format!("{name}=\"{value}\""))
+                .collect();
+            if let Some((name, value)) = extra {
+                pairs.push(format!("{name}=\"{value}\""));
+            }
+            let labels = if pairs.is_empty() {
+                String::new()
+            } else {
+                format!("{{{}}}", pairs.join(","))
+            };
+            let _ = writeln!(
+                out,
+                "{family_name}{suffix}{labels} {}",
+                format_openmetrics_value(value)
+            );
+        };
+
+        match family.get_field_type() {
+            MetricType::COUNTER => sample("_total", None, metric.get_counter().get_value()),
+            MetricType::GAUGE => sample("", None, metric.get_gauge().get_value()),
+            MetricType::UNTYPED => sample("", None, metric.get_untyped().get_value()),
+            MetricType::HISTOGRAM => {
+                let histogram = metric.get_histogram();
+                for bucket in histogram.get_bucket() {
+                    sample(
+                        "_bucket",
+                        Some(("le", format_openmetrics_value(bucket.get_upper_bound()))),
+                        bucket.get_cumulative_count() as f64,
+                    );
+                }
+                let count = histogram.get_sample_count() as f64;
+                sample("_bucket", Some(("le", "+Inf".to_string())), count);
+                sample("_count", None, count);
+                sample("_sum", None, histogram.get_sample_sum());
+            }
+            MetricType::SUMMARY => {
+                let summary = metric.get_summary();
+                for quantile in summary.get_quantile() {
+                    sample(
+                        "",
+                        Some((
+                            "quantile",
+                            format_openmetrics_value(quantile.get_quantile()),
+                        )),
+                        quantile.get_value(),
+                    );
+                }
+                sample("_count", None, summary.get_sample_count() as f64);
+                sample("_sum", None, summary.get_sample_sum());
+            }
+        }
+    }
+}
+
+fn escape_openmetrics(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_openmetrics_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
 pub fn export_metrics() -> String {
     let encoder = TextEncoder::new();
     let metric_families = prometheus::gather();
@@ -95,3 +504,49 @@ pub fn export_metrics() -> String {
             )
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_labels() {
+        let labels = ModelLabels::allowlist(["mistral:latest"]);
+        assert_eq!(labels.label("mistral:latest"), "mistral:latest");
+        assert_eq!(labels.label("made-up-model"), "other");
+
+        assert_eq!(
+            ModelLabels::unrestricted().label("made-up-model"),
+            "made-up-model"
+        );
+    }
+
+    #[test]
+    fn test_openmetrics_formatting() {
+        assert_eq!(escape_openmetrics("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+        assert_eq!(format_openmetrics_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_openmetrics_value(0.25), "0.25");
+        assert_eq!(format_openmetrics_value(3.0), "3");
+    }
+
+    #[test]
+    fn test_batch_size_label() {
+        assert_eq!(batch_size_label(1), "1");
+        assert_eq!(batch_size_label(3), "2-4");
+        assert_eq!(batch_size_label(8), "5-8");
+        assert_eq!(batch_size_label(20), "9+");
+    }
+
+    #[test]
+    fn test_parse_buckets() {
+        assert_eq!(parse_buckets("0.1, 1,10"), Some(vec![0.1, 1.0, 10.0]));
+    }
+
+    #[test]
+    fn test_parse_buckets_rejects_invalid() {
+        assert_eq!(parse_buckets(""), None);
+        assert_eq!(parse_buckets("1,abc"), None);
+        assert_eq!(parse_buckets("1,1"), None);
+        assert_eq!(parse_buckets("10,1"), None);
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct MistralChatRequest {
     pub model: String,
     pub messages: Vec<MistralMessage>,
@@ -9,15 +9,281 @@ pub struct MistralChatRequest {
     pub top_p: Option<f32>,
     pub max_tokens: Option<i32>,
     pub random_seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<serde_json::Value>>,
+    /// `auto`, `none`, `any`, `required`, or a specific function the model must call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+    /// Asks Mistral to prepend its safety system prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safe_prompt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    /// Alternatives reported for each generated token; requires `logprobs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<MistralStreamOptions>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MistralStreamOptions {
+    pub include_usage: bool,
+}
+
+/// Fill-in-the-middle request for code completion models such as Codestral.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MistralFimRequest {
+    pub model: String,
+    pub prompt: String,
+    pub suffix: Option<String>,
+    pub stream: Option<bool>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<i32>,
+    pub random_seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+}
+
+/// Plain-text completion request, sent verbatim to OpenAI-compatible backends' `/completions`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MistralTextRequest {
+    pub model: String,
+    pub prompt: String,
+    pub stream: Option<bool>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<i32>,
+    pub random_seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<MistralStreamOptions>,
+}
+
+/// A request body for one of Mistral's completion endpoints.
+pub trait MistralCompletionRequest: Serialize + Send {
+    /// Path of the backend endpoint this request is sent to, below the API prefix.
+    fn endpoint(&self) -> &'static str;
+
+    fn model(&self) -> &str;
+
+    /// Asks the backend to append a usage chunk to streamed responses, where supported.
+    fn request_stream_usage(&mut self);
+
+    /// The text the model is prompted with, used to estimate prompt tokens.
+    fn prompt_text(&self) -> String;
+
+    /// Whether sending the request again should produce the same completion.
+    fn is_deterministic(&self) -> bool;
+}
+
+fn is_deterministic(temperature: Option<f32>, random_seed: Option<i32>) -> bool {
+    temperature == Some(0.0) || random_seed.is_some()
+}
+
+impl MistralCompletionRequest for MistralChatRequest {
+    fn endpoint(&self) -> &'static str {
+        "/chat/completions"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn request_stream_usage(&mut self) {
+        self.stream_options = Some(MistralStreamOptions {
+            include_usage: true,
+        });
+    }
+
+    fn prompt_text(&self) -> String {
+        self.messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn is_deterministic(&self) -> bool {
+        is_deterministic(self.temperature, self.random_seed)
+    }
+}
+
+impl MistralCompletionRequest for MistralFimRequest {
+    fn endpoint(&self) -> &'static str {
+        "/fim/completions"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    // The FIM endpoint always reports usage on its final chunk.
+    fn request_stream_usage(&mut self) {}
+
+    fn prompt_text(&self) -> String {
+        match &self.suffix {
+            Some(suffix) => format!("{}\n{}", self.prompt, suffix),
+            None => self.prompt.clone(),
+        }
+    }
+
+    fn is_deterministic(&self) -> bool {
+        is_deterministic(self.temperature, self.random_seed)
+    }
+}
+
+impl MistralCompletionRequest for MistralTextRequest {
+    fn endpoint(&self) -> &'static str {
+        "/completions"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn request_stream_usage(&mut self) {
+        self.stream_options = Some(MistralStreamOptions {
+            include_usage: true,
+        });
+    }
+
+    fn prompt_text(&self) -> String {
+        self.prompt.clone()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        is_deterministic(self.temperature, self.random_seed)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(into = "OutgoingMistralMessage")]
 pub struct MistralMessage {
     pub role: String,
+    /// Empty when the backend sent `null`, as it does for an assistant turn that only calls
+    /// tools.
+    #[serde(default, deserialize_with = "deserialize_content")]
     pub content: String,
+    /// Base64-encoded images sent alongside `content` to vision models.
+    #[serde(skip)]
+    pub images: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<serde_json::Value>>,
+    /// On a trailing assistant message, asks the model to continue it rather than reply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<bool>,
+    /// Reasoning a model produced separately from its reply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A message as sent to the backend, whose content becomes a list of parts when it carries
+/// images.
+#[derive(Serialize)]
+struct OutgoingMistralMessage {
+    role: String,
+    content: Option<OutgoingContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_content: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum OutgoingContent {
+    Text(String),
+    Parts(Vec<serde_json::Value>),
+}
+
+impl From<MistralMessage> for OutgoingMistralMessage {
+    fn from(msg: MistralMessage) -> Self {
+        // A tool-calling turn without text goes back as `null`, as backends sent it
+        let content = if msg.content.is_empty() && msg.tool_calls.is_some() {
+            None
+        } else if msg.images.is_empty() {
+            Some(OutgoingContent::Text(msg.content))
+        } else {
+            let text = serde_json::json!({ "type": "text", "text": msg.content });
+            let images = msg.images.iter().map(|image| {
+                serde_json::json!({ "type": "image_url", "image_url": image_data_url(image) })
+            });
+            Some(OutgoingContent::Parts(
+                std::iter::once(text).chain(images).collect(),
+            ))
+        };
+        OutgoingMistralMessage {
+            role: msg.role,
+            content,
+            tool_calls: msg.tool_calls,
+            prefix: msg.prefix,
+            reasoning_content: msg.reasoning_content,
+        }
+    }
+}
+
+/// Ollama sends bare base64 image data; Mistral expects a data URL naming its type, which is
+/// sniffed from the encoded magic bytes.
+fn image_data_url(image: &str) -> String {
+    if image.starts_with("data:") {
+        return image.to_string();
+    }
+    let media_type = if image.starts_with("iVBORw0KGgo") {
+        "image/png"
+    } else if image.starts_with("R0lGOD") {
+        "image/gif"
+    } else if image.starts_with("UklGR") {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    };
+    format!("data:{media_type};base64,{image}")
+}
+
+/// Message content as Mistral sends it: plain text, or a list of typed parts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MistralContent {
+    Text(String),
+    Parts(Vec<MistralContentPart>),
+}
+
+#[derive(Deserialize)]
+struct MistralContentPart {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// Reads `content` from either form, concatenating the text parts of a list since Ollama
+/// messages only carry text. Non-text parts such as images are dropped, and `null` is read as
+/// empty.
+fn deserialize_content<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Option::<MistralContent>::deserialize(deserializer)? {
+        None => String::new(),
+        Some(MistralContent::Text(text)) => text,
+        Some(MistralContent::Parts(parts)) => parts
+            .into_iter()
+            .filter(|part| part.kind == "text")
+            .filter_map(|part| part.text)
+            .collect(),
+    })
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MistralChatResponse {
     pub id: String,
     pub object: String,
@@ -27,15 +293,63 @@ pub struct MistralChatResponse {
     pub usage: Option<MistralUsage>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "RawMistralChoice")]
 pub struct MistralChoice {
     pub index: i32,
     pub message: Option<MistralMessage>,
     pub delta: Option<MistralMessage>,
     pub finish_reason: Option<String>,
+    /// Token log probabilities, passed through to clients as received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A choice as received, which for `/completions` carries plain `text` instead of a message.
+#[derive(Deserialize)]
+struct RawMistralChoice {
+    index: i32,
+    message: Option<MistralMessage>,
+    delta: Option<MistralMessage>,
+    finish_reason: Option<String>,
+    text: Option<String>,
+    logprobs: Option<serde_json::Value>,
+}
+
+impl From<RawMistralChoice> for MistralChoice {
+    fn from(raw: RawMistralChoice) -> Self {
+        // Present text as both message and delta so sync and streaming paths read it alike
+        let (message, delta) = match (raw.message, raw.delta, raw.text) {
+            (None, None, Some(text)) => {
+                let message = MistralMessage {
+                    role: "assistant".to_string(),
+                    content: text,
+                    ..Default::default()
+                };
+                (Some(message.clone()), Some(message))
+            }
+            (message, delta, _) => (message, delta),
+        };
+        MistralChoice {
+            index: raw.index,
+            message,
+            delta,
+            finish_reason: raw.finish_reason,
+            // Backends send an explicit null when logprobs weren't requested
+            logprobs: raw.logprobs.filter(|logprobs| !logprobs.is_null()),
+        }
+    }
+}
+
+impl MistralChoice {
+    /// The choice's message, falling back to `delta` for backends that send stream-style
+    /// choices in a non-streaming response.
+    pub fn content_message(&self) -> Option<&MistralMessage> {
+        self.message.as_ref().or(self.delta.as_ref())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MistralUsage {
     pub prompt_tokens: i32,
     pub completion_tokens: i32,
@@ -48,7 +362,9 @@ pub struct MistralStreamChunk {
     pub object: String,
     pub created: i64,
     pub model: String,
+    #[serde(default)]
     pub choices: Vec<MistralChoice>,
+    pub usage: Option<MistralUsage>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -64,3 +380,43 @@ pub struct MistralModel {
     pub created: i64,
     pub owned_by: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_null_content_read_as_empty() {
+        let message: MistralMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{"id": "call_1", "function": {"name": "f", "arguments": "{}"}}]
+        }))
+        .unwrap();
+        assert_eq!(message.content, "");
+        assert!(message.tool_calls.is_some());
+
+        let message: MistralMessage = serde_json::from_value(json!({"role": "assistant"})).unwrap();
+        assert_eq!(message.content, "");
+    }
+
+    #[test]
+    fn test_tool_call_turn_without_text_serialized_with_null_content() {
+        let message = MistralMessage {
+            role: "assistant".to_string(),
+            tool_calls: Some(vec![json!({"id": "call_1"})]),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(message).unwrap()["content"],
+            json!(null)
+        );
+
+        let message = MistralMessage {
+            role: "assistant".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(serde_json::to_value(message).unwrap()["content"], "");
+    }
+}
@@ -2,25 +2,81 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OllamaGenerateRequest {
+    #[serde(default)]
     pub model: String,
     pub prompt: String,
     pub stream: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_options")]
     pub options: Option<serde_json::Value>,
     pub context: Option<Vec<i32>>,
+    pub suffix: Option<String>,
+    /// Include the prompt at the start of the response text.
+    pub echo: Option<bool>,
+    /// Send the prompt verbatim, without wrapping it in a chat message.
+    pub raw: Option<bool>,
+    /// Base64-encoded images for vision models.
+    pub images: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OllamaChatRequest {
+    #[serde(default)]
     pub model: String,
     pub messages: Vec<OllamaMessage>,
     pub stream: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_options")]
     pub options: Option<serde_json::Value>,
+    pub tools: Option<Vec<serde_json::Value>>,
+    pub tool_choice: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OllamaCopyRequest {
+    pub source: String,
+    pub destination: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OllamaDeleteRequest {
+    #[serde(alias = "name")]
+    pub model: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OllamaCreateRequest {
+    #[serde(alias = "name")]
+    pub model: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OllamaPullRequest {
+    #[serde(alias = "name")]
+    pub model: String,
+}
+
+/// Body of `POST /api/abort`, a proxy extension naming the request to cancel.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AbortRequest {
+    pub request_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OllamaStatusResponse {
+    pub status: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct OllamaMessage {
     pub role: String,
+    /// Clients replaying OpenAI-style histories send `null` for tool-calling turns, and
+    /// clients ported from Anthropic's API send a list of text blocks.
+    #[serde(default, deserialize_with = "deserialize_message_content")]
     pub content: String,
+    /// The model's reasoning, shown apart from the reply by clients that support thinking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<serde_json::Value>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -28,7 +84,14 @@ pub struct OllamaGenerateResponse {
     pub model: String,
     pub created_at: String,
     pub response: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
     pub done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<String>,
+    /// Extension: token log probabilities, when requested through `options.logprobs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<serde_json::Value>,
     pub context: Option<Vec<i32>>,
     pub total_duration: Option<i64>,
     pub load_duration: Option<i64>,
@@ -43,7 +106,15 @@ pub struct OllamaChatResponse {
     pub model: String,
     pub created_at: String,
     pub message: OllamaMessage,
+    /// Extension: additional candidates when more than one completion (`n`) was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<OllamaMessage>>,
     pub done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<String>,
+    /// Extension: token log probabilities, when requested through `options.logprobs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<serde_json::Value>,
     pub total_duration: Option<i64>,
     pub load_duration: Option<i64>,
     pub prompt_eval_count: Option<i32>,
@@ -52,15 +123,98 @@ pub struct OllamaChatResponse {
     pub eval_duration: Option<i64>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OllamaListResponse {
     pub models: Vec<OllamaModel>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OllamaModel {
     pub name: String,
     pub modified_at: String,
     pub size: i64,
     pub digest: String,
 }
+
+/// A block of Anthropic-style message content.
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+/// Reads message `content` given as a string, `null`, or a list of `{"type": "text"}` blocks
+/// whose text is concatenated. Other block types are rejected rather than dropped, since the
+/// model would otherwise answer without part of what the client sent.
+fn deserialize_message_content<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct ContentVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for ContentVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a string or a list of text blocks")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_string<E: serde::de::Error>(self, value: String) -> Result<String, E> {
+            Ok(value)
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<String, E> {
+            Ok(String::new())
+        }
+
+        fn visit_seq<A>(self, mut blocks: A) -> Result<String, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut content = String::new();
+            while let Some(block) = blocks.next_element::<ContentBlock>()? {
+                if block.kind != "text" {
+                    return Err(serde::de::Error::custom(format!(
+                        "unsupported content block type '{}', only text blocks are accepted",
+                        block.kind
+                    )));
+                }
+                content.push_str(&block.text);
+            }
+            Ok(content)
+        }
+    }
+
+    deserializer.deserialize_any(ContentVisitor)
+}
+
+/// Reads `options`, which must be an object when present, so a misplaced string or list is
+/// reported instead of every setting in it being silently ignored.
+fn deserialize_options<'de, D>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{Error, Unexpected};
+    use serde_json::Value;
+
+    let unexpected = match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => return Ok(None),
+        Some(options @ Value::Object(_)) => return Ok(Some(options)),
+        Some(Value::String(s)) => {
+            return Err(D::Error::invalid_type(
+                Unexpected::Str(&s),
+                &"a JSON object",
+            ))
+        }
+        Some(Value::Array(_)) => Unexpected::Seq,
+        Some(Value::Bool(b)) => Unexpected::Bool(b),
+        Some(Value::Number(_)) => Unexpected::Other("number"),
+    };
+    Err(D::Error::invalid_type(unexpected, &"a JSON object"))
+}
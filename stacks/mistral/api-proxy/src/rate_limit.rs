@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::metrics::{ModelLabels, RATE_LIMITED_TOTAL};
+
+/// Classic token bucket: holds up to `capacity` tokens and refills at `rate` per second.
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        // Allow a burst of one second's worth of requests, and at least one request
+        let capacity = rate.max(1.0);
+        TokenBucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Per-model request rate limits, keyed by backend (translated) model name.
+///
+/// Models without a configured limit are never throttled.
+pub struct ModelRateLimiter {
+    buckets: HashMap<String, Mutex<TokenBucket>>,
+    model_labels: ModelLabels,
+}
+
+impl ModelRateLimiter {
+    /// `limits` maps model names to allowed requests per second; non-positive rates are ignored.
+    pub fn new(limits: &HashMap<String, f64>, model_labels: ModelLabels) -> Self {
+        let buckets = limits
+            .iter()
+            .filter(|(_, &rate)| rate > 0.0)
+            .map(|(model, &rate)| (model.clone(), Mutex::new(TokenBucket::new(rate))))
+            .collect();
+        ModelRateLimiter {
+            buckets,
+            model_labels,
+        }
+    }
+
+    /// Admits a request for `model`, or returns how long until the next one would be allowed.
+    pub fn check(&self, model: &str) -> Result<(), Duration> {
+        let Some(bucket) = self.buckets.get(model) else {
+            return Ok(());
+        };
+
+        bucket.lock().unwrap().try_take().inspect_err(|_| {
+            RATE_LIMITED_TOTAL
+                .with_label_values(&[self.model_labels.label(model)])
+                .inc();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(model: &str, rate: f64) -> ModelRateLimiter {
+        ModelRateLimiter::new(
+            &HashMap::from([(model.to_string(), rate)]),
+            ModelLabels::unrestricted(),
+        )
+    }
+
+    #[test]
+    fn test_limited_model_is_throttled_after_burst() {
+        let limiter = limiter("limited-model-unit", 2.0);
+
+        assert!(limiter.check("limited-model-unit").is_ok());
+        assert!(limiter.check("limited-model-unit").is_ok());
+        let retry_after = limiter.check("limited-model-unit").unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = limiter("refill-model-unit", 20.0);
+        for _ in 0..20 {
+            limiter.check("refill-model-unit").unwrap();
+        }
+        assert!(limiter.check("refill-model-unit").is_err());
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(limiter.check("refill-model-unit").is_ok());
+    }
+
+    #[test]
+    fn test_unlisted_model_is_unlimited() {
+        let limiter = limiter("limited-model-unit", 1.0);
+        for _ in 0..100 {
+            assert!(limiter.check("other-model").is_ok());
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Caches backend replies to deterministic requests, enabled by `RESPONSE_CACHE_TTL_SECS`.
+//!
+//! A request qualifies only when it isn't streamed and asks for `temperature: 0` or a fixed
+//! seed, so sending it again should produce the same completion. Replies are cached as the
+//! backend sent them and converted per request, so `context`, sessions and `echo` still apply.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::mistral::{MistralChatResponse, MistralCompletionRequest};
+
+pub struct ResponseCache {
+    /// `None` disables the cache.
+    ttl: Option<Duration>,
+    capacity: usize,
+    inner: Mutex<ResponseCacheInner>,
+}
+
+#[derive(Default)]
+struct ResponseCacheInner {
+    entries: HashMap<String, (Instant, MistralChatResponse)>,
+    // Least recently used first, used to evict when over capacity
+    order: VecDeque<String>,
+}
+
+impl ResponseCacheInner {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|entry| entry == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.to_string());
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+        self.order.retain(|entry| entry != key);
+    }
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Option<Duration>, capacity: usize) -> Self {
+        ResponseCache {
+            ttl: ttl.filter(|_| capacity > 0),
+            capacity,
+            inner: Mutex::new(ResponseCacheInner::default()),
+        }
+    }
+
+    /// The key `req` is cached under, or `None` if it shouldn't be cached.
+    ///
+    /// The key is the endpoint and the serialized request itself rather than a digest of
+    /// them, so two different requests can never share a reply.
+    pub fn key<R: MistralCompletionRequest>(&self, stream: bool, req: &R) -> Option<String> {
+        if self.ttl.is_none() || stream || !req.is_deterministic() {
+            return None;
+        }
+        // The serialized request covers every parameter that affects the completion
+        let body = serde_json::to_string(req).ok()?;
+        Some(format!("{} {body}", req.endpoint()))
+    }
+
+    /// Returns the reply cached under `key` if it hasn't expired.
+    pub fn get(&self, key: &str) -> Option<MistralChatResponse> {
+        let ttl = self.ttl?;
+        let mut inner = self.inner.lock().unwrap();
+        let (stored_at, response) = inner.entries.get(key)?;
+        if stored_at.elapsed() >= ttl {
+            inner.remove(key);
+            return None;
+        }
+        let response = response.clone();
+        inner.touch(key);
+        Some(response)
+    }
+
+    pub fn store(&self, key: String, response: MistralChatResponse) {
+        if self.ttl.is_none() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(&key);
+        inner.entries.insert(key, (Instant::now(), response));
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::mistral::{MistralChatRequest, MistralMessage};
+
+    fn request(temperature: Option<f32>, random_seed: Option<i32>) -> MistralChatRequest {
+        MistralChatRequest {
+            model: "mistral-7b".to_string(),
+            messages: vec![MistralMessage {
+                role: "user".to_string(),
+                content: "Hello".to_string(),
+                ..Default::default()
+            }],
+            temperature,
+            random_seed,
+            ..Default::default()
+        }
+    }
+
+    fn response(id: &str) -> MistralChatResponse {
+        MistralChatResponse {
+            id: id.to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "mistral-7b".to_string(),
+            choices: vec![],
+            usage: None,
+        }
+    }
+
+    fn cache(capacity: usize) -> ResponseCache {
+        ResponseCache::new(Some(Duration::from_secs(60)), capacity)
+    }
+
+    #[test]
+    fn test_only_deterministic_unstreamed_requests_cached() {
+        let cache = cache(10);
+        assert!(cache.key(false, &request(Some(0.0), None)).is_some());
+        assert!(cache.key(false, &request(Some(0.7), Some(42))).is_some());
+        assert!(cache.key(false, &request(Some(0.7), None)).is_none());
+        assert!(cache.key(true, &request(Some(0.0), None)).is_none());
+
+        let disabled = ResponseCache::new(None, 10);
+        assert!(disabled.key(false, &request(Some(0.0), None)).is_none());
+    }
+
+    #[test]
+    fn test_key_depends_on_parameters() {
+        let cache = cache(10);
+        assert_eq!(
+            cache.key(false, &request(Some(0.0), None)),
+            cache.key(false, &request(Some(0.0), None))
+        );
+        assert_ne!(
+            cache.key(false, &request(Some(0.0), None)),
+            cache.key(false, &request(Some(0.0), Some(1)))
+        );
+    }
+
+    #[test]
+    fn test_reply_served_only_for_the_same_request() {
+        let cache = cache(10);
+        let key = cache.key(false, &request(Some(0.0), None)).unwrap();
+        cache.store(key.clone(), response("a"));
+
+        let mut other = request(Some(0.0), None);
+        other.messages[0].content = "Goodbye".to_string();
+        let other_key = cache.key(false, &other).unwrap();
+
+        assert_eq!(cache.get(&key).unwrap().id, "a");
+        assert!(cache.get(&other_key).is_none());
+    }
+
+    #[test]
+    fn test_expired_entries_not_served() {
+        let cache = ResponseCache::new(Some(Duration::ZERO), 10);
+        cache.store("a".to_string(), response("a"));
+        assert!(cache.get("a").is_none());
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_evicted() {
+        let cache = cache(2);
+        cache.store("1".to_string(), response("a"));
+        cache.store("2".to_string(), response("b"));
+        cache.get("1");
+        cache.store("3".to_string(), response("c"));
+
+        assert_eq!(cache.get("1").unwrap().id, "a");
+        assert!(cache.get("2").is_none());
+        assert_eq!(cache.get("3").unwrap().id, "c");
+    }
+}
@@ -0,0 +1,112 @@
+//! Adds operator-configured headers, such as `Cache-Control` for a CDN, to proxy responses.
+//!
+//! Streamed responses are left alone, since caching or rewriting them breaks incremental
+//! delivery. Headers the framework or proxy manage can't be configured, and a header a handler
+//! already set is never replaced.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// Headers that describe the body or connection, or that the proxy sets itself.
+const PROTECTED_HEADERS: &[&str] = &[
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "keep-alive",
+    "retry-after",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "vary",
+    "www-authenticate",
+    "x-proxy-backend",
+    "x-request-id",
+];
+
+/// Content types of responses that are delivered incrementally.
+const STREAMING_CONTENT_TYPES: &[&str] = &["text/event-stream", "application/x-ndjson"];
+
+/// Validates the configured headers, rejecting invalid names or values and protected headers.
+pub fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::try_from(name.as_str())
+            .map_err(|_| format!("invalid header name {name:?}"))?;
+        if PROTECTED_HEADERS.contains(&name.as_str())
+            || name.as_str().starts_with("access-control-")
+        {
+            return Err(format!("{name} is managed by the proxy and can't be set"));
+        }
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| format!("invalid value {value:?} for header {name}"))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Adds the configured headers to non-streaming responses that don't already carry them.
+pub async fn add_response_headers(
+    State(headers): State<Arc<HeaderMap>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    if is_streaming(response.headers()) {
+        return response;
+    }
+
+    for (name, value) in headers.iter() {
+        if !response.headers().contains_key(name) {
+            response.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+    response
+}
+
+fn is_streaming(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|media_type| STREAMING_CONTENT_TYPES.contains(&media_type.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_header_map_accepts_valid_headers() {
+        let map = header_map(&headers(&[
+            ("Cache-Control", "public, max-age=60"),
+            ("X-Served-By", "edge-1"),
+        ]))
+        .unwrap();
+
+        assert_eq!(map[header::CACHE_CONTROL], "public, max-age=60");
+        assert_eq!(map["x-served-by"], "edge-1");
+    }
+
+    #[test]
+    fn test_header_map_rejects_invalid_and_protected_headers() {
+        assert!(header_map(&headers(&[("Bad Header", "x")])).is_err());
+        assert!(header_map(&headers(&[("X-Ok", "line\nbreak")])).is_err());
+        assert!(header_map(&headers(&[("Content-Type", "text/plain")])).is_err());
+        assert!(header_map(&headers(&[("Access-Control-Allow-Origin", "*")])).is_err());
+    }
+}
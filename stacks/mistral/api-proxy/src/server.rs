@@ -0,0 +1,178 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware,
+    routing::{delete, get, head, post},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::{
+    compression::{
+        predicate::{NotForContentType, Predicate},
+        CompressionLayer, DefaultPredicate,
+    },
+    cors::{AllowOrigin, CorsLayer},
+    decompression::RequestDecompressionLayer,
+    trace::TraceLayer,
+};
+use tracing::error;
+
+use crate::body_limit::reject_oversized_bodies;
+use crate::config::Config;
+use crate::deadline::DEADLINE_HEADER;
+use crate::forward_headers::{self, capture_forward_headers};
+use crate::handlers::chat::{
+    handle_abort, handle_chat, handle_generate, AppState, AGGREGATE_STREAM_HEADER, DRY_RUN_HEADER,
+    INCLUDE_RAW_HEADER, PROXY_BACKEND_HEADER, SESSION_ID_HEADER,
+};
+use crate::handlers::models::{
+    handle_blob_exists, handle_blob_upload, handle_copy, handle_create, handle_delete,
+    handle_list_models, handle_pull,
+};
+use crate::handlers::system::{
+    handle_health, handle_metrics, handle_readiness, handle_version, require_bearer_token,
+};
+use crate::request_id::{propagate_request_id, REQUEST_ID_HEADER};
+use crate::response_headers::add_response_headers;
+
+pub fn build_router(config: &Config, state: Arc<AppState>) -> Router {
+    let forwarded = forward_headers::allowlist(&config.forward_headers);
+    let cors = cors_layer(config, &forwarded);
+
+    // Bodies are deserialized in full, so cap them before they reach the JSON extractor. A
+    // declared length over the limit is turned away before the body is read at all.
+    let body_limit = DefaultBodyLimit::max(config.max_request_bytes);
+    let declared_length_limit =
+        middleware::from_fn_with_state(config.max_request_bytes, reject_oversized_bodies);
+
+    // Clients may gzip large prompts. The body limit is enforced as the extractor reads the
+    // decoded body, so it also caps the decompressed size against compression bombs.
+    let decompression = RequestDecompressionLayer::new();
+
+    // Compression buffers output, which would stall incremental delivery of streamed
+    // responses, so only non-streaming bodies are compressed.
+    let compression = CompressionLayer::new().compress_when(
+        DefaultPredicate::new().and(NotForContentType::const_new("application/x-ndjson")),
+    );
+
+    let mut metrics_routes = Router::new()
+        .route("/api/metrics", get(handle_metrics))
+        .route("/metrics", get(handle_metrics));
+    if let Some(token) = &config.metrics_auth_token {
+        metrics_routes = metrics_routes.route_layer(middleware::from_fn_with_state(
+            Arc::new(token.clone()),
+            require_bearer_token,
+        ));
+    }
+
+    // Endpoints an operator doesn't use can be left unregistered, so they 404
+    let mut completion_routes = Router::new();
+    if config.enable_generate {
+        completion_routes = completion_routes.route(
+            "/api/generate",
+            post(handle_generate)
+                .layer(body_limit)
+                .layer(declared_length_limit.clone()),
+        );
+    }
+    if config.enable_chat {
+        completion_routes = completion_routes.route(
+            "/api/chat",
+            post(handle_chat)
+                .layer(body_limit)
+                .layer(declared_length_limit),
+        );
+    }
+
+    let mut model_management_routes = Router::new();
+    if config.enable_model_management {
+        model_management_routes = model_management_routes
+            .route("/api/copy", post(handle_copy))
+            .route("/api/create", post(handle_create))
+            .route(
+                "/api/blobs/:digest",
+                head(handle_blob_exists).post(handle_blob_upload),
+            )
+            .route("/api/delete", delete(handle_delete))
+            .route("/api/pull", post(handle_pull));
+    }
+
+    Router::new()
+        .merge(completion_routes)
+        .route("/api/tags", get(handle_list_models))
+        .route("/api/models", get(handle_list_models))
+        .merge(model_management_routes)
+        .route("/api/abort", post(handle_abort))
+        .route("/api/version", get(handle_version))
+        .merge(metrics_routes)
+        .route("/readyz", get(handle_readiness))
+        .route("/", get(handle_health))
+        .layer(compression)
+        .layer(decompression)
+        .layer(middleware::from_fn_with_state(
+            Arc::new(config.response_headers.clone()),
+            add_response_headers,
+        ))
+        .layer(middleware::from_fn_with_state(
+            Arc::new(forwarded),
+            capture_forward_headers,
+        ))
+        .layer(cors)
+        .layer(middleware::from_fn(propagate_request_id))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+fn cors_layer(config: &Config, forwarded: &[HeaderName]) -> CorsLayer {
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+        .allow_headers(
+            [
+                header::CONTENT_TYPE,
+                header::CONTENT_ENCODING,
+                header::AUTHORIZATION,
+                REQUEST_ID_HEADER.clone(),
+                DEADLINE_HEADER.clone(),
+                DRY_RUN_HEADER.clone(),
+                AGGREGATE_STREAM_HEADER.clone(),
+                SESSION_ID_HEADER.clone(),
+                INCLUDE_RAW_HEADER.clone(),
+            ]
+            .into_iter()
+            .chain(forwarded.iter().cloned())
+            .collect::<Vec<_>>(),
+        )
+        .expose_headers([REQUEST_ID_HEADER.clone(), PROXY_BACKEND_HEADER.clone()])
+        .allow_credentials(config.cors_allow_credentials);
+
+    cors.allow_origin(allowed_origins(config))
+}
+
+fn allowed_origins(config: &Config) -> AllowOrigin {
+    if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        // Browsers reject a literal `*` on credentialed requests, so echo the caller's origin instead
+        return if config.cors_allow_credentials {
+            AllowOrigin::mirror_request()
+        } else {
+            AllowOrigin::any()
+        };
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| match origin.parse::<HeaderValue>() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                error!("Ignoring invalid CORS origin: {:?}", origin);
+                None
+            }
+        })
+        .collect();
+
+    AllowOrigin::list(origins)
+}
@@ -0,0 +1,163 @@
+//! Server-side chat sessions, keyed by the client's `X-Session-Id` header.
+//!
+//! Clients in a session send only their new messages each turn; the proxy prepends the
+//! session's earlier messages before forwarding and records the new exchange once the reply is
+//! complete. Sessions live in memory, so they are lost on restart, and the least recently used
+//! session is dropped when there are too many.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::models::mistral::MistralMessage;
+
+#[derive(Debug)]
+pub struct SessionStore {
+    max_sessions: usize,
+    max_messages: usize,
+    inner: Mutex<SessionStoreInner>,
+}
+
+#[derive(Debug, Default)]
+struct SessionStoreInner {
+    sessions: HashMap<String, Vec<MistralMessage>>,
+    // Least recently used first, used to evict when over capacity
+    order: VecDeque<String>,
+}
+
+impl SessionStoreInner {
+    fn touch(&mut self, id: &str) {
+        if let Some(position) = self.order.iter().position(|entry| entry == id) {
+            self.order.remove(position);
+        }
+        self.order.push_back(id.to_string());
+    }
+}
+
+impl SessionStore {
+    /// Keeps at most `max_sessions` sessions of at most `max_messages` messages each; a
+    /// `max_sessions` of 0 disables sessions.
+    pub fn new(max_sessions: usize, max_messages: usize) -> Self {
+        SessionStore {
+            max_sessions,
+            max_messages,
+            inner: Mutex::new(SessionStoreInner::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_sessions > 0
+    }
+
+    /// The session's earlier messages, oldest first; empty for a new session.
+    pub fn history(&self, id: &str) -> Vec<MistralMessage> {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(messages) = inner.sessions.get(id).cloned() else {
+            return Vec::new();
+        };
+        inner.touch(id);
+        messages
+    }
+
+    /// Appends a turn to the session, keeping only its most recent messages.
+    pub fn append(&self, id: &str, messages: Vec<MistralMessage>) {
+        if !self.enabled() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let history = inner.sessions.entry(id.to_string()).or_default();
+        history.extend(messages);
+        let excess = history.len().saturating_sub(self.max_messages);
+        history.drain(..excess);
+        inner.touch(id);
+
+        while inner.order.len() > self.max_sessions {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.sessions.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A turn's new messages, recorded in their session once the reply is known.
+#[derive(Debug, Clone)]
+pub struct SessionTurn {
+    store: Arc<SessionStore>,
+    id: String,
+    messages: Vec<MistralMessage>,
+}
+
+impl SessionTurn {
+    pub fn new(store: Arc<SessionStore>, id: String, messages: Vec<MistralMessage>) -> Self {
+        SessionTurn {
+            store,
+            id,
+            messages,
+        }
+    }
+
+    pub fn complete(mut self, reply: String) {
+        self.messages.push(MistralMessage {
+            role: "assistant".to_string(),
+            content: reply,
+            ..Default::default()
+        });
+        self.store.append(&self.id, self.messages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(content: &str) -> MistralMessage {
+        MistralMessage {
+            role: "user".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_history_accumulates_across_turns() {
+        let store = Arc::new(SessionStore::new(10, 10));
+        SessionTurn::new(store.clone(), "s".to_string(), vec![user("one")])
+            .complete("first".to_string());
+        SessionTurn::new(store.clone(), "s".to_string(), vec![user("two")])
+            .complete("second".to_string());
+
+        let contents: Vec<String> = store.history("s").into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, ["one", "first", "two", "second"]);
+        assert!(store.history("other").is_empty());
+    }
+
+    #[test]
+    fn test_history_keeps_most_recent_messages() {
+        let store = SessionStore::new(10, 2);
+        store.append("s", vec![user("one"), user("two"), user("three")]);
+
+        let contents: Vec<String> = store.history("s").into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, ["two", "three"]);
+    }
+
+    #[test]
+    fn test_least_recently_used_session_evicted() {
+        let store = SessionStore::new(2, 10);
+        store.append("a", vec![user("a")]);
+        store.append("b", vec![user("b")]);
+        // Reading `a` makes `b` the least recently used
+        store.history("a");
+        store.append("c", vec![user("c")]);
+
+        assert!(!store.history("a").is_empty());
+        assert!(store.history("b").is_empty());
+        assert!(!store.history("c").is_empty());
+    }
+
+    #[test]
+    fn test_disabled_store_records_nothing() {
+        let store = SessionStore::new(0, 10);
+        store.append("s", vec![user("one")]);
+        assert!(store.history("s").is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use std::time::Duration;
+
+/// SSE comment line; clients ignore it, but it keeps intermediaries from timing out.
+pub const KEEPALIVE_COMMENT: &str = ": keepalive\n\n";
+
+/// Accumulates raw stream bytes and hands out complete lines without copying them.
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    buf: BytesMut,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn extend(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Number of buffered bytes not yet returned as a line.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Length of the trailing line segment that has not yet been terminated by a `\n`.
+    pub fn partial_line_len(&self) -> usize {
+        match memchr::memrchr(b'\n', &self.buf) {
+            Some(end) => self.buf.len() - end - 1,
+            None => self.buf.len(),
+        }
+    }
+
+    /// Removes the next complete line from the buffer, without its `\n` terminator.
+    pub fn next_line(&mut self) -> Option<Bytes> {
+        let end = memchr::memchr(b'\n', &self.buf)?;
+        let mut line = self.buf.split_to(end + 1).freeze();
+        line.truncate(end);
+        Some(line)
+    }
+}
+
+/// Returns the payload of an SSE `data: ` line, ignoring surrounding whitespace.
+pub fn data_payload(line: &[u8]) -> Option<&[u8]> {
+    line.trim_ascii().strip_prefix(b"data: ")
+}
+
+/// Passes `events` through, emitting [`KEEPALIVE_COMMENT`] every `interval` until the first
+/// event arrives (e.g. while the backend is still prefilling a long prompt).
+pub fn with_keepalive<S, E>(
+    events: S,
+    interval: Option<Duration>,
+) -> impl Stream<Item = Result<String, E>>
+where
+    S: Stream<Item = Result<String, E>>,
+{
+    async_stream::stream! {
+        let mut events = Box::pin(events);
+
+        if let Some(interval) = interval {
+            let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                tokio::select! {
+                    event = events.next() => {
+                        match event {
+                            Some(event) => {
+                                yield event;
+                                break;
+                            }
+                            None => return,
+                        }
+                    }
+                    _ = ticker.tick() => yield Ok(KEEPALIVE_COMMENT.to_string()),
+                }
+            }
+        }
+
+        while let Some(event) = events.next().await {
+            yield event;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_split_across_chunks() {
+        let mut buffer = LineBuffer::new();
+        buffer.extend(b"data: {\"a\"");
+        assert_eq!(buffer.next_line(), None);
+
+        buffer.extend(b":1}\ndata: [DO");
+        assert_eq!(buffer.next_line().as_deref(), Some(&b"data: {\"a\":1}"[..]));
+        assert_eq!(buffer.next_line(), None);
+
+        buffer.extend(b"NE]\n");
+        assert_eq!(buffer.next_line().as_deref(), Some(&b"data: [DONE]"[..]));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_partial_line_len_ignores_complete_lines() {
+        let mut buffer = LineBuffer::new();
+        buffer.extend(b"data: 1\ndata: 2\n");
+        assert_eq!(buffer.partial_line_len(), 0);
+
+        buffer.extend(b"data: 3");
+        assert_eq!(buffer.partial_line_len(), 7);
+        assert_eq!(buffer.len(), 23);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_stops_once_data_flows() {
+        let events = futures::stream::once(async {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+            Ok::<_, ()>("data: first\n\n".to_string())
+        })
+        .chain(futures::stream::iter(vec![Ok(
+            "data: second\n\n".to_string()
+        )]));
+
+        let out: Vec<String> = with_keepalive(events, Some(Duration::from_millis(100)))
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(
+            out,
+            vec![
+                KEEPALIVE_COMMENT,
+                KEEPALIVE_COMMENT,
+                "data: first\n\n",
+                "data: second\n\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_data_payload() {
+        assert_eq!(data_payload(b"data: {}\r"), Some(&b"{}"[..]));
+        assert_eq!(data_payload(b"  data: [DONE]  "), Some(&b"[DONE]"[..]));
+        assert_eq!(data_payload(b": keep-alive"), None);
+        assert_eq!(data_payload(b""), None);
+    }
+}
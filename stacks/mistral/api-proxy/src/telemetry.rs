@@ -0,0 +1,62 @@
+//! Optional OpenTelemetry trace export.
+//!
+//! When `OTEL_EXPORTER_OTLP_ENDPOINT` is set, tracing spans are exported over OTLP/HTTP and the
+//! W3C `traceparent` header links the client's trace, the proxy's spans, and the backend call.
+//! When it is unset no tracer is installed: spans carry no OpenTelemetry context, so nothing is
+//! extracted or injected.
+
+use std::collections::HashMap;
+
+use axum::http::HeaderMap;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::TraceError;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{self as sdktrace, Tracer};
+use opentelemetry_sdk::Resource;
+use reqwest::RequestBuilder;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Installs a batching OTLP/HTTP exporter sending to `endpoint` and returns its tracer.
+pub fn init_tracer(endpoint: &str, service_name: &str) -> Result<Tracer, TraceError> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            sdktrace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+}
+
+/// Flushes spans still queued for export.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Makes `span` a child of the trace described by the incoming `traceparent`, if any.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let carrier: HashMap<String, String> = headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let context = TraceContextPropagator::new().extract(&carrier);
+    span.set_parent(context);
+}
+
+/// Adds a `traceparent` for the current span, so the backend's work joins the same trace.
+pub fn inject_trace_context(builder: RequestBuilder) -> RequestBuilder {
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(&Span::current().context(), &mut carrier);
+    carrier.into_iter().fold(builder, |builder, (name, value)| {
+        builder.header(name, value)
+    })
+}
@@ -0,0 +1,78 @@
+use crate::models::mistral::MistralMessage;
+
+/// Used for models without a configured template: each message on its own role-labelled line.
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "{{role}}: {{content}}\n\n";
+
+/// Renders `messages` into a single prompt for a plain completions endpoint.
+///
+/// `template` is applied to each message in turn, with `{{role}}` and `{{content}}` replaced by
+/// the message's fields; any other text, including unknown placeholders, is kept verbatim.
+pub fn render_prompt(template: &str, messages: &[MistralMessage]) -> String {
+    let mut prompt = String::new();
+    for message in messages {
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            prompt.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let value = if placeholder.starts_with("{{role}}") {
+                Some(message.role.as_str())
+            } else if placeholder.starts_with("{{content}}") {
+                Some(message.content.as_str())
+            } else {
+                None
+            };
+            match value {
+                Some(value) => {
+                    prompt.push_str(value);
+                    rest = &placeholder[placeholder.find("}}").unwrap() + 2..];
+                }
+                None => {
+                    prompt.push_str("{{");
+                    rest = &placeholder[2..];
+                }
+            }
+        }
+        prompt.push_str(rest);
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> MistralMessage {
+        MistralMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_prompt_with_template() {
+        let messages = [message("system", "Be brief."), message("user", "Hi")];
+        assert_eq!(
+            render_prompt("<|{{role}}|>\n{{content}}</s>\n", &messages),
+            "<|system|>\nBe brief.</s>\n<|user|>\nHi</s>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_prompt_with_default_template() {
+        let messages = [message("user", "Hi"), message("assistant", "Hello!")];
+        assert_eq!(
+            render_prompt(DEFAULT_PROMPT_TEMPLATE, &messages),
+            "user: Hi\n\nassistant: Hello!\n\n"
+        );
+    }
+
+    #[test]
+    fn test_placeholders_in_content_are_not_expanded() {
+        let messages = [message("user", "say {{role}}")];
+        assert_eq!(
+            render_prompt("[{{role}}] {{content}} {{other}}", &messages),
+            "[user] say {{role}} {{other}}"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::handlers::chat::{backend_post, backend_request_body, with_timeout, AppState};
+use crate::metrics::{ACTIVE_REQUESTS, MODEL_LOAD_DURATION_SECONDS};
+use crate::models::mistral::{MistralChatRequest, MistralMessage};
+
+/// Sends a one-token generation to each model so the backend loads it before real traffic.
+///
+/// Failures are logged and skipped; readiness is marked complete once every model was tried.
+pub async fn warmup_models(state: Arc<AppState>, models: Vec<String>) {
+    for model in &models {
+        let model_name = state.translate_model(model);
+        info!("Warming up model {}", model_name);
+
+        let started = Instant::now();
+        match warmup_model(&state, &model_name).await {
+            Ok(()) => {
+                let elapsed = started.elapsed();
+                MODEL_LOAD_DURATION_SECONDS
+                    .with_label_values(&[state.model_label(model)])
+                    .observe(elapsed.as_secs_f64());
+                info!("Model {} warmed up in {:.2?}", model_name, elapsed);
+            }
+            Err(e) => warn!("Warmup of model {} failed: {}", model_name, e),
+        }
+    }
+
+    state.readiness.mark_warmup_complete();
+}
+
+/// Pings `model` with a one-token generation every `interval` so the backend keeps it loaded.
+///
+/// Runs until the task is dropped. Pings are skipped while completions are in flight, since
+/// real traffic keeps the model resident and a ping would only compete with it.
+pub async fn keep_model_resident(state: Arc<AppState>, model: String, interval: Duration) {
+    let model_name = state.translate_model(&model);
+    info!(
+        "Keeping model {} resident with a ping every {:?}",
+        model_name, interval
+    );
+
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        if ACTIVE_REQUESTS.get() > 0 {
+            debug!("Skipping keepalive of {}, requests are active", model_name);
+            continue;
+        }
+        match warmup_model(&state, &model_name).await {
+            Ok(()) => debug!("Keepalive of {} succeeded", model_name),
+            Err(e) => warn!("Keepalive of model {} failed: {}", model_name, e),
+        }
+    }
+}
+
+async fn warmup_model(state: &AppState, model_name: &str) -> Result<(), String> {
+    let url = state.backend_url("/chat/completions");
+    let req = MistralChatRequest {
+        model: model_name.to_string(),
+        messages: vec![MistralMessage {
+            role: "user".to_string(),
+            content: "Hi".to_string(),
+            ..Default::default()
+        }],
+        stream: Some(false),
+        max_tokens: Some(1),
+        ..Default::default()
+    };
+
+    let body = backend_request_body(&req, &state.backend_seed_field).map_err(|e| e.to_string())?;
+    let response = with_timeout(backend_post(state, &url), state.sync_request_timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("backend returned {}", response.status()));
+    }
+
+    Ok(())
+}
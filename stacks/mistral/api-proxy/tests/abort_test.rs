@@ -0,0 +1,103 @@
+use axum::{body::Body, routing::post, Router};
+use futures::StreamExt;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+mod common;
+
+use common::{spawn_backend, spawn_proxy, stream_chunk, test_config, test_server};
+
+/// Sends one chunk, then holds the stream open forever. `closed` resolves once the proxy drops
+/// the backend response.
+async fn hanging_backend() -> (String, oneshot::Receiver<()>) {
+    let (closed_tx, closed_rx) = oneshot::channel::<()>();
+    let closed_tx = Arc::new(Mutex::new(Some(closed_tx)));
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let closed_tx = closed_tx.lock().unwrap().take().unwrap();
+            async move {
+                let stream = async_stream::stream! {
+                    // Dropped along with the stream when the proxy stops reading
+                    let _closed_tx = closed_tx;
+                    yield Ok::<_, std::io::Error>(format!("data: {}\n\n", stream_chunk("Hi")));
+                    std::future::pending::<()>().await;
+                };
+                Body::from_stream(stream)
+            }
+        }),
+    );
+    (spawn_backend(backend).await, closed_rx)
+}
+
+#[tokio::test]
+async fn test_abort_ends_stream_and_backend_call() {
+    let (backend_url, backend_closed) = hanging_backend().await;
+    let proxy_url = spawn_proxy(&test_config(&backend_url)).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{proxy_url}/api/chat"))
+        .header("x-request-id", "stop-me")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    let mut body = response.bytes_stream();
+    let first = body.next().await.unwrap().unwrap();
+    assert!(String::from_utf8_lossy(&first).contains("Hi"));
+
+    let abort = client
+        .post(format!("{proxy_url}/api/abort"))
+        .json(&json!({"request_id": "stop-me"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(abort.status(), 200);
+
+    let rest = tokio::time::timeout(Duration::from_secs(5), async {
+        let mut rest = Vec::new();
+        while let Some(chunk) = body.next().await {
+            rest.extend_from_slice(&chunk.unwrap());
+        }
+        String::from_utf8(rest).unwrap()
+    })
+    .await
+    .expect("stream should close after abort");
+    assert!(rest.contains("Request aborted"), "rest of stream: {rest:?}");
+
+    tokio::time::timeout(Duration::from_secs(5), backend_closed)
+        .await
+        .expect("backend stream should be dropped")
+        .unwrap_err();
+
+    // The registry entry is gone, so a second abort finds nothing
+    let again = client
+        .post(format!("{proxy_url}/api/abort"))
+        .json(&json!({"request_id": "stop-me"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status(), 404);
+}
+
+#[tokio::test]
+async fn test_abort_unknown_request_returns_not_found() {
+    let server = test_server(&test_config("http://localhost:1"));
+
+    let response = server
+        .post("/api/abort")
+        .json(&json!({"request_id": "never-started"}))
+        .await;
+
+    response.assert_status_not_found();
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("never-started"));
+}
@@ -0,0 +1,71 @@
+use axum::{body::Body, routing::post, Router};
+use futures::StreamExt;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use mistral_ollama_proxy::metrics::ACTIVE_STREAMS;
+
+mod common;
+
+use common::{spawn_backend, spawn_proxy, stream_chunk, test_config};
+
+async fn wait_for_gauge(expected: i64) {
+    for _ in 0..100 {
+        if ACTIVE_STREAMS.get() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!(
+        "mistral_active_streams stayed at {} (expected {expected})",
+        ACTIVE_STREAMS.get()
+    );
+}
+
+#[tokio::test]
+async fn test_active_streams_gauge_rises_and_falls() {
+    let (release_tx, release_rx) = oneshot::channel::<()>();
+    let release_rx = Arc::new(Mutex::new(Some(release_rx)));
+
+    // Sends one chunk, then holds the stream open until released
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let release_rx = release_rx.lock().unwrap().take().unwrap();
+            async move {
+                let stream = async_stream::stream! {
+                    yield Ok::<_, std::io::Error>(format!("data: {}\n\n", stream_chunk("Hi")));
+                    let _ = release_rx.await;
+                    yield Ok("data: [DONE]\n\n".to_string());
+                };
+                Body::from_stream(stream)
+            }
+        }),
+    );
+    let backend_url = spawn_backend(backend).await;
+    let proxy_url = spawn_proxy(&test_config(&backend_url)).await;
+
+    let baseline = ACTIVE_STREAMS.get();
+
+    let response = reqwest::Client::new()
+        .post(format!("{proxy_url}/api/chat"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    let mut body = response.bytes_stream();
+
+    let first = body.next().await.unwrap().unwrap();
+    assert!(String::from_utf8_lossy(&first).contains("Hi"));
+    wait_for_gauge(baseline + 1).await;
+
+    release_tx.send(()).unwrap();
+    while body.next().await.is_some() {}
+    wait_for_gauge(baseline).await;
+}
@@ -0,0 +1,78 @@
+use axum::{http::HeaderValue, routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{spawn_backend, sse_body, stream_chunk, test_config, test_server, usage_chunk};
+use mistral_ollama_proxy::handlers::chat::AGGREGATE_STREAM_HEADER;
+
+async fn server(captured: Arc<Mutex<Vec<Value>>>) -> axum_test::TestServer {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured.clone();
+            async move {
+                captured.lock().unwrap().push(body);
+                sse_body(&[
+                    stream_chunk("Hello"),
+                    stream_chunk(", "),
+                    stream_chunk("world"),
+                    usage_chunk(7, 3),
+                ])
+            }
+        }),
+    );
+    test_server(&test_config(&spawn_backend(backend).await))
+}
+
+#[tokio::test]
+async fn test_chat_stream_aggregated_into_one_response() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let server = server(captured.clone()).await;
+
+    let response = server
+        .post("/api/chat")
+        .add_header(
+            AGGREGATE_STREAM_HEADER.clone(),
+            HeaderValue::from_static("true"),
+        )
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/json");
+    let body: Value = response.json();
+    assert_eq!(body["message"]["role"], "assistant");
+    assert_eq!(body["message"]["content"], "Hello, world");
+    assert_eq!(body["done"], true);
+    assert_eq!(body["prompt_eval_count"], 7);
+    assert_eq!(body["eval_count"], 3);
+
+    // The backend still streamed
+    assert_eq!(captured.lock().unwrap()[0]["stream"], true);
+}
+
+#[tokio::test]
+async fn test_generate_stream_aggregated_into_one_response() {
+    let server = server(Arc::default()).await;
+
+    let body: Value = server
+        .post("/api/generate")
+        .add_header(
+            AGGREGATE_STREAM_HEADER.clone(),
+            HeaderValue::from_static("1"),
+        )
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "stream": true}))
+        .await
+        .json();
+
+    assert_eq!(body["response"], "Hello, world");
+    assert_eq!(body["done"], true);
+    assert_eq!(body["eval_count"], 3);
+    assert!(body["context"].is_array());
+}
@@ -0,0 +1,75 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+
+use mistral_ollama_proxy::metrics::MODEL_DENIED_TOTAL;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+async fn server(allowed_models: Option<&[&str]>) -> axum_test::TestServer {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hi")) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.allowed_models =
+        allowed_models.map(|models| models.iter().map(|m| m.to_string()).collect());
+    test_server(&config)
+}
+
+fn chat_request(model: &str) -> Value {
+    json!({
+        "model": model,
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    })
+}
+
+#[tokio::test]
+async fn test_allowed_model_served() {
+    server(Some(&["mistral:latest"]))
+        .await
+        .post("/api/chat")
+        .json(&chat_request("mistral:latest"))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_denied_model_rejected() {
+    let denied = MODEL_DENIED_TOTAL.with_label_values(&["mixtral:latest"]);
+    let before = denied.get();
+    let server = server(Some(&["mistral:latest"])).await;
+
+    let response = server
+        .post("/api/chat")
+        .json(&chat_request("mixtral:latest"))
+        .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "model 'mixtral:latest' is not allowed"
+    );
+
+    server
+        .post("/api/generate")
+        .json(&json!({"model": "mixtral:latest", "prompt": "Hello", "stream": false}))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    assert_eq!(denied.get() - before, 2.0);
+}
+
+#[tokio::test]
+async fn test_every_model_allowed_when_unset() {
+    let server = server(None).await;
+
+    for model in ["mistral:latest", "mixtral:latest", "custom-model"] {
+        server
+            .post("/api/chat")
+            .json(&chat_request(model))
+            .await
+            .assert_status_ok();
+    }
+}
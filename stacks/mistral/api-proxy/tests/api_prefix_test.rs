@@ -0,0 +1,99 @@
+use axum::{
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+use mistral_ollama_proxy::config::parse_api_prefix;
+
+/// A backend serving the OpenAI-style API below `prefix`.
+fn prefixed_backend(prefix: &str) -> Router {
+    Router::new()
+        .route(
+            &format!("{prefix}/chat/completions"),
+            post(|Json(_): Json<Value>| async { chat_completion("Hi").to_string() }),
+        )
+        .route(
+            &format!("{prefix}/models"),
+            get(|| async {
+                Json(json!({
+                    "object": "list",
+                    "data": [{"id": "codestral", "object": "model", "created": 0, "owned_by": "mistral"}]
+                }))
+            }),
+        )
+}
+
+#[test]
+fn test_parse_api_prefix() {
+    assert_eq!(parse_api_prefix("/v1").unwrap(), "/v1");
+    assert_eq!(parse_api_prefix("/openai/v1/").unwrap(), "/openai/v1");
+    assert_eq!(parse_api_prefix("").unwrap(), "");
+    assert_eq!(parse_api_prefix("/").unwrap(), "");
+
+    assert!(parse_api_prefix("v1").is_err());
+    assert!(parse_api_prefix("/v1?x=1").is_err());
+    assert!(parse_api_prefix("/v1#frag").is_err());
+    assert!(parse_api_prefix("/my api").is_err());
+}
+
+#[tokio::test]
+async fn test_requests_use_configured_prefix() {
+    let mut config = test_config(&spawn_backend(prefixed_backend("/openai/v1")).await);
+    config.backend_api_prefix = "/openai/v1".to_string();
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["message"]["content"], "Hi");
+
+    let body: Value = server.get("/api/tags").await.json();
+    assert_eq!(body["models"][0]["name"], "codestral:latest");
+}
+
+#[tokio::test]
+async fn test_empty_prefix_uses_backend_root() {
+    let mut config = test_config(&spawn_backend(prefixed_backend("")).await);
+    config.backend_api_prefix = String::new();
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hello",
+            "stream": false
+        }))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["response"], "Hi");
+}
+
+#[tokio::test]
+async fn test_default_prefix_misses_prefixed_backend() {
+    let server = test_server(&test_config(
+        &spawn_backend(prefixed_backend("/openai/v1")).await,
+    ));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hello",
+            "stream": false
+        }))
+        .await;
+    assert!(!response.status_code().is_success());
+}
@@ -0,0 +1,55 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+async fn backend() -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(|Json(body): Json<Value>| async move {
+            if body["stream"] == true {
+                sse_body(&[stream_chunk("Hi")])
+            } else {
+                chat_completion("Hi").to_string()
+            }
+        }),
+    ))
+    .await
+}
+
+fn chat_request(stream: bool) -> Value {
+    json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": stream
+    })
+}
+
+#[tokio::test]
+async fn test_backend_header_present_when_enabled() {
+    let backend = backend().await;
+    let mut config = test_config(&backend);
+    config.expose_backend_header = true;
+    let server = test_server(&config);
+
+    for stream in [false, true] {
+        let response = server.post("/api/chat").json(&chat_request(stream)).await;
+        response.assert_status_ok();
+        assert_eq!(response.header("x-proxy-backend"), backend.as_str());
+    }
+}
+
+#[tokio::test]
+async fn test_backend_header_absent_when_disabled() {
+    let mut config = test_config(&backend().await);
+    config.expose_backend_header = false;
+    let server = test_server(&config);
+
+    for stream in [false, true] {
+        let response = server.post("/api/chat").json(&chat_request(stream)).await;
+        response.assert_status_ok();
+        assert!(response.headers().get("x-proxy-backend").is_none());
+    }
+}
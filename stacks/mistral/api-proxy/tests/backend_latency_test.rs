@@ -0,0 +1,40 @@
+//! Kept in its own test binary so no other requests add samples to the histograms.
+
+use axum::{routing::post, Json, Router};
+use serde_json::json;
+use std::time::Duration;
+
+use mistral_ollama_proxy::metrics::{BACKEND_DURATION_SECONDS, HTTP_REQUEST_DURATION_SECONDS};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+#[tokio::test]
+async fn test_backend_duration_recorded_alongside_handler_duration() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Json(chat_completion("Hi"))
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let backend = BACKEND_DURATION_SECONDS.with_label_values(&["chat"]);
+    let total = HTTP_REQUEST_DURATION_SECONDS.with_label_values(&["chat"]);
+    assert_eq!(backend.get_sample_count(), 1);
+    assert_eq!(total.get_sample_count(), 1);
+    assert!(backend.get_sample_sum() >= 0.05);
+    assert!(backend.get_sample_sum() <= total.get_sample_sum());
+}
@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::{http::StatusCode, routing::post, Router};
+use serde_json::json;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+use mistral_ollama_proxy::metrics::BACKEND_HEALTHY;
+
+/// A backend counting its requests, answering with `status` (and a completion when it's 200).
+async fn counting_backend(status: StatusCode, hits: Arc<AtomicUsize>) -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move {
+            hits.fetch_add(1, Ordering::SeqCst);
+            (status, chat_completion("Hi").to_string())
+        }),
+    ))
+    .await
+}
+
+#[tokio::test]
+async fn test_traffic_shifts_to_healthy_backend() {
+    let healthy_hits = Arc::new(AtomicUsize::new(0));
+    let failing_hits = Arc::new(AtomicUsize::new(0));
+    let healthy = counting_backend(StatusCode::OK, healthy_hits.clone()).await;
+    let failing = counting_backend(StatusCode::INTERNAL_SERVER_ERROR, failing_hits.clone()).await;
+
+    let mut config = test_config(&healthy);
+    config.backend_urls = vec![healthy.clone(), failing.clone()];
+    // Leave backend failures to the selector rather than the circuit breakers
+    config.circuit_failure_threshold = 0;
+    let server = test_server(&config);
+
+    for _ in 0..40 {
+        server
+            .post("/api/chat")
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": "Hello"}],
+                "stream": false
+            }))
+            .await;
+    }
+
+    let healthy_hits = healthy_hits.load(Ordering::SeqCst);
+    let failing_hits = failing_hits.load(Ordering::SeqCst);
+    assert_eq!(healthy_hits + failing_hits, 40);
+    assert!(
+        failing_hits <= 8,
+        "failing backend still got {failing_hits} requests"
+    );
+
+    assert_eq!(BACKEND_HEALTHY.with_label_values(&[&failing]).get(), 0);
+    assert_eq!(BACKEND_HEALTHY.with_label_values(&[&healthy]).get(), 1);
+}
@@ -0,0 +1,95 @@
+use axum::body::Body;
+use axum::{routing::post, Json, Router};
+use futures::StreamExt;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server,
+};
+
+/// A backend whose sync replies take `delay` and whose streams send a token every `delay`.
+async fn slow_backend(delay: Duration) -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(req): Json<serde_json::Value>| async move {
+            if req["stream"] == json!(true) {
+                let chunks =
+                    futures::stream::iter(["One ", "two ", "three"]).then(move |word| async move {
+                        tokio::time::sleep(delay).await;
+                        Ok::<_, std::io::Error>(sse_body(&[stream_chunk(word)]))
+                    });
+                Body::from_stream(chunks.boxed())
+            } else {
+                tokio::time::sleep(delay).await;
+                Body::from(chat_completion("Slow reply").to_string())
+            }
+        }),
+    ))
+    .await
+}
+
+fn chat_request(stream: bool) -> serde_json::Value {
+    json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": stream
+    })
+}
+
+#[tokio::test]
+async fn test_sync_call_times_out_at_sync_limit() {
+    let backend = slow_backend(Duration::from_secs(3)).await;
+    let mut config = test_config(&backend);
+    config.sync_request_timeout_secs = 1;
+    config.stream_total_timeout_secs = 30;
+    let server = test_server(&config);
+
+    let started = Instant::now();
+    let response = server.post("/api/chat").json(&chat_request(false)).await;
+
+    assert!(response.status_code().is_server_error());
+    assert!(started.elapsed() < Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn test_stream_outlives_sync_limit() {
+    // The whole stream takes about 1.5s, past the 1s sync limit
+    let backend = slow_backend(Duration::from_millis(500)).await;
+    let mut config = test_config(&backend);
+    config.sync_request_timeout_secs = 1;
+    config.stream_total_timeout_secs = 30;
+    let server = test_server(&config);
+
+    let body = server
+        .post("/api/chat")
+        .json(&chat_request(true))
+        .await
+        .text();
+
+    let content: String = parse_proxy_events(&body)
+        .iter()
+        .filter_map(|event| event["message"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "One two three");
+}
+
+#[test]
+fn test_timeouts_default_to_request_timeout() {
+    let config = test_config("http://localhost:1");
+    assert_eq!(
+        config.sync_request_timeout_secs,
+        config.request_timeout_secs
+    );
+    assert_eq!(
+        config.stream_total_timeout_secs,
+        config.request_timeout_secs
+    );
+    assert_eq!(
+        config.sync_request_timeout(),
+        Some(Duration::from_secs(300))
+    );
+}
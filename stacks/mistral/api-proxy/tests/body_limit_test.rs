@@ -0,0 +1,122 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+mod common;
+
+fn create_test_server(max_request_bytes: usize) -> TestServer {
+    let mut config = common::test_config("http://localhost:0"); // Non-existent backend
+    config.max_request_bytes = max_request_bytes;
+
+    common::test_server(&config)
+}
+
+#[tokio::test]
+async fn test_oversized_generate_body_is_rejected() {
+    let server = create_test_server(1024);
+
+    let response = server
+        .post("/api/generate")
+        .json(&serde_json::json!({
+            "model": "test-model",
+            "prompt": "x".repeat(4096),
+            "stream": false
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_oversized_chat_body_is_rejected() {
+    let server = create_test_server(1024);
+
+    let response = server
+        .post("/api/chat")
+        .json(&serde_json::json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "x".repeat(4096)}],
+            "stream": false
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_body_within_limit_reaches_handler() {
+    let server = create_test_server(1024);
+
+    let response = server
+        .post("/api/generate")
+        .json(&serde_json::json!({
+            "model": "test-model",
+            "prompt": "Hello",
+            "stream": false
+        }))
+        .await;
+
+    // The backend is unreachable, so anything but 413 means the limit let it through
+    assert_ne!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_oversized_content_length_rejected_before_body_is_read() {
+    let mut config = common::test_config("http://localhost:0");
+    config.max_request_bytes = 1024;
+    let proxy = common::spawn_proxy(&config).await;
+
+    // Only the headers are sent, so a reply means the body was never waited for
+    let mut socket = tokio::net::TcpStream::connect(proxy.trim_start_matches("http://"))
+        .await
+        .unwrap();
+    socket
+        .write_all(
+            b"POST /api/chat HTTP/1.1\r\nHost: localhost\r\n\
+              Content-Type: application/json\r\nContent-Length: 1000000000\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut response = vec![0; 1024];
+    let read = tokio::time::timeout(Duration::from_secs(5), socket.read(&mut response))
+        .await
+        .expect("proxy waited for the body")
+        .unwrap();
+    let response = String::from_utf8_lossy(&response[..read]);
+    assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+    assert!(
+        response.contains("exceeds the 1024 byte limit"),
+        "{response}"
+    );
+}
+
+#[tokio::test]
+async fn test_chunked_body_without_content_length_is_limited() {
+    let mut config = common::test_config("http://localhost:0");
+    config.max_request_bytes = 1024;
+    let proxy = common::spawn_proxy(&config).await;
+
+    let body = serde_json::json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "x".repeat(4096)}],
+        "stream": false
+    })
+    .to_string();
+    // A streamed body is sent chunked, without a Content-Length
+    let chunks: Vec<Result<String, std::io::Error>> = body
+        .as_bytes()
+        .chunks(512)
+        .map(|chunk| Ok(String::from_utf8(chunk.to_vec()).unwrap()))
+        .collect();
+
+    let response = reqwest::Client::new()
+        .post(format!("{proxy}/api/chat"))
+        .body(reqwest::Body::wrap_stream(futures::stream::iter(chunks)))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+}
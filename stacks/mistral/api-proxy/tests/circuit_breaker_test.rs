@@ -0,0 +1,112 @@
+use axum::{http::StatusCode, routing::post, Router};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use mistral_ollama_proxy::metrics::CIRCUIT_STATE;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+#[tokio::test]
+async fn test_repeated_failures_open_circuit() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let backend = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let calls = calls_clone.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::INTERNAL_SERVER_ERROR, "model crashed")
+            }
+        }),
+    ))
+    .await;
+
+    let mut config = test_config(&backend);
+    config.circuit_failure_threshold = 3;
+    config.circuit_cooldown_secs = 60;
+    let server = test_server(&config);
+    let request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    });
+
+    for _ in 0..3 {
+        server
+            .post("/api/chat")
+            .json(&request)
+            .await
+            .assert_status(StatusCode::BAD_GATEWAY);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(CIRCUIT_STATE.with_label_values(&[&backend]).get(), 1);
+
+    // While open, requests fail immediately without reaching the backend
+    let started = Instant::now();
+    let response = server.post("/api/chat").json(&request).await;
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("retry in"));
+}
+
+#[tokio::test]
+async fn test_failing_backend_does_not_open_other_backends_circuit() {
+    let failing_calls = Arc::new(AtomicUsize::new(0));
+    let failing_calls_clone = failing_calls.clone();
+    let failing = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let calls = failing_calls_clone.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::INTERNAL_SERVER_ERROR, "model crashed")
+            }
+        }),
+    ))
+    .await;
+    let healthy = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { chat_completion("Hi").to_string() }),
+    ))
+    .await;
+
+    let mut config = test_config(&healthy);
+    config.backend_urls = vec![healthy.clone(), failing.clone()];
+    config.circuit_failure_threshold = 2;
+    config.circuit_cooldown_secs = 60;
+    let server = test_server(&config);
+    let request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    });
+
+    // Keep sending until the failing backend has failed past the threshold
+    for _ in 0..20 {
+        if failing_calls.load(Ordering::SeqCst) >= 2 {
+            break;
+        }
+        server.post("/api/chat").json(&request).await;
+    }
+    assert_eq!(failing_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(CIRCUIT_STATE.with_label_values(&[&failing]).get(), 1);
+    assert_eq!(CIRCUIT_STATE.with_label_values(&[&healthy]).get(), 0);
+
+    // The healthy backend's circuit is still closed, so its share of requests succeeds
+    let mut succeeded = 0;
+    for _ in 0..6 {
+        if server.post("/api/chat").json(&request).await.status_code() == StatusCode::OK {
+            succeeded += 1;
+        }
+    }
+    assert!(succeeded > 0, "no request reached the healthy backend");
+    assert_eq!(failing_calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,88 @@
+//! A fake Mistral API built on wiremock, for exercising the real handlers end to end.
+
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::server::build_router;
+
+use super::{chat_completion, sse_body, stream_chunk, test_config};
+
+pub struct MockMistral {
+    server: MockServer,
+}
+
+impl MockMistral {
+    pub async fn start() -> Self {
+        MockMistral {
+            server: MockServer::start().await,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Answers non-streaming chat completions with `content`.
+    pub async fn chat_reply(&self, content: &str) -> &Self {
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({"stream": false})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_completion(content)))
+            .mount(&self.server)
+            .await;
+        self
+    }
+
+    /// Answers streaming chat completions with one chunk per entry of `contents`.
+    pub async fn chat_stream(&self, contents: &[&str]) -> &Self {
+        let chunks: Vec<Value> = contents.iter().map(|c| stream_chunk(c)).collect();
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({"stream": true})))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(sse_body(&chunks), "text/event-stream"),
+            )
+            .mount(&self.server)
+            .await;
+        self
+    }
+
+    /// Lists `ids` from the models endpoint.
+    pub async fn models(&self, ids: &[&str]) -> &Self {
+        let data: Vec<Value> = ids
+            .iter()
+            .map(|id| json!({"id": id, "object": "model", "created": 1234567890, "owned_by": "mistralai"}))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"object": "list", "data": data})),
+            )
+            .mount(&self.server)
+            .await;
+        self
+    }
+
+    /// Bodies of the requests the proxy sent to `request_path`.
+    pub async fn received_bodies(&self, request_path: &str) -> Vec<Value> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|request| request.url.path() == request_path)
+            .map(|request| request.body_json().unwrap())
+            .collect()
+    }
+
+    /// The proxy's router, with default settings, in front of this backend.
+    pub fn proxy(&self) -> TestServer {
+        let config = test_config("http://localhost:1");
+        let state = Arc::new(AppState::for_backend(&config, &self.url()).unwrap());
+        TestServer::new(build_router(&config, state)).unwrap()
+    }
+}
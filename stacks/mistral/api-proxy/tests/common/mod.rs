@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use axum::Router;
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use mistral_ollama_proxy::client::build_client;
+use mistral_ollama_proxy::config::Config;
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::server::build_router;
+
+pub mod mock_mistral;
+
+/// Serves `router` on an ephemeral local port and returns its base URL.
+pub async fn spawn_backend(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+pub fn test_config(mistral_url: &str) -> Config {
+    let mut config = Config::from_env();
+    config.mistral_url = mistral_url.to_string();
+    config
+}
+
+pub fn test_server(config: &Config) -> TestServer {
+    let state = Arc::new(AppState::new(build_client(config).unwrap(), config));
+    TestServer::new(build_router(config, state)).unwrap()
+}
+
+pub fn chat_completion(content: &str) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+    })
+}
+
+pub fn stream_chunk(content: &str) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion.chunk",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{
+            "index": 0,
+            "delta": {"role": "assistant", "content": content},
+            "finish_reason": null
+        }]
+    })
+}
+
+pub fn usage_chunk(prompt_tokens: i32, completion_tokens: i32) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion.chunk",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [],
+        "usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens
+        }
+    })
+}
+
+/// Renders `events` as a Mistral SSE body terminated by `[DONE]`.
+pub fn sse_body(events: &[Value]) -> String {
+    let mut body: String = events.iter().map(|e| format!("data: {e}\n\n")).collect();
+    body.push_str("data: [DONE]\n\n");
+    body
+}
+
+/// Parses the `data:` events of a proxied streaming response.
+pub fn parse_proxy_events(body: &str) -> Vec<Value> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect()
+}
+
+/// Serves the real proxy router on an ephemeral local port and returns its base URL.
+pub async fn spawn_proxy(config: &Config) -> String {
+    let state = Arc::new(AppState::new(build_client(config).unwrap(), config));
+    spawn_backend(build_router(config, state)).await
+}
@@ -0,0 +1,86 @@
+use axum::{
+    http::{HeaderName, HeaderValue},
+    routing::get,
+    Json, Router,
+};
+use flate2::read::GzDecoder;
+use serde_json::{json, Value};
+use std::io::Read;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+fn models_backend() -> Router {
+    Router::new().route(
+        "/v1/models",
+        get(|| async {
+            Json(json!({
+                "object": "list",
+                "data": [
+                    {"id": "mistral-7b", "object": "model", "created": 0, "owned_by": "local"},
+                    {"id": "mixtral-8x7b", "object": "model", "created": 0, "owned_by": "local"}
+                ]
+            }))
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_tags_response_is_gzip_compressed() {
+    let server = test_server(&test_config(&spawn_backend(models_backend()).await));
+
+    let response = server
+        .get("/api/tags")
+        .add_header(
+            HeaderName::from_static("accept-encoding"),
+            HeaderValue::from_static("gzip"),
+        )
+        .await;
+
+    assert_eq!(response.header("content-encoding"), "gzip");
+
+    let mut decompressed = String::new();
+    GzDecoder::new(response.as_bytes().as_ref())
+        .read_to_string(&mut decompressed)
+        .unwrap();
+    let body: Value = serde_json::from_str(&decompressed).unwrap();
+    assert_eq!(body["models"][0]["name"], "mistral:latest");
+    assert_eq!(body["models"][1]["name"], "mixtral:latest");
+}
+
+#[tokio::test]
+async fn test_tags_response_uncompressed_without_accept_encoding() {
+    let server = test_server(&test_config(&spawn_backend(models_backend()).await));
+
+    let response = server.get("/api/tags").await;
+
+    assert!(response.headers().get("content-encoding").is_none());
+    let body: Value = response.json();
+    assert_eq!(body["models"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_streaming_response_is_not_compressed() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        axum::routing::post(|| async { common::sse_body(&[common::stream_chunk("Hi")]) }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .add_header(
+            HeaderName::from_static("accept-encoding"),
+            HeaderValue::from_static("gzip"),
+        )
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await;
+
+    assert!(response.headers().get("content-encoding").is_none());
+    assert!(response.text().contains("\"done\":true"));
+}
@@ -0,0 +1,49 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config};
+use mistral_ollama_proxy::client::build_client;
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::metrics::{AVAILABLE_PERMITS, PERMIT_WAIT_SECONDS};
+use mistral_ollama_proxy::server::build_router;
+
+#[tokio::test]
+async fn test_saturated_permits_record_wait_time() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|Json(_): Json<Value>| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Json(chat_completion("Hi"))
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.max_concurrent_requests = 1;
+    let state = Arc::new(AppState::new(build_client(&config).unwrap(), &config));
+    assert_eq!(AVAILABLE_PERMITS.get(), 1);
+
+    // Served over a real socket so both requests are in flight at once
+    let proxy = spawn_backend(build_router(&config, state)).await;
+    let client = reqwest::Client::new();
+    let chat = || {
+        client
+            .post(format!("{proxy}/api/chat"))
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": "Hello"}],
+                "stream": false
+            }))
+            .send()
+    };
+    let (first, second) = tokio::join!(chat(), chat());
+    assert!(first.unwrap().status().is_success());
+    assert!(second.unwrap().status().is_success());
+
+    // One request waited for the other's backend call to finish
+    assert_eq!(PERMIT_WAIT_SECONDS.get_sample_count(), 2);
+    assert!(PERMIT_WAIT_SECONDS.get_sample_sum() >= 0.1);
+    assert_eq!(AVAILABLE_PERMITS.get(), 1);
+}
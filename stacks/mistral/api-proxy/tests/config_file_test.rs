@@ -0,0 +1,48 @@
+//! Kept in its own test binary because it sets process environment variables.
+
+use mistral_ollama_proxy::config::{Config, LogFormat};
+
+const SAMPLE_CONFIG: &str = r#"
+mistral_url = "http://file-backend:9000"
+bind_address = "127.0.0.1:12345"
+request_timeout_secs = 42
+log_format = "json"
+readiness_require_warmup = true
+warmup_models = ["mistral:latest", "mixtral:latest"]
+circuit_cooldown_secs = 10
+backend_api_prefix = "/openai/v1/"
+"#;
+
+#[test]
+fn test_file_values_with_env_overrides() {
+    let path = std::env::temp_dir().join(format!("proxy-config-{}.toml", std::process::id()));
+    std::fs::write(&path, SAMPLE_CONFIG).unwrap();
+
+    std::env::set_var("CIRCUIT_COOLDOWN_SECS", "99");
+    let config = Config::from_file(path.to_str().unwrap());
+    std::env::remove_var("CIRCUIT_COOLDOWN_SECS");
+
+    assert_eq!(config.mistral_url, "http://file-backend:9000");
+    assert_eq!(config.bind_address, "127.0.0.1:12345");
+    assert_eq!(config.request_timeout_secs, 42);
+    assert_eq!(config.log_format, LogFormat::Json);
+    assert!(config.readiness_require_warmup);
+    assert_eq!(
+        config.warmup_models,
+        vec!["mistral:latest", "mixtral:latest"]
+    );
+    // The environment wins over the file
+    assert_eq!(config.circuit_cooldown_secs, 99);
+    // The prefix is normalized
+    assert_eq!(config.backend_api_prefix, "/openai/v1");
+    // Settings absent from both keep their defaults
+    assert_eq!(config.channel_buffer_size, 100);
+
+    // CONFIG_FILE selects the file for from_env
+    std::env::set_var("CONFIG_FILE", &path);
+    let config = Config::from_env();
+    std::env::remove_var("CONFIG_FILE");
+    assert_eq!(config.mistral_url, "http://file-backend:9000");
+
+    std::fs::remove_file(&path).unwrap();
+}
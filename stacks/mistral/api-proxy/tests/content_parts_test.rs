@@ -0,0 +1,157 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, test_config, test_server,
+};
+
+fn completion_with_content(content: Value) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+    })
+}
+
+async fn chat_with_backend_content(content: Value) -> Value {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(_): Json<Value>| {
+            let content = content.clone();
+            async move { Json(completion_with_content(content)) }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .json()
+}
+
+#[tokio::test]
+async fn test_string_content() {
+    let body = chat_with_backend_content(json!("Hello there")).await;
+    assert_eq!(body["message"]["content"], "Hello there");
+}
+
+#[tokio::test]
+async fn test_content_parts_flattened_to_text() {
+    let body = chat_with_backend_content(json!([
+        {"type": "text", "text": "Hello "},
+        {"type": "image_url", "image_url": "https://example.com/cat.png"},
+        {"type": "text", "text": "there"}
+    ]))
+    .await;
+    assert_eq!(body["message"]["content"], "Hello there");
+}
+
+#[tokio::test]
+async fn test_streamed_content_parts_flattened_to_text() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async {
+            sse_body(&[json!({
+                "id": "cmpl-test",
+                "object": "chat.completion.chunk",
+                "created": 1234567890,
+                "model": "mistral-7b",
+                "choices": [{
+                    "index": 0,
+                    "delta": {"role": "assistant", "content": [{"type": "text", "text": "Hi"}]},
+                    "finish_reason": null
+                }]
+            })])
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let events = parse_proxy_events(
+        &server
+            .post("/api/chat")
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": "Hello"}],
+                "stream": true
+            }))
+            .await
+            .text(),
+    );
+    assert_eq!(events[0]["message"]["content"], "Hi");
+}
+
+/// Sends a chat whose user message has `content` as given, returning the proxy's status and
+/// the messages the backend received.
+async fn chat_with_request_content(content: Value) -> (StatusCode, Option<Value>) {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body["messages"].clone());
+                Json(chat_completion("Hello"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": content}],
+            "stream": false
+        }))
+        .await;
+    let sent = captured.lock().unwrap().take();
+    (response.status_code(), sent)
+}
+
+#[tokio::test]
+async fn test_request_string_content_forwarded() {
+    let (status, sent) = chat_with_request_content(json!("Summarize this")).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sent.unwrap()[0]["content"], "Summarize this");
+}
+
+#[tokio::test]
+async fn test_request_text_blocks_joined_into_string() {
+    let (status, sent) = chat_with_request_content(json!([
+        {"type": "text", "text": "Summarize "},
+        {"type": "text", "text": "this"}
+    ]))
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sent.unwrap()[0]["content"], "Summarize this");
+}
+
+#[tokio::test]
+async fn test_request_non_text_blocks_rejected() {
+    let (status, sent) = chat_with_request_content(json!([
+        {"type": "text", "text": "What is this?"},
+        {"type": "image", "source": {"type": "base64", "data": "iVBORw0KGgo"}}
+    ]))
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(sent.is_none());
+}
@@ -0,0 +1,141 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server,
+};
+
+#[tokio::test]
+async fn test_generate_context_round_trip() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                let mut captured = captured.lock().unwrap();
+                captured.push(body);
+                Json(chat_completion(&format!("reply {}", captured.len())))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let first: Value = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "My name is Sam", "stream": false}))
+        .await
+        .json();
+    let context = first["context"].clone();
+    assert!(context.is_array());
+
+    let second: Value = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "What is my name?",
+            "context": context,
+            "stream": false
+        }))
+        .await
+        .json();
+    assert_eq!(second["response"], "reply 2");
+    assert_ne!(second["context"], first["context"]);
+
+    let captured = captured.lock().unwrap();
+    let messages = captured[1]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0]["content"], "My name is Sam");
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["content"], "reply 1");
+    assert_eq!(messages[2]["content"], "What is my name?");
+}
+
+#[tokio::test]
+async fn test_generate_with_unknown_context_starts_fresh() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                captured.lock().unwrap().push(body);
+                Json(chat_completion("ok"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hello",
+            "context": [1, 2, 3],
+            "stream": false
+        }))
+        .await;
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured[0]["messages"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_streamed_generate_returns_context_on_done_chunk() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                let streamed = body["stream"] == true;
+                captured.lock().unwrap().push(body);
+                if streamed {
+                    sse_body(&[stream_chunk("Hello"), stream_chunk(", Sam")])
+                } else {
+                    chat_completion("ok").to_string()
+                }
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let events = parse_proxy_events(
+        &server
+            .post("/api/generate")
+            .json(&json!({"model": "mistral:latest", "prompt": "My name is Sam", "stream": true}))
+            .await
+            .text(),
+    );
+
+    let (done, chunks) = events.split_last().unwrap();
+    assert_eq!(done["done"], true);
+    assert!(done["context"].is_array());
+    assert!(chunks.iter().all(|chunk| chunk.get("context").is_none()));
+
+    server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "What is my name?",
+            "context": done["context"],
+            "stream": false
+        }))
+        .await;
+
+    let captured = captured.lock().unwrap();
+    let messages = captured[1]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["content"], "Hello, Sam");
+}
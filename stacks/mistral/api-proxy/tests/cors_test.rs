@@ -0,0 +1,86 @@
+use axum::http::{header, HeaderValue};
+use axum::Router;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+async fn cors_config(
+    origins: &[&str],
+    allow_credentials: bool,
+) -> mistral_ollama_proxy::config::Config {
+    let mut config = test_config(&spawn_backend(Router::new()).await);
+    config.cors_allowed_origins = origins.iter().map(|o| o.to_string()).collect();
+    config.cors_allow_credentials = allow_credentials;
+    config
+}
+
+#[tokio::test]
+async fn test_wildcard_origin_allows_any() {
+    let server = test_server(&cors_config(&["*"], false).await);
+
+    let response = server
+        .get("/")
+        .add_header(
+            header::ORIGIN,
+            HeaderValue::from_static("https://anywhere.example"),
+        )
+        .await;
+
+    assert_eq!(
+        response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        HeaderValue::from_static("*")
+    );
+}
+
+#[tokio::test]
+async fn test_wildcard_origin_with_credentials_mirrors_origin() {
+    let server = test_server(&cors_config(&["*"], true).await);
+
+    let response = server
+        .get("/")
+        .add_header(
+            header::ORIGIN,
+            HeaderValue::from_static("https://anywhere.example"),
+        )
+        .await;
+
+    assert_eq!(
+        response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        HeaderValue::from_static("https://anywhere.example")
+    );
+    assert_eq!(
+        response.header(header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        HeaderValue::from_static("true")
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_origin_is_skipped() {
+    let server =
+        test_server(&cors_config(&["http://bad\norigin", "https://good.example"], false).await);
+
+    let response = server
+        .get("/")
+        .add_header(
+            header::ORIGIN,
+            HeaderValue::from_static("https://good.example"),
+        )
+        .await;
+    assert_eq!(
+        response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        HeaderValue::from_static("https://good.example")
+    );
+
+    let response = server
+        .get("/")
+        .add_header(
+            header::ORIGIN,
+            HeaderValue::from_static("https://other.example"),
+        )
+        .await;
+    assert!(response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
@@ -0,0 +1,71 @@
+use axum::http::HeaderValue;
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::json;
+use std::time::Duration;
+
+use mistral_ollama_proxy::metrics::HTTP_REQUESTS_TOTAL;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+async fn slow_backend(delay: Duration) -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move {
+            tokio::time::sleep(delay).await;
+            Json(chat_completion("Slow reply"))
+        }),
+    ))
+    .await
+}
+
+fn chat_request() -> serde_json::Value {
+    json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    })
+}
+
+#[tokio::test]
+async fn test_deadline_exceeded_returns_gateway_timeout() {
+    let backend = slow_backend(Duration::from_secs(2)).await;
+    let server = test_server(&test_config(&backend));
+    let deadline_errors =
+        HTTP_REQUESTS_TOTAL.with_label_values(&["chat", "mistral:latest", "error", "deadline"]);
+    let errors_before = deadline_errors.get();
+
+    let response = server
+        .post("/api/chat")
+        .add_header(
+            "x-deadline-ms".parse().unwrap(),
+            HeaderValue::from_static("100"),
+        )
+        .json(&chat_request())
+        .await;
+
+    response.assert_status(StatusCode::GATEWAY_TIMEOUT);
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("100ms"));
+    assert!(deadline_errors.get() >= errors_before + 1.0);
+}
+
+#[tokio::test]
+async fn test_deadline_met_returns_response() {
+    let backend = slow_backend(Duration::from_millis(50)).await;
+    let server = test_server(&test_config(&backend));
+
+    let response = server
+        .post("/api/chat")
+        .add_header(
+            "x-deadline-ms".parse().unwrap(),
+            HeaderValue::from_static("5000"),
+        )
+        .json(&chat_request())
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["message"]["content"], "Slow reply");
+}
@@ -0,0 +1,47 @@
+use axum::http::HeaderValue;
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{test_config, test_server};
+use mistral_ollama_proxy::handlers::chat::DRY_RUN_HEADER;
+
+/// Returns the model a chat request for `model` would be sent to the backend with.
+async fn translated_model(model: Option<&str>) -> Value {
+    let mut config = test_config("http://127.0.0.1:9");
+    config.default_model = Some("mixtral:latest".to_string());
+    let server = test_server(&config);
+
+    let mut request = json!({
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    });
+    if let Some(model) = model {
+        request["model"] = json!(model);
+    }
+
+    let body: Value = server
+        .post("/api/chat")
+        .add_header(DRY_RUN_HEADER.clone(), HeaderValue::from_static("true"))
+        .json(&request)
+        .await
+        .json();
+    body["model"].clone()
+}
+
+#[tokio::test]
+async fn test_empty_model_uses_default() {
+    assert_eq!(translated_model(Some("")).await, "mixtral-8x7b");
+    assert_eq!(translated_model(None).await, "mixtral-8x7b");
+}
+
+#[tokio::test]
+async fn test_default_alias_uses_default() {
+    assert_eq!(translated_model(Some("default")).await, "mixtral-8x7b");
+}
+
+#[tokio::test]
+async fn test_explicit_model_left_untouched() {
+    assert_eq!(translated_model(Some("mistral:latest")).await, "mistral-7b");
+    assert_eq!(translated_model(Some("codestral")).await, "codestral");
+}
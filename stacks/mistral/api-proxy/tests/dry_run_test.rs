@@ -0,0 +1,108 @@
+use axum::http::HeaderValue;
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+use mistral_ollama_proxy::handlers::chat::DRY_RUN_HEADER;
+use mistral_ollama_proxy::metrics::GENERATE_DURATION_SECONDS;
+
+/// Returns a server whose backend counts the requests it receives.
+async fn server_with_counting_backend() -> (axum_test::TestServer, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let backend_calls = calls.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(_): Json<Value>| {
+            backend_calls.fetch_add(1, Ordering::SeqCst);
+            async { chat_completion("Hi").to_string() }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+    (server, calls)
+}
+
+#[tokio::test]
+async fn test_dry_run_header_returns_translated_chat_request() {
+    let (server, calls) = server_with_counting_backend().await;
+
+    let response = server
+        .post("/api/chat")
+        .add_header(DRY_RUN_HEADER.clone(), HeaderValue::from_static("true"))
+        .json(&json!({
+            "model": "dry-run-chat:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false,
+            "options": {"temperature": 0.5, "num_predict": 64, "stop": "\n"}
+        }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>(),
+        json!({
+            "model": "dry-run-chat:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false,
+            "temperature": 0.5,
+            "top_p": null,
+            "max_tokens": 64,
+            "random_seed": null,
+            "stop": ["\n"]
+        })
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        GENERATE_DURATION_SECONDS
+            .with_label_values(&["dry-run-chat:latest"])
+            .get_sample_count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_dry_run_query_returns_translated_generate_request() {
+    let (server, calls) = server_with_counting_backend().await;
+
+    let response = server
+        .post("/api/generate")
+        .add_query_param("dry_run", "1")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Why is the sky blue?",
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["model"], "mistral-7b");
+    assert_eq!(
+        body["messages"],
+        json!([{"role": "user", "content": "Why is the sky blue?"}])
+    );
+    assert_eq!(body["stream"], true);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_without_dry_run_request_reaches_backend() {
+    let (server, calls) = server_with_counting_backend().await;
+
+    let response = server
+        .post("/api/chat")
+        .add_header(DRY_RUN_HEADER.clone(), HeaderValue::from_static("false"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["message"]["content"], "Hi");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,62 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server, usage_chunk,
+};
+
+async fn generate(echo: Option<bool>, stream: bool) -> axum_test::TestResponse {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| async move {
+            if body["stream"] == true {
+                sse_body(&[stream_chunk(" world"), stream_chunk("!"), usage_chunk(3, 2)])
+            } else {
+                chat_completion(" world").to_string()
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let mut request = json!({
+        "model": "mistral:latest",
+        "prompt": "Hello",
+        "stream": stream
+    });
+    if let Some(echo) = echo {
+        request["echo"] = json!(echo);
+    }
+    server.post("/api/generate").json(&request).await
+}
+
+#[tokio::test]
+async fn test_echo_prepends_prompt() {
+    let body: Value = generate(Some(true), false).await.json();
+
+    assert_eq!(body["response"], "Hello world");
+    // Usage still reflects only the generated tokens
+    assert_eq!(body["eval_count"], 5);
+}
+
+#[tokio::test]
+async fn test_echo_false_returns_completion_only() {
+    let body: Value = generate(Some(false), false).await.json();
+    assert_eq!(body["response"], " world");
+
+    let body: Value = generate(None, false).await.json();
+    assert_eq!(body["response"], " world");
+}
+
+#[tokio::test]
+async fn test_echo_prepends_prompt_to_first_stream_chunk() {
+    let events = parse_proxy_events(&generate(Some(true), true).await.text());
+
+    assert_eq!(events[0]["response"], "Hello world");
+    assert_eq!(events[1]["response"], "!");
+    let done = events.last().unwrap();
+    assert_eq!(done["done"], true);
+    assert_eq!(done["eval_count"], 2);
+}
@@ -0,0 +1,70 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+async fn chat_with_backend_choices(choices: Value) -> axum_test::TestResponse {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let choices = choices.clone();
+            async move {
+                Json(json!({
+                    "id": "cmpl-test",
+                    "object": "chat.completion",
+                    "created": 1234567890,
+                    "model": "mistral-7b",
+                    "choices": choices
+                }))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+}
+
+#[tokio::test]
+async fn test_empty_choices_returns_bad_gateway() {
+    let response = chat_with_backend_choices(json!([])).await;
+
+    response.assert_status(StatusCode::BAD_GATEWAY);
+    let body: Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("no choices"));
+}
+
+#[tokio::test]
+async fn test_content_filter_returns_unprocessable_entity() {
+    let response = chat_with_backend_choices(json!([{
+        "index": 0,
+        "finish_reason": "content_filter"
+    }]))
+    .await;
+
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("content filter"));
+}
+
+#[tokio::test]
+async fn test_delta_only_choice_returns_content() {
+    let response = chat_with_backend_choices(json!([{
+        "index": 0,
+        "delta": {"role": "assistant", "content": "Hi from delta"},
+        "finish_reason": "stop"
+    }]))
+    .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["message"]["content"], "Hi from delta");
+}
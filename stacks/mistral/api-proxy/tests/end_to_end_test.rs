@@ -0,0 +1,79 @@
+use serde_json::{json, Value};
+
+mod common;
+
+use common::mock_mistral::MockMistral;
+use common::parse_proxy_events;
+
+#[tokio::test]
+async fn test_sync_chat_through_mock_backend() {
+    let backend = MockMistral::start().await;
+    backend.chat_reply("Hello from Mistral").await;
+    let proxy = backend.proxy();
+
+    let response = proxy
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false,
+            "options": {"temperature": 0.5}
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["model"], "mistral-7b");
+    assert_eq!(body["message"]["role"], "assistant");
+    assert_eq!(body["message"]["content"], "Hello from Mistral");
+    assert_eq!(body["done"], true);
+    assert_eq!(body["prompt_eval_count"], 10);
+    assert_eq!(body["eval_count"], 5);
+
+    let sent = backend.received_bodies("/v1/chat/completions").await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["model"], "mistral-7b");
+    assert_eq!(sent[0]["messages"][0]["content"], "Hello");
+    assert_eq!(sent[0]["temperature"], 0.5);
+}
+
+#[tokio::test]
+async fn test_streaming_chat_through_mock_backend() {
+    let backend = MockMistral::start().await;
+    backend.chat_stream(&["Hello", " there"]).await;
+    let proxy = backend.proxy();
+
+    let response = proxy
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let events = parse_proxy_events(&response.text());
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["message"]["content"], "Hello");
+    assert_eq!(events[1]["message"]["content"], " there");
+    assert_eq!(events[2]["done"], true);
+    assert_eq!(events[2]["model"], "mistral-7b");
+}
+
+#[tokio::test]
+async fn test_model_listing_through_mock_backend() {
+    let backend = MockMistral::start().await;
+    backend.models(&["mistral-7b", "codestral"]).await;
+    let proxy = backend.proxy();
+
+    let body: Value = proxy.get("/api/tags").await.json();
+
+    let names: Vec<&str> = body["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["mistral:latest", "codestral:latest"]);
+}
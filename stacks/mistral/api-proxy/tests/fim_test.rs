@@ -0,0 +1,91 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+/// Records the path and body of every backend request.
+type Captured = Arc<Mutex<Vec<(&'static str, Value)>>>;
+
+fn recording_backend(captured: Captured) -> Router {
+    let chat_captured = captured.clone();
+    let fim_captured = captured;
+
+    Router::new()
+        .route(
+            "/v1/chat/completions",
+            post(move |Json(body): Json<Value>| {
+                let captured = chat_captured.clone();
+                async move {
+                    captured.lock().unwrap().push(("chat", body));
+                    Json(chat_completion("from chat"))
+                }
+            }),
+        )
+        .route(
+            "/v1/fim/completions",
+            post(move |Json(body): Json<Value>| {
+                let captured = fim_captured.clone();
+                async move {
+                    captured.lock().unwrap().push(("fim", body));
+                    Json(chat_completion("from fim"))
+                }
+            }),
+        )
+}
+
+#[tokio::test]
+async fn test_generate_with_suffix_uses_fim_endpoint() {
+    let captured: Captured = Arc::default();
+    let url = spawn_backend(recording_backend(captured.clone())).await;
+    let server = test_server(&test_config(&url));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "codestral:latest",
+            "prompt": "def add(a, b):\n    ",
+            "suffix": "\n\nprint(add(1, 2))",
+            "stream": false
+        }))
+        .await;
+
+    let body: Value = response.json();
+    assert_eq!(body["response"], "from fim");
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 1);
+    let (endpoint, sent) = &captured[0];
+    assert_eq!(*endpoint, "fim");
+    assert_eq!(sent["prompt"], "def add(a, b):\n    ");
+    assert_eq!(sent["suffix"], "\n\nprint(add(1, 2))");
+    assert!(sent.get("messages").is_none());
+}
+
+#[tokio::test]
+async fn test_generate_without_suffix_uses_chat_endpoint() {
+    let captured: Captured = Arc::default();
+    let url = spawn_backend(recording_backend(captured.clone())).await;
+    let server = test_server(&test_config(&url));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hello",
+            "stream": false
+        }))
+        .await;
+
+    let body: Value = response.json();
+    assert_eq!(body["response"], "from chat");
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 1);
+    let (endpoint, sent) = &captured[0];
+    assert_eq!(*endpoint, "chat");
+    assert_eq!(sent["messages"][0]["content"], "Hello");
+    assert!(sent.get("suffix").is_none());
+}
@@ -0,0 +1,122 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue},
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+use mistral_ollama_proxy::forward_headers::allowlist;
+
+type Seen = Arc<Mutex<Vec<HeaderMap>>>;
+
+/// A backend recording the headers of every request it receives.
+async fn recording_backend(seen: Seen) -> String {
+    let completions_seen = seen.clone();
+    spawn_backend(
+        Router::new()
+            .route(
+                "/v1/chat/completions",
+                post(
+                    move |headers: HeaderMap, Json(body): Json<Value>| async move {
+                        completions_seen.lock().unwrap().push(headers);
+                        if body["stream"] == true {
+                            sse_body(&[stream_chunk("Hi")])
+                        } else {
+                            chat_completion("Hi").to_string()
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/v1/models",
+                get(move |headers: HeaderMap| async move {
+                    seen.lock().unwrap().push(headers);
+                    Json(json!({"object": "list", "data": []}))
+                }),
+            ),
+    )
+    .await
+}
+
+fn header(name: &'static str, value: &'static str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static(name),
+        HeaderValue::from_static(value),
+    )
+}
+
+#[tokio::test]
+async fn test_allowlisted_headers_forwarded_and_others_dropped() {
+    let seen = Seen::default();
+    let mut config = test_config(&recording_backend(seen.clone()).await);
+    config.forward_headers = vec!["x-model-version".to_string(), "x-tenant-id".to_string()];
+    let server = test_server(&config);
+
+    let requests = [
+        server.post("/api/chat").json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        })),
+        server.post("/api/generate").json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hello",
+            "stream": true
+        })),
+        server.get("/api/tags"),
+    ];
+    for request in requests {
+        let (model_version, model_version_value) = header("x-model-version", "2024-05");
+        let (tenant, tenant_value) = header("x-tenant-id", "acme");
+        let (secret, secret_value) = header("x-internal-secret", "hunter2");
+        request
+            .add_header(model_version, model_version_value)
+            .add_header(tenant, tenant_value)
+            .add_header(secret, secret_value)
+            .await
+            .assert_status_ok();
+    }
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 3);
+    for headers in seen.iter() {
+        assert_eq!(headers["x-model-version"], "2024-05");
+        assert_eq!(headers["x-tenant-id"], "acme");
+        assert!(headers.get("x-internal-secret").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_nothing_forwarded_by_default() {
+    let seen = Seen::default();
+    let server = test_server(&test_config(&recording_backend(seen.clone()).await));
+
+    let (tenant, tenant_value) = header("x-tenant-id", "acme");
+    server
+        .get("/api/tags")
+        .add_header(tenant, tenant_value)
+        .await
+        .assert_status_ok();
+
+    assert!(seen.lock().unwrap()[0].get("x-tenant-id").is_none());
+}
+
+#[test]
+fn test_allowlist_never_includes_hop_by_hop_headers() {
+    let names = [
+        "x-tenant-id",
+        "host",
+        "content-length",
+        "connection",
+        "bad header",
+    ]
+    .map(str::to_string);
+
+    let allowed = allowlist(&names);
+    assert_eq!(allowed, vec![HeaderName::from_static("x-tenant-id")]);
+}
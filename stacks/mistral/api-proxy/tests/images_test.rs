@@ -0,0 +1,73 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAAB";
+
+#[tokio::test]
+async fn test_generate_images_sent_as_multimodal_message() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(chat_completion("A single pixel"))
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.multimodal_models = vec!["pixtral:latest".to_string()];
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "pixtral:latest",
+            "prompt": "What is in this picture?",
+            "images": [PNG, "/9j/4AAQSkZJRg"],
+            "stream": false
+        }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["response"], "A single pixel");
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert_eq!(
+        sent["messages"][0]["content"],
+        json!([
+            {"type": "text", "text": "What is in this picture?"},
+            {"type": "image_url", "image_url": format!("data:image/png;base64,{PNG}")},
+            {"type": "image_url", "image_url": "data:image/jpeg;base64,/9j/4AAQSkZJRg"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_generate_images_rejected_for_text_only_model() {
+    let server = test_server(&test_config("http://localhost:0"));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "What is in this picture?",
+            "images": [PNG],
+            "stream": false
+        }))
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "model 'mistral:latest' does not support images"
+    );
+}
@@ -0,0 +1,132 @@
+use axum::{
+    http::{HeaderValue, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+use mistral_ollama_proxy::handlers::chat::INCLUDE_RAW_HEADER;
+
+/// Sends a chat with `X-Include-Raw: true` to a proxy with `DEBUG_ALLOW_RAW` set as given.
+async fn chat_with_raw_header(allow_raw: bool) -> Value {
+    let mut completion = chat_completion("Hello there");
+    completion["system_fingerprint"] = json!("fp_internal");
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move { Json(completion) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.debug_allow_raw = allow_raw;
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/chat")
+        .add_header(INCLUDE_RAW_HEADER.clone(), HeaderValue::from_static("true"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": false
+        }))
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_raw_response_included_when_allowed() {
+    let body = chat_with_raw_header(true).await;
+
+    assert_eq!(body["message"]["content"], "Hello there");
+    assert_eq!(body["_raw"]["system_fingerprint"], "fp_internal");
+    assert_eq!(
+        body["_raw"]["choices"][0]["message"]["content"],
+        "Hello there"
+    );
+}
+
+#[tokio::test]
+async fn test_raw_response_omitted_unless_allowed() {
+    let body = chat_with_raw_header(false).await;
+
+    assert_eq!(body["message"]["content"], "Hello there");
+    assert!(body.get("_raw").is_none());
+}
+
+#[tokio::test]
+async fn test_raw_response_omitted_without_header() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hello there")) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.debug_allow_raw = true;
+    let server = test_server(&config);
+
+    let body: Value = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "stream": false}))
+        .await
+        .json();
+
+    assert_eq!(body["response"], "Hello there");
+    assert!(body.get("_raw").is_none());
+}
+
+#[tokio::test]
+async fn test_malformed_backend_reply_with_raw_is_bad_gateway() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(json!({"id": "cmpl-test", "choices": "none"})) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.debug_allow_raw = true;
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/chat")
+        .add_header(INCLUDE_RAW_HEADER.clone(), HeaderValue::from_static("true"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": false
+        }))
+        .await;
+
+    response.assert_status(StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn test_cached_reply_returned_without_raw() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hello there")) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.debug_allow_raw = true;
+    config.response_cache_ttl_secs = 60;
+    let server = test_server(&config);
+    let request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hi"}],
+        "options": {"temperature": 0},
+        "stream": false
+    });
+
+    let mut bodies = Vec::new();
+    for _ in 0..2 {
+        let body: Value = server
+            .post("/api/chat")
+            .add_header(INCLUDE_RAW_HEADER.clone(), HeaderValue::from_static("true"))
+            .json(&request)
+            .await
+            .json();
+        bodies.push(body);
+    }
+
+    assert!(bodies[0].get("_raw").is_some());
+    assert_eq!(bodies[1]["message"]["content"], "Hello there");
+    assert!(bodies[1].get("_raw").is_none());
+}
@@ -0,0 +1,67 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use serde_json::Value;
+
+mod common;
+
+use common::{test_config, test_server};
+
+async fn post_raw(path: &str, body: &'static str) -> axum_test::TestResponse {
+    let server = test_server(&test_config("http://127.0.0.1:9"));
+    server
+        .post(path)
+        .add_header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )
+        .bytes(body.into())
+        .await
+}
+
+#[tokio::test]
+async fn test_malformed_json_returns_structured_400() {
+    for path in ["/api/chat", "/api/generate", "/api/delete"] {
+        let response = if path == "/api/delete" {
+            test_server(&test_config("http://127.0.0.1:9"))
+                .delete(path)
+                .bytes("{\"model\": ".into())
+                .await
+        } else {
+            post_raw(path, "{\"model\": \"mistral:latest\", ").await
+        };
+
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        let error = body["error"].as_str().unwrap();
+        assert!(error.starts_with("JSON parsing error: "), "{path}: {error}");
+    }
+}
+
+#[tokio::test]
+async fn test_wrong_field_type_names_the_field() {
+    let response = post_raw(
+        "/api/chat",
+        r#"{"model": "mistral:latest", "messages": [{"role": "user", "content": 42}]}"#,
+    )
+    .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    let error = body["error"].as_str().unwrap();
+    assert!(
+        error.starts_with("JSON parsing error: messages[0].content: invalid type"),
+        "{error}"
+    );
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_missing_field_returns_400() {
+    let response = post_raw("/api/generate", r#"{"model": "mistral:latest"}"#).await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `prompt`"));
+}
@@ -0,0 +1,64 @@
+use axum::body::Body;
+use axum::{routing::post, Router};
+use futures::StreamExt;
+use std::time::Duration;
+
+mod common;
+
+use common::{parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+/// A backend that sends headers immediately but takes a while to produce its first token.
+async fn slow_prefill_backend(prefill: Duration) -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move {
+            let body = sse_body(&[stream_chunk("Hi")]);
+            let delayed = futures::stream::once(async move {
+                tokio::time::sleep(prefill).await;
+                Ok::<_, std::io::Error>(body)
+            });
+            Body::from_stream(delayed.boxed())
+        }),
+    ))
+    .await
+}
+
+fn chat_request() -> serde_json::Value {
+    serde_json::json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": true
+    })
+}
+
+#[tokio::test]
+async fn test_keepalive_comments_sent_before_first_token() {
+    let backend = slow_prefill_backend(Duration::from_millis(450)).await;
+    let mut config = test_config(&backend);
+    config.stream_keepalive_secs = 0.1;
+    let server = test_server(&config);
+
+    let body = server.post("/api/chat").json(&chat_request()).await.text();
+
+    let keepalives = body.matches(": keepalive\n\n").count();
+    assert!(keepalives >= 2, "expected heartbeats, got body: {body:?}");
+    // Heartbeats only precede the first real event
+    let first_data = body.find("data: ").unwrap();
+    assert_eq!(body[first_data..].matches(": keepalive").count(), 0);
+
+    let events = parse_proxy_events(&body);
+    assert_eq!(events[0]["message"]["content"], "Hi");
+    assert_eq!(events.last().unwrap()["done"], true);
+}
+
+#[tokio::test]
+async fn test_keepalive_disabled() {
+    let backend = slow_prefill_backend(Duration::from_millis(250)).await;
+    let mut config = test_config(&backend);
+    config.stream_keepalive_secs = 0.0;
+    let server = test_server(&config);
+
+    let body = server.post("/api/chat").json(&chat_request()).await.text();
+
+    assert!(!body.contains(": keepalive"));
+}
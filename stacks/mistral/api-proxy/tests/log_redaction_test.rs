@@ -0,0 +1,95 @@
+use axum::{http::StatusCode, routing::post, Router};
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{spawn_backend, stream_chunk, test_config, test_server};
+
+const SECRET: &str = "my-secret-prompt";
+
+/// Collects everything logged while it is the default subscriber.
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLogs {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+/// Sends a chat whose backend rejects it quoting the prompt, then a stream with a corrupt line
+/// quoting it, returning what was logged.
+async fn logs_for_leaky_backend(redact: bool) -> String {
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|body: String| async move {
+            if body.contains("\"stream\":true") {
+                let events = format!(
+                    "data: {{not json {SECRET}}}\n\ndata: {}\n\ndata: [DONE]\n\n",
+                    stream_chunk("ok")
+                );
+                (StatusCode::OK, events)
+            } else {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("prompt '{SECRET}' exceeds the context window"),
+                )
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.log_redact_prompts = redact;
+    let server = test_server(&config);
+
+    for stream in [false, true] {
+        server
+            .post("/api/chat")
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": SECRET}],
+                "stream": stream
+            }))
+            .await;
+    }
+
+    logs.text()
+}
+
+#[tokio::test]
+async fn test_prompt_content_logged_without_redaction() {
+    let logs = logs_for_leaky_backend(false).await;
+    assert!(logs.contains(SECRET), "{logs}");
+}
+
+#[tokio::test]
+async fn test_prompt_content_redacted_from_logs() {
+    let logs = logs_for_leaky_backend(true).await;
+    assert!(!logs.contains(SECRET), "{logs}");
+    assert!(
+        logs.contains("Mistral API error (400 Bad Request)"),
+        "{logs}"
+    );
+    assert!(logs.contains("bytes redacted"), "{logs}");
+}
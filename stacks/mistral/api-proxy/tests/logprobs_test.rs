@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{parse_proxy_events, spawn_backend, sse_body, test_config, test_server};
+
+fn token_logprobs(token: &str) -> Value {
+    json!({
+        "content": [{
+            "token": token,
+            "logprob": -0.25,
+            "top_logprobs": [{"token": token, "logprob": -0.25}, {"token": "Hey", "logprob": -1.5}]
+        }]
+    })
+}
+
+fn completion_with_logprobs() -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "Hi"},
+            "finish_reason": "stop",
+            "logprobs": token_logprobs("Hi")
+        }],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 1, "total_tokens": 11}
+    })
+}
+
+fn chunk_with_logprobs(content: &str) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "chat.completion.chunk",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{
+            "index": 0,
+            "delta": {"role": "assistant", "content": content},
+            "finish_reason": null,
+            "logprobs": token_logprobs(content)
+        }]
+    })
+}
+
+async fn chat(stream: bool, options: Value) -> (axum_test::TestResponse, Value) {
+    let sent = Arc::new(Mutex::new(Value::Null));
+    let recorded = sent.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| async move {
+            let stream = body["stream"] == true;
+            *recorded.lock().unwrap() = body;
+            if stream {
+                sse_body(&[chunk_with_logprobs("Hi"), chunk_with_logprobs("!")])
+            } else {
+                completion_with_logprobs().to_string()
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": stream,
+            "options": options
+        }))
+        .await;
+    let sent = sent.lock().unwrap().clone();
+    (response, sent)
+}
+
+#[tokio::test]
+async fn test_logprobs_options_sent_to_backend() {
+    let (_, sent) = chat(false, json!({"logprobs": true, "top_logprobs": 2})).await;
+    assert_eq!(sent["logprobs"], true);
+    assert_eq!(sent["top_logprobs"], 2);
+
+    let (_, sent) = chat(false, json!({})).await;
+    assert!(sent.get("logprobs").is_none());
+    assert!(sent.get("top_logprobs").is_none());
+}
+
+#[tokio::test]
+async fn test_logprobs_returned_in_chat_response() {
+    let (response, _) = chat(false, json!({"logprobs": true})).await;
+
+    let body: Value = response.json();
+    assert_eq!(body["message"]["content"], "Hi");
+    assert_eq!(body["logprobs"], token_logprobs("Hi"));
+}
+
+#[tokio::test]
+async fn test_logprobs_preserved_in_stream_chunks() {
+    let (response, _) = chat(true, json!({"logprobs": true})).await;
+
+    let events = parse_proxy_events(&response.text());
+    assert_eq!(events[0]["logprobs"], token_logprobs("Hi"));
+    assert_eq!(events[1]["logprobs"], token_logprobs("!"));
+    assert!(events.last().unwrap().get("logprobs").is_none());
+}
@@ -0,0 +1,69 @@
+//! Kept in its own test binary because it asserts an exact change in a global counter.
+
+use axum::{routing::post, Router};
+use serde_json::json;
+
+use mistral_ollama_proxy::metrics::STREAMS_TRUNCATED_TOTAL;
+
+mod common;
+
+use common::{parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+#[tokio::test]
+async fn test_stream_cut_off_after_max_chunks() {
+    let chunks: Vec<_> = (0..10).map(|i| stream_chunk(&format!("t{i} "))).collect();
+    let body = sse_body(&chunks);
+    let backend = spawn_backend(
+        Router::new().route("/v1/chat/completions", post(move || async move { body })),
+    )
+    .await;
+    let mut config = test_config(&backend);
+    config.max_stream_chunks = 3;
+    let server = test_server(&config);
+    let truncated = STREAMS_TRUNCATED_TOTAL.with_label_values(&["chat"]);
+    let truncated_before = truncated.get();
+
+    let body = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Count"}],
+            "stream": true
+        }))
+        .await
+        .text();
+
+    let events = parse_proxy_events(&body);
+    assert_eq!(events.len(), 4, "events: {events:?}");
+    let content: String = events[..3]
+        .iter()
+        .map(|e| e["message"]["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(content, "t0 t1 t2 ");
+    assert_eq!(events[3]["done"], true);
+    assert_eq!(events[3]["done_reason"], "length");
+    assert_eq!(truncated.get(), truncated_before + 1.0);
+}
+
+#[tokio::test]
+async fn test_stream_under_limit_not_truncated() {
+    let body = sse_body(&[stream_chunk("Hi")]);
+    let backend = spawn_backend(
+        Router::new().route("/v1/chat/completions", post(move || async move { body })),
+    )
+    .await;
+    let mut config = test_config(&backend);
+    config.max_stream_chunks = 3;
+    let server = test_server(&config);
+
+    let body = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "stream": true}))
+        .await
+        .text();
+
+    let events = parse_proxy_events(&body);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1]["done"], true);
+    assert!(events[1].get("done_reason").is_none());
+}
@@ -0,0 +1,64 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::Router;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+async fn server_with_token(token: Option<&str>) -> axum_test::TestServer {
+    let mut config = test_config(&spawn_backend(Router::new()).await);
+    config.metrics_auth_token = token.map(str::to_string);
+    test_server(&config)
+}
+
+#[tokio::test]
+async fn test_metrics_with_valid_token() {
+    let server = server_with_token(Some("s3cret")).await;
+
+    for path in ["/metrics", "/api/metrics"] {
+        let response = server
+            .get(path)
+            .add_header(
+                header::AUTHORIZATION,
+                HeaderValue::from_static("Bearer s3cret"),
+            )
+            .await;
+
+        response.assert_status_ok();
+        assert_eq!(response.header(header::CONTENT_TYPE), "text/plain");
+        assert!(response.text().contains("# TYPE"));
+    }
+}
+
+#[tokio::test]
+async fn test_metrics_without_valid_token_is_rejected() {
+    let server = server_with_token(Some("s3cret")).await;
+
+    for path in ["/metrics", "/api/metrics"] {
+        server
+            .get(path)
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        let response = server
+            .get(path)
+            .add_header(
+                header::AUTHORIZATION,
+                HeaderValue::from_static("Bearer wrong"),
+            )
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(response.header(header::WWW_AUTHENTICATE), "Bearer");
+    }
+
+    // Other endpoints are unaffected
+    server.get("/").await.assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_metrics_open_when_auth_disabled() {
+    let server = server_with_token(None).await;
+
+    server.get("/metrics").await.assert_status_ok();
+    server.get("/api/metrics").await.assert_status_ok();
+}
@@ -87,6 +87,25 @@ async fn test_metrics_after_chat_request() {
     assert!(metrics_body.contains("chat"));
 }
 
+#[tokio::test]
+async fn test_histograms_use_llm_buckets() {
+    metrics::HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&["bucket_test"])
+        .observe(0.2);
+    metrics::DECODE_DURATION_SECONDS
+        .with_label_values(&["bucket_test", "1"])
+        .observe(0.02);
+
+    let body = metrics::export_metrics();
+
+    assert!(body.contains(
+        "mistral_http_request_duration_seconds_bucket{endpoint=\"bucket_test\",le=\"300\"}"
+    ));
+    assert!(body.contains(
+        "mistral_decode_duration_seconds_bucket{batch_size=\"1\",model=\"bucket_test\",le=\"0.0025\"}"
+    ));
+}
+
 // Helper function to create test app
 async fn create_test_app() -> axum::Router {
     use axum::{
@@ -97,6 +116,7 @@ async fn create_test_app() -> axum::Router {
     use tower_http::cors::CorsLayer;
 
     // Import necessary modules from the main crate
+    use mistral_ollama_proxy::config::Config;
     use mistral_ollama_proxy::handlers::{
         chat::{handle_chat, handle_generate, AppState},
         models::handle_list_models,
@@ -111,12 +131,12 @@ async fn create_test_app() -> axum::Router {
         .build()
         .unwrap();
 
-    let state = Arc::new(AppState {
-        client,
-        mistral_url: "http://localhost:0".to_string(), // Non-existent backend
-        channel_buffer_size: 100,
-        max_line_length: 1_000_000,
-    });
+    let mut config = Config::from_env();
+    config.mistral_url = "http://localhost:0".to_string(); // Non-existent backend
+    config.channel_buffer_size = 100;
+    config.max_line_length = 1_000_000;
+
+    let state = Arc::new(AppState::new(client, &config));
 
     Router::new()
         .route("/api/generate", post(handle_generate))
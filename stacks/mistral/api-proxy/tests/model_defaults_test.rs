@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+/// Sends a chat with `options` for a model configured with defaults, returning the request
+/// the backend received.
+async fn sent_request(options: Option<Value>) -> Value {
+    let sent = Arc::new(Mutex::new(Value::Null));
+    let recorded = sent.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| async move {
+            *recorded.lock().unwrap() = body;
+            chat_completion("Hi").to_string()
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.model_defaults = [(
+        "mistral-7b".to_string(),
+        json!({"temperature": 0.2, "top_p": 0.5})
+            .as_object()
+            .unwrap()
+            .clone(),
+    )]
+    .into();
+    let server = test_server(&config);
+
+    let mut request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    });
+    if let Some(options) = options {
+        request["options"] = options;
+    }
+    server
+        .post("/api/chat")
+        .json(&request)
+        .await
+        .assert_status_ok();
+
+    let sent = sent.lock().unwrap().clone();
+    sent
+}
+
+#[tokio::test]
+async fn test_model_defaults_apply_when_options_omitted() {
+    let sent = sent_request(None).await;
+
+    assert_eq!(sent["temperature"], 0.2);
+    assert_eq!(sent["top_p"], 0.5);
+}
+
+#[tokio::test]
+async fn test_client_options_override_model_defaults() {
+    let sent = sent_request(Some(json!({"temperature": 0.9}))).await;
+
+    assert_eq!(sent["temperature"], 0.9);
+    assert_eq!(sent["top_p"], 0.5);
+}
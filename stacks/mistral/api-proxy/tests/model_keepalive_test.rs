@@ -0,0 +1,65 @@
+//! Kept in its own test binary because it adjusts the global active request gauge.
+
+use axum::{routing::post, Json, Router};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::metrics::ACTIVE_REQUESTS;
+use mistral_ollama_proxy::warmup::keep_model_resident;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config};
+
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Starts the keepalive task against a stub backend and returns the requests it receives.
+async fn start_keepalive() -> (Arc<Mutex<Vec<Value>>>, tokio::task::JoinHandle<()>) {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let recorded = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| async move {
+            recorded.lock().unwrap().push(body);
+            Json(chat_completion("ok"))
+        }),
+    );
+    let config = test_config(&spawn_backend(backend).await);
+    let state = Arc::new(AppState::new(reqwest::Client::new(), &config));
+
+    let task = tokio::spawn(keep_model_resident(
+        state,
+        "mistral:latest".to_string(),
+        INTERVAL,
+    ));
+    (captured, task)
+}
+
+#[tokio::test]
+async fn test_keepalive_pings_at_interval_and_pauses_for_traffic() {
+    let (captured, task) = start_keepalive().await;
+
+    tokio::time::sleep(INTERVAL * 5 + INTERVAL / 2).await;
+    let pings = captured.lock().unwrap().len();
+    assert!((4..=6).contains(&pings), "expected ~5 pings, got {pings}");
+    {
+        let captured = captured.lock().unwrap();
+        assert_eq!(captured[0]["model"], "mistral-7b");
+        assert_eq!(captured[0]["max_tokens"], 1);
+    }
+
+    // Real traffic keeps the model loaded, so no pings are sent while it is in flight
+    ACTIVE_REQUESTS.inc();
+    tokio::time::sleep(INTERVAL / 2).await;
+    let before_traffic = captured.lock().unwrap().len();
+    tokio::time::sleep(INTERVAL * 3).await;
+    assert_eq!(captured.lock().unwrap().len(), before_traffic);
+    ACTIVE_REQUESTS.dec();
+
+    tokio::time::sleep(INTERVAL * 2 + INTERVAL / 2).await;
+    assert!(captured.lock().unwrap().len() > before_traffic);
+
+    task.abort();
+}
@@ -0,0 +1,176 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    chat_completion, spawn_backend, sse_body, stream_chunk, test_config, test_server, usage_chunk,
+};
+use mistral_ollama_proxy::metrics::ModelLabels;
+
+async fn chat(server: &axum_test::TestServer, model: &str) {
+    chat_with(server, model, false).await;
+}
+
+async fn chat_with(server: &axum_test::TestServer, model: &str, stream: bool) {
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": model,
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": stream
+        }))
+        .await
+        .assert_status_ok();
+}
+
+async fn backend() -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(|Json(body): Json<Value>| async move {
+            if body["stream"] == true {
+                sse_body(&[
+                    stream_chunk("Hi"),
+                    stream_chunk(" there"),
+                    usage_chunk(3, 2),
+                ])
+            } else {
+                chat_completion("Hi").to_string()
+            }
+        }),
+    ))
+    .await
+}
+
+#[tokio::test]
+async fn test_requests_labeled_with_requested_model() {
+    let server = test_server(&test_config(&backend().await));
+
+    chat(&server, "label-test-model:latest").await;
+
+    let metrics = server.get("/metrics").await.text();
+    assert!(metrics
+        .lines()
+        .any(|line| line.starts_with("mistral_http_requests_total")
+            && line.contains(r#"endpoint="chat""#)
+            && line.contains(r#"model="label-test-model:latest""#)
+            && line.contains(r#"status="success""#)));
+}
+
+#[tokio::test]
+async fn test_unlisted_models_labeled_other() {
+    let mut config = test_config(&backend().await);
+    config.metric_model_labels = ModelLabels::allowlist(["listed-label-model"]);
+    let server = test_server(&config);
+
+    chat(&server, "listed-label-model").await;
+    chat(&server, "unlisted-label-model").await;
+
+    let metrics = server.get("/metrics").await.text();
+    let request_counts: Vec<&str> = metrics
+        .lines()
+        .filter(|line| line.starts_with("mistral_http_requests_total"))
+        .collect();
+    let labeled = |model: &str| {
+        let label = format!(r#"model="{model}""#);
+        request_counts.iter().any(|line| line.contains(&label))
+    };
+    assert!(labeled("listed-label-model"));
+    assert!(!labeled("unlisted-label-model"));
+    assert!(labeled("other"));
+}
+
+#[tokio::test]
+async fn test_unlisted_models_labeled_other_on_model_metrics() {
+    let mut config = test_config(&backend().await);
+    config.metric_model_labels = ModelLabels::allowlist(["listed-model-metric"]);
+    let server = test_server(&config);
+
+    for model in ["listed-model-metric", "unlisted-model-metric"] {
+        chat_with(&server, model, true).await;
+    }
+
+    let metrics = server.get("/metrics").await.text();
+    for metric in [
+        "mistral_generate_duration_seconds_count",
+        "mistral_generate_tokens_total",
+    ] {
+        let series: Vec<&str> = metrics
+            .lines()
+            .filter(|line| line.starts_with(metric))
+            .collect();
+        let labeled = |model: &str| {
+            let label = format!(r#"model="{model}""#);
+            series.iter().any(|line| line.contains(&label))
+        };
+        assert!(
+            labeled("listed-model-metric"),
+            "{metric} lacks listed model"
+        );
+        assert!(
+            !labeled("unlisted-model-metric"),
+            "{metric} has unlisted model"
+        );
+        assert!(labeled("other"), "{metric} lacks other");
+    }
+}
+
+#[tokio::test]
+async fn test_translated_models_labeled_with_requested_name() {
+    let mut config = test_config(&backend().await);
+    config.model_map.insert(
+        "mapped-label-model:latest".to_string(),
+        "mapped-label-backend".to_string(),
+    );
+    config.metric_model_labels = ModelLabels::allowlist(["mapped-label-model:latest"]);
+    let server = test_server(&config);
+
+    chat_with(&server, "mapped-label-model:latest", true).await;
+
+    let metrics = server.get("/metrics").await.text();
+    for metric in [
+        "mistral_generate_duration_seconds_count",
+        "mistral_generate_tokens_total",
+        "mistral_prefill_duration_seconds_count",
+        "mistral_decode_duration_seconds_count",
+        "mistral_tokens_per_second_count",
+    ] {
+        assert!(
+            metrics.lines().any(|line| line.starts_with(metric)
+                && line.contains(r#"model="mapped-label-model:latest""#)),
+            "{metric} lacks the requested model"
+        );
+        assert!(
+            !metrics
+                .lines()
+                .any(|line| line.starts_with(metric) && line.contains("mapped-label-backend")),
+            "{metric} is labeled with the backend model"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_translated_models_labeled_with_requested_name_without_streaming() {
+    let mut config = test_config(&backend().await);
+    config.model_map.insert(
+        "mapped-sync-label-model:latest".to_string(),
+        "mapped-sync-label-backend".to_string(),
+    );
+    config.metric_model_labels = ModelLabels::allowlist(["mapped-sync-label-model:latest"]);
+    let server = test_server(&config);
+
+    chat(&server, "mapped-sync-label-model:latest").await;
+
+    let metrics = server.get("/metrics").await.text();
+    for metric in [
+        "mistral_generate_duration_seconds_count",
+        "mistral_generate_tokens_total",
+        "mistral_tokens_per_second_count",
+    ] {
+        assert!(
+            metrics.lines().any(|line| line.starts_with(metric)
+                && line.contains(r#"model="mapped-sync-label-model:latest""#)),
+            "{metric} lacks the requested model"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use axum::{
+    http::{Method, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+async fn server() -> axum_test::TestServer {
+    let backend = Router::new().route(
+        "/v1/models",
+        get(|| async {
+            Json(json!({
+                "object": "list",
+                "data": [
+                    {"id": "custom-model", "object": "model", "created": 0, "owned_by": "local"}
+                ]
+            }))
+        }),
+    );
+    test_server(&test_config(&spawn_backend(backend).await))
+}
+
+#[tokio::test]
+async fn test_copy_acknowledged() {
+    server()
+        .await
+        .post("/api/copy")
+        .json(&json!({"source": "custom-model:latest", "destination": "my-model"}))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_create_acknowledged() {
+    let response = server()
+        .await
+        .post("/api/create")
+        .json(&json!({"model": "my-model", "modelfile": "FROM custom-model"}))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["status"], "success");
+}
+
+fn blob_path() -> String {
+    format!("/api/blobs/sha256:{}", "0".repeat(64))
+}
+
+#[tokio::test]
+async fn test_blob_reported_present() {
+    server()
+        .await
+        .method(Method::HEAD, &blob_path())
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_blob_upload_acknowledged() {
+    server()
+        .await
+        .post(&blob_path())
+        .bytes(vec![0u8; 16].into())
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_blob_with_malformed_digest_rejected() {
+    let server = server().await;
+
+    server
+        .method(Method::HEAD, "/api/blobs/not-a-digest")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .post("/api/blobs/not-a-digest")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_delete_known_model() {
+    let server = server().await;
+
+    server
+        .delete("/api/delete")
+        .json(&json!({"model": "custom-model:latest"}))
+        .await
+        .assert_status_ok();
+    server
+        .delete("/api/delete")
+        .json(&json!({"name": "custom-model"}))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_delete_unknown_model() {
+    let response = server()
+        .await
+        .delete("/api/delete")
+        .json(&json!({"model": "missing-model"}))
+        .await;
+
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.json::<Value>()["error"],
+        "model 'missing-model' not found"
+    );
+}
+
+#[tokio::test]
+async fn test_pull_without_coordinator_succeeds_immediately() {
+    let response = server()
+        .await
+        .post("/api/pull")
+        .json(&json!({"model": "custom-model"}))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/x-ndjson");
+    assert_eq!(response.text(), "{\"status\":\"success\"}\n");
+}
+
+#[tokio::test]
+async fn test_pull_streams_coordinator_progress() {
+    let progress = [
+        json!({"status": "pulling manifest"}),
+        json!({"status": "downloading", "completed": 50, "total": 100}),
+        json!({"status": "downloading", "completed": 100, "total": 100}),
+        json!({"status": "success"}),
+    ];
+    let body: String = progress.iter().map(|line| format!("{line}\n")).collect();
+    let coordinator = Router::new().route(
+        "/pull",
+        axum::routing::post(move |Json(req): Json<Value>| async move {
+            assert_eq!(req["model"], "custom-model");
+            body
+        }),
+    );
+    let mut config = test_config("http://127.0.0.1:9");
+    config.pull_coordinator_url = Some(format!("{}/pull", spawn_backend(coordinator).await));
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/pull")
+        .json(&json!({"model": "custom-model"}))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "application/x-ndjson");
+    let lines: Vec<Value> = response
+        .text()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines, progress);
+}
@@ -0,0 +1,99 @@
+use axum::{
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+fn custom_map() -> HashMap<String, String> {
+    HashMap::from([
+        ("llama:latest".to_string(), "llama-3-8b".to_string()),
+        ("coder:latest".to_string(), "codestral-22b".to_string()),
+    ])
+}
+
+#[tokio::test]
+async fn test_fallback_listing_reflects_model_map() {
+    let backend = Router::new().route(
+        "/v1/models",
+        get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.model_map = custom_map();
+    let server = test_server(&config);
+
+    let body: Value = server.get("/api/tags").await.json();
+
+    let names: Vec<&str> = body["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["coder:latest", "llama:latest"]);
+}
+
+#[tokio::test]
+async fn test_fallback_listing_uses_default_map() {
+    let backend = Router::new().route(
+        "/v1/models",
+        get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let body: Value = server.get("/api/tags").await.json();
+
+    let names: Vec<&str> = body["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "mistral:7b",
+            "mistral:latest",
+            "mixtral:8x7b",
+            "mixtral:latest"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_chat_model_translated_with_model_map() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(chat_completion("Hi"))
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.model_map = custom_map();
+    let server = test_server(&config);
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "llama:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert_eq!(sent["model"], "llama-3-8b");
+}
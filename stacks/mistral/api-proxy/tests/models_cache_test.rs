@@ -0,0 +1,74 @@
+use axum::{http::StatusCode, routing::get, Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+/// Serves `custom-model` on the first `healthy_calls` requests, then fails.
+fn counting_backend(calls: Arc<AtomicUsize>, healthy_calls: usize) -> Router {
+    Router::new().route(
+        "/v1/models",
+        get(move || {
+            let calls = calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < healthy_calls {
+                    Ok(Json(json!({
+                        "object": "list",
+                        "data": [
+                            {"id": "custom-model", "object": "model", "created": 0, "owned_by": "local"}
+                        ]
+                    })))
+                } else {
+                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_tags_served_from_cache() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = spawn_backend(counting_backend(calls.clone(), usize::MAX)).await;
+    let mut config = test_config(&url);
+    config.models_cache_ttl_secs = 60;
+    let server = test_server(&config);
+
+    server.get("/api/tags").await;
+    let body: Value = server.get("/api/models").await.json();
+
+    assert_eq!(body["models"][0]["name"], "custom-model:latest");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_expired_cache_refreshes() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = spawn_backend(counting_backend(calls.clone(), usize::MAX)).await;
+    let mut config = test_config(&url);
+    config.models_cache_ttl_secs = 0;
+    let server = test_server(&config);
+
+    server.get("/api/tags").await;
+    server.get("/api/tags").await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_stale_cache_served_when_backend_fails() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = spawn_backend(counting_backend(calls.clone(), 1)).await;
+    let mut config = test_config(&url);
+    config.models_cache_ttl_secs = 0;
+    let server = test_server(&config);
+
+    server.get("/api/tags").await;
+    let body: Value = server.get("/api/tags").await.json();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(body["models"][0]["name"], "custom-model:latest");
+}
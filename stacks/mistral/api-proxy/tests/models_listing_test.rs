@@ -0,0 +1,71 @@
+use axum::{routing::get, Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+/// Serves `custom-model`, but only after stalling on the first `slow_calls` requests.
+fn slow_backend(calls: Arc<AtomicUsize>, slow_calls: usize) -> Router {
+    Router::new().route(
+        "/v1/models",
+        get(move || {
+            let calls = calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < slow_calls {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+                Json(json!({
+                    "object": "list",
+                    "data": [
+                        {"id": "custom-model", "object": "model", "created": 0, "owned_by": "local"}
+                    ]
+                }))
+            }
+        }),
+    )
+}
+
+fn model_names(body: &Value) -> Vec<&str> {
+    body["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_slow_backend_falls_back_to_default_models() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = spawn_backend(slow_backend(calls.clone(), usize::MAX)).await;
+    let mut config = test_config(&url);
+    config.models_list_timeout_secs = 0.2;
+    config.models_list_retries = 1;
+    let server = test_server(&config);
+
+    let started = Instant::now();
+    let body: Value = server.get("/api/tags").await.json();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(model_names(&body).contains(&"mistral:latest"));
+}
+
+#[tokio::test]
+async fn test_retry_returns_live_models_once_backend_recovers() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = spawn_backend(slow_backend(calls.clone(), 1)).await;
+    let mut config = test_config(&url);
+    config.models_list_timeout_secs = 0.2;
+    config.models_list_retries = 1;
+    let server = test_server(&config);
+
+    let body: Value = server.get("/api/tags").await.json();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(model_names(&body), ["custom-model:latest"]);
+}
@@ -0,0 +1,63 @@
+use axum::http::{header, HeaderValue};
+
+use mistral_ollama_proxy::metrics::{HTTP_REQUESTS_TOTAL, REQUEST_BYTES};
+
+mod common;
+
+use common::{test_config, test_server};
+
+fn record_samples(endpoint: &str) {
+    HTTP_REQUESTS_TOTAL
+        .with_label_values(&[endpoint, "m", "success", "none"])
+        .inc();
+    REQUEST_BYTES.with_label_values(&[endpoint]).observe(100.0);
+}
+
+#[tokio::test]
+async fn test_metrics_default_to_prometheus_text() {
+    record_samples("prometheus_test");
+    let server = test_server(&test_config("http://localhost:1"));
+
+    let response = server.get("/metrics").await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header(header::CONTENT_TYPE), "text/plain");
+    let body = response.text();
+    assert!(body.contains("# TYPE mistral_http_requests_total counter"));
+    assert!(!body.contains("# EOF"));
+}
+
+#[tokio::test]
+async fn test_metrics_negotiate_openmetrics() {
+    record_samples("openmetrics_test");
+    let server = test_server(&test_config("http://localhost:1"));
+
+    let response = server
+        .get("/metrics")
+        .add_header(
+            header::ACCEPT,
+            HeaderValue::from_static(
+                "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5",
+            ),
+        )
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        "application/openmetrics-text; version=1.0.0; charset=utf-8"
+    );
+    let body = response.text();
+    assert!(body.ends_with("# EOF\n"));
+    assert!(body.contains("# TYPE mistral_http_requests counter\n"));
+    assert!(body.contains(
+        "mistral_http_requests_total{endpoint=\"openmetrics_test\",error_type=\"none\",model=\"m\",status=\"success\"} 1\n"
+    ));
+    assert!(body.contains("# TYPE mistral_request_bytes histogram\n"));
+    assert!(
+        body.contains("mistral_request_bytes_bucket{endpoint=\"openmetrics_test\",le=\"256\"} 1\n")
+    );
+    assert!(body
+        .contains("mistral_request_bytes_bucket{endpoint=\"openmetrics_test\",le=\"+Inf\"} 1\n"));
+    assert!(body.contains("mistral_request_bytes_sum{endpoint=\"openmetrics_test\"} 100\n"));
+}
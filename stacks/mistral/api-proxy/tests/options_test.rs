@@ -0,0 +1,91 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+fn error_message(response: &axum_test::TestResponse) -> String {
+    response.json::<Value>()["error"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_string_options_rejected() {
+    let server = test_server(&test_config("http://127.0.0.1:9"));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "options": "temperature=0"}))
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let error = error_message(&response);
+    assert!(
+        error.starts_with(
+            "JSON parsing error: options: invalid type: string \"temperature=0\", \
+             expected a JSON object"
+        ),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_array_options_rejected() {
+    let server = test_server(&test_config("http://127.0.0.1:9"));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "options": [{"temperature": 0}]
+        }))
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let error = error_message(&response);
+    assert!(
+        error.starts_with(
+            "JSON parsing error: options: invalid type: sequence, expected a JSON object"
+        ),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_object_and_null_options_accepted() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                captured.lock().unwrap().push(body);
+                Json(chat_completion("Hello"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    for options in [json!({"temperature": 0.5}), Value::Null] {
+        server
+            .post("/api/chat")
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "options": options,
+                "stream": false
+            }))
+            .await
+            .assert_status_ok();
+    }
+
+    let sent = captured.lock().unwrap();
+    assert_eq!(sent[0]["temperature"], 0.5);
+    assert_eq!(sent[1]["temperature"], Value::Null);
+}
@@ -0,0 +1,64 @@
+//! Kept in its own test binary so no other requests add samples to the histograms.
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+use mistral_ollama_proxy::metrics::{REQUEST_BYTES, RESPONSE_BYTES};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+#[tokio::test]
+async fn test_payload_sizes_recorded_for_sync_and_streaming() {
+    let streamed = sse_body(&[stream_chunk("Hello"), stream_chunk(" world")]);
+    let streamed_len = streamed.len();
+    let completion = chat_completion("Hi").to_string();
+    let completion_len = completion.len();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let reply = if body["stream"] == true {
+                streamed.clone()
+            } else {
+                completion.clone()
+            };
+            async move { reply }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let chat_requests = REQUEST_BYTES.with_label_values(&["chat"]);
+    assert_eq!(chat_requests.get_sample_count(), 1);
+    assert!(chat_requests.get_sample_sum() > 0.0);
+    let chat_responses = RESPONSE_BYTES.with_label_values(&["chat"]);
+    assert_eq!(chat_responses.get_sample_count(), 1);
+    assert_eq!(chat_responses.get_sample_sum(), completion_len as f64);
+
+    server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hello", "stream": true}))
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        REQUEST_BYTES
+            .with_label_values(&["generate"])
+            .get_sample_count(),
+        1
+    );
+    let generate_responses = RESPONSE_BYTES.with_label_values(&["generate"]);
+    assert_eq!(generate_responses.get_sample_count(), 1);
+    assert_eq!(generate_responses.get_sample_sum(), streamed_len as f64);
+}
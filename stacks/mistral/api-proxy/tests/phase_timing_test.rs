@@ -0,0 +1,81 @@
+use axum::{body::Body, routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use mistral_ollama_proxy::metrics::{DECODE_DURATION_SECONDS, PREFILL_DURATION_SECONDS};
+use prometheus::HistogramVec;
+
+mod common;
+
+use common::{spawn_backend, stream_chunk, test_config, test_server};
+
+/// Streams `words` with `delay` before each, so the first arrives after one delay (prefill)
+/// and each later one a delay after the previous (decode).
+async fn timed_backend(words: &'static [&'static str], delay: Duration) -> String {
+    spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(_): Json<Value>| async move {
+            let stream = async_stream::stream! {
+                for word in words {
+                    tokio::time::sleep(delay).await;
+                    yield Ok::<_, std::io::Error>(format!("data: {}\n\n", stream_chunk(word)));
+                }
+                yield Ok("data: [DONE]\n\n".to_string());
+            };
+            Body::from_stream(stream)
+        }),
+    ))
+    .await
+}
+
+/// Sample count and sum for `model`, across batch sizes.
+fn samples(histogram: &HistogramVec, model: &str) -> (u64, f64) {
+    ["1", "2-4", "5-8", "9+"]
+        .iter()
+        .map(|batch_size| histogram.with_label_values(&[model, batch_size]))
+        .fold((0, 0.0), |(count, sum), h| {
+            (count + h.get_sample_count(), sum + h.get_sample_sum())
+        })
+}
+
+async fn stream_chat(backend: &str, model: &str) {
+    let server = test_server(&test_config(backend));
+    let body = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": model,
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await
+        .text();
+    assert!(
+        body.contains("\"done\":true"),
+        "stream didn't finish: {body}"
+    );
+}
+
+#[tokio::test]
+async fn test_prefill_and_decode_recorded_from_stream_timing() {
+    let backend = timed_backend(&["One", " two", " three"], Duration::from_millis(100)).await;
+
+    stream_chat(&backend, "timing-model").await;
+
+    let (prefill_count, prefill_sum) = samples(&PREFILL_DURATION_SECONDS, "timing-model");
+    assert_eq!(prefill_count, 1);
+    assert!(prefill_sum >= 0.1, "prefill was {prefill_sum}s");
+
+    let (decode_count, decode_sum) = samples(&DECODE_DURATION_SECONDS, "timing-model");
+    assert_eq!(decode_count, 2);
+    assert!(decode_sum >= 0.2, "decode total was {decode_sum}s");
+}
+
+#[tokio::test]
+async fn test_stream_without_content_records_nothing() {
+    let backend = timed_backend(&[], Duration::from_millis(10)).await;
+
+    stream_chat(&backend, "silent-model").await;
+
+    assert_eq!(samples(&PREFILL_DURATION_SECONDS, "silent-model").0, 0);
+    assert_eq!(samples(&DECODE_DURATION_SECONDS, "silent-model").0, 0);
+}
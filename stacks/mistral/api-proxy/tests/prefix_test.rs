@@ -0,0 +1,49 @@
+use axum::http::HeaderValue;
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{test_config, test_server};
+use mistral_ollama_proxy::handlers::chat::DRY_RUN_HEADER;
+
+/// Returns the messages a chat request would be sent to the backend with.
+async fn translated_messages(messages: Value) -> Value {
+    let server = test_server(&test_config("http://127.0.0.1:9"));
+    let body: Value = server
+        .post("/api/chat")
+        .add_header(DRY_RUN_HEADER.clone(), HeaderValue::from_static("true"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": messages,
+            "stream": false
+        }))
+        .await
+        .json();
+    body["messages"].clone()
+}
+
+#[tokio::test]
+async fn test_trailing_assistant_message_requests_continuation() {
+    let messages = translated_messages(json!([
+        {"role": "user", "content": "Write a haiku about autumn"},
+        {"role": "assistant", "content": "Autumn moonlight -"}
+    ]))
+    .await;
+
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["prefix"], true);
+    assert!(messages[0].get("prefix").is_none());
+}
+
+#[tokio::test]
+async fn test_conversation_ending_with_user_has_no_prefix() {
+    let messages = translated_messages(json!([
+        {"role": "user", "content": "Hello"},
+        {"role": "assistant", "content": "Hi!"},
+        {"role": "user", "content": "How are you?"}
+    ]))
+    .await;
+
+    let messages = messages.as_array().unwrap();
+    assert!(messages.iter().all(|m| m.get("prefix").is_none()));
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+/// Answers every text completion with "Hello!", recording the prompts it was sent.
+async fn completions_backend(prompts: Arc<Mutex<Vec<String>>>) -> String {
+    let backend = Router::new().route(
+        "/v1/completions",
+        post(move |Json(body): Json<Value>| async move {
+            prompts
+                .lock()
+                .unwrap()
+                .push(body["prompt"].as_str().unwrap().to_string());
+            json!({
+                "id": "cmpl-test",
+                "object": "text_completion",
+                "created": 1234567890,
+                "model": "mistral-7b",
+                "choices": [{"index": 0, "text": "Hello!", "finish_reason": "stop"}]
+            })
+            .to_string()
+        }),
+    );
+    spawn_backend(backend).await
+}
+
+/// Sends two raw turns, the second continuing the first via `context`, and returns the
+/// prompt the backend received for the second.
+async fn second_turn_prompt(templates: &[(&str, &str)]) -> String {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let mut config = test_config(&completions_backend(prompts.clone()).await);
+    config.backend_completions_endpoint = true;
+    config.prompt_templates = templates
+        .iter()
+        .map(|(model, template)| (model.to_string(), template.to_string()))
+        .collect();
+    let server = test_server(&config);
+
+    let first: Value = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "raw": true, "stream": false}))
+        .await
+        .json();
+    assert_eq!(prompts.lock().unwrap()[0], "Hi");
+
+    server
+        .post("/api/generate")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "<|user|>\nAnd you?",
+            "raw": true,
+            "stream": false,
+            "context": first["context"]
+        }))
+        .await
+        .assert_status_ok();
+
+    let prompts = prompts.lock().unwrap();
+    prompts[1].clone()
+}
+
+#[tokio::test]
+async fn test_history_rendered_with_configured_template() {
+    let prompt = second_turn_prompt(&[("mistral-7b", "<|{{role}}|>\n{{content}}</s>\n")]).await;
+
+    assert_eq!(
+        prompt,
+        "<|user|>\nHi</s>\n<|assistant|>\nHello!</s>\n<|user|>\nAnd you?"
+    );
+}
+
+#[tokio::test]
+async fn test_history_rendered_with_default_template() {
+    let prompt = second_turn_prompt(&[("some-other-model", "{{content}}")]).await;
+
+    assert_eq!(
+        prompt,
+        "user: Hi\n\nassistant: Hello!\n\n<|user|>\nAnd you?"
+    );
+}
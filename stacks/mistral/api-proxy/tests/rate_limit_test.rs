@@ -0,0 +1,63 @@
+use axum::http::{header, StatusCode};
+use axum::{routing::post, Json, Router};
+use serde_json::json;
+use std::collections::HashMap;
+
+use mistral_ollama_proxy::metrics::RATE_LIMITED_TOTAL;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+async fn rate_limited_server() -> axum_test::TestServer {
+    let backend = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hi")) }),
+    ))
+    .await;
+    let mut config = test_config(&backend);
+    config.model_rate_limits = HashMap::from([("mistral-7b".to_string(), 1.0)]);
+    test_server(&config)
+}
+
+fn chat_request(model: &str) -> serde_json::Value {
+    json!({
+        "model": model,
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    })
+}
+
+#[tokio::test]
+async fn test_limited_model_returns_429_past_its_rate() {
+    let server = rate_limited_server().await;
+    let rejected = RATE_LIMITED_TOTAL.with_label_values(&["mistral-7b"]);
+    let rejected_before = rejected.get();
+
+    server
+        .post("/api/chat")
+        .json(&chat_request("mistral:latest"))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/chat")
+        .json(&chat_request("mistral:latest"))
+        .await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.header(header::RETRY_AFTER), "1");
+    assert!(rejected.get() >= rejected_before + 1.0);
+}
+
+#[tokio::test]
+async fn test_unlisted_model_is_unaffected() {
+    let server = rate_limited_server().await;
+
+    for _ in 0..5 {
+        server
+            .post("/api/chat")
+            .json(&chat_request("mixtral:latest"))
+            .await
+            .assert_status_ok();
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, test_config, test_server,
+};
+
+/// A text completion as OpenAI-compatible backends return it from `/v1/completions`.
+fn text_completion(text: &str) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "text_completion",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{"index": 0, "text": text, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 4, "completion_tokens": 2, "total_tokens": 6}
+    })
+}
+
+fn text_chunk(text: &str) -> Value {
+    json!({
+        "id": "cmpl-test",
+        "object": "text_completion",
+        "created": 1234567890,
+        "model": "mistral-7b",
+        "choices": [{"index": 0, "text": text, "finish_reason": null}]
+    })
+}
+
+/// Serves both endpoints, recording which path each request hit and the body it carried.
+async fn recording_backend(requests: Arc<Mutex<Vec<(&'static str, Value)>>>) -> String {
+    let chat_requests = requests.clone();
+    let backend = Router::new()
+        .route(
+            "/v1/chat/completions",
+            post(move |Json(body): Json<Value>| async move {
+                chat_requests.lock().unwrap().push(("chat", body));
+                chat_completion("from chat").to_string()
+            }),
+        )
+        .route(
+            "/v1/completions",
+            post(move |Json(body): Json<Value>| async move {
+                let stream = body["stream"] == true;
+                requests.lock().unwrap().push(("completions", body));
+                if stream {
+                    sse_body(&[text_chunk("from "), text_chunk("completions")])
+                } else {
+                    text_completion("from completions").to_string()
+                }
+            }),
+        );
+    spawn_backend(backend).await
+}
+
+async fn generate(
+    completions_endpoint: bool,
+    raw: Option<bool>,
+    stream: bool,
+) -> (axum_test::TestResponse, Vec<(&'static str, Value)>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let mut config = test_config(&recording_backend(requests.clone()).await);
+    config.backend_completions_endpoint = completions_endpoint;
+    let server = test_server(&config);
+
+    let mut request = json!({
+        "model": "mistral:latest",
+        "prompt": "[INST] Hi [/INST]",
+        "stream": stream
+    });
+    if let Some(raw) = raw {
+        request["raw"] = json!(raw);
+    }
+    let response = server.post("/api/generate").json(&request).await;
+    let requests = requests.lock().unwrap().drain(..).collect();
+    (response, requests)
+}
+
+#[tokio::test]
+async fn test_raw_prompt_sent_verbatim_to_completions() {
+    let (response, requests) = generate(true, Some(true), false).await;
+
+    let body: Value = response.json();
+    assert_eq!(body["response"], "from completions");
+    assert_eq!(body["eval_count"], 2);
+
+    assert_eq!(requests.len(), 1);
+    let (endpoint, sent) = &requests[0];
+    assert_eq!(*endpoint, "completions");
+    assert_eq!(sent["prompt"], "[INST] Hi [/INST]");
+    assert!(sent.get("messages").is_none());
+}
+
+#[tokio::test]
+async fn test_raw_prompt_streams_from_completions() {
+    let (response, requests) = generate(true, Some(true), true).await;
+
+    let events = parse_proxy_events(&response.text());
+    assert_eq!(events[0]["response"], "from ");
+    assert_eq!(events[1]["response"], "completions");
+    assert_eq!(events.last().unwrap()["done"], true);
+    assert_eq!(requests[0].0, "completions");
+}
+
+#[tokio::test]
+async fn test_non_raw_prompt_wrapped_in_chat() {
+    for raw in [None, Some(false)] {
+        let (response, requests) = generate(true, raw, false).await;
+
+        let body: Value = response.json();
+        assert_eq!(body["response"], "from chat");
+        let (endpoint, sent) = &requests[0];
+        assert_eq!(*endpoint, "chat");
+        assert_eq!(sent["messages"][0]["content"], "[INST] Hi [/INST]");
+    }
+}
+
+#[tokio::test]
+async fn test_raw_falls_back_to_chat_without_completions_endpoint() {
+    let (response, requests) = generate(false, Some(true), false).await;
+
+    let body: Value = response.json();
+    assert_eq!(body["response"], "from chat");
+    assert_eq!(requests[0].0, "chat");
+}
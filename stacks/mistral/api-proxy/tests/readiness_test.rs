@@ -0,0 +1,71 @@
+use axum::{http::StatusCode, routing::get, Json, Router};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::server::build_router;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+fn healthy_backend() -> Router {
+    Router::new().route(
+        "/v1/models",
+        get(|| async { Json(json!({"object": "list", "data": []})) }),
+    )
+}
+
+#[tokio::test]
+async fn test_ready_when_backend_answers() {
+    let server = test_server(&test_config(&spawn_backend(healthy_backend()).await));
+
+    let response = server.get("/readyz").await;
+
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["ready"], true);
+}
+
+#[tokio::test]
+async fn test_not_ready_when_backend_fails() {
+    let backend = Router::new().route(
+        "/v1/models",
+        get(|| async { StatusCode::SERVICE_UNAVAILABLE }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server.get("/readyz").await;
+
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = response.json();
+    assert_eq!(body["backend"], false);
+}
+
+#[tokio::test]
+async fn test_not_ready_when_backend_unreachable() {
+    let server = test_server(&test_config("http://localhost:0"));
+
+    let response = server.get("/readyz").await;
+
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_ready_waits_for_required_warmup() {
+    let mut config = test_config(&spawn_backend(healthy_backend()).await);
+    config.readiness_require_warmup = true;
+    let state = Arc::new(AppState::new(reqwest::Client::new(), &config));
+    let server = axum_test::TestServer::new(build_router(&config, state.clone())).unwrap();
+
+    let response = server.get("/readyz").await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = response.json();
+    assert_eq!(body["backend"], true);
+    assert_eq!(body["warmup"], false);
+
+    state.readiness.mark_warmup_complete();
+
+    let response = server.get("/readyz").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
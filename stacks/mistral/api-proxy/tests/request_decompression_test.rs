@@ -0,0 +1,94 @@
+use axum::{
+    http::{HeaderName, HeaderValue, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use flate2::{write::GzEncoder, write::ZlibEncoder, Compression};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+fn gzip(body: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn deflate(body: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn chat_body(content: &str) -> Vec<u8> {
+    serde_json::to_vec(&json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": content}],
+        "stream": false
+    }))
+    .unwrap()
+}
+
+fn content_encoding(encoding: &'static str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static("content-encoding"),
+        HeaderValue::from_static(encoding),
+    )
+}
+
+#[tokio::test]
+async fn test_compressed_chat_body_is_decoded() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                captured.lock().unwrap().push(body);
+                Json(chat_completion("Hi"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    for (encoding, body) in [
+        ("gzip", gzip(&chat_body("Hello gzip"))),
+        ("deflate", deflate(&chat_body("Hello deflate"))),
+    ] {
+        let (name, value) = content_encoding(encoding);
+        server
+            .post("/api/chat")
+            .add_header(name, value)
+            .bytes(body.into())
+            .await
+            .assert_status_ok();
+    }
+
+    let sent = captured.lock().unwrap();
+    assert_eq!(sent[0]["messages"][0]["content"], "Hello gzip");
+    assert_eq!(sent[1]["messages"][0]["content"], "Hello deflate");
+}
+
+#[tokio::test]
+async fn test_oversized_decompressed_body_is_rejected() {
+    let mut config = test_config("http://127.0.0.1:9");
+    config.max_request_bytes = 1024;
+    let server = test_server(&config);
+
+    // Compresses to well under the limit but expands far past it
+    let body = gzip(&chat_body(&"x".repeat(64 * 1024)));
+    assert!(body.len() < 1024);
+
+    let (name, value) = content_encoding("gzip");
+    server
+        .post("/api/chat")
+        .add_header(name, value)
+        .bytes(body.into())
+        .await
+        .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,72 @@
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+#[tokio::test]
+async fn test_client_request_id_is_echoed_and_forwarded() {
+    let seen: Arc<Mutex<Option<String>>> = Arc::default();
+    let seen_clone = seen.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |headers: HeaderMap| {
+            let seen = seen_clone.clone();
+            async move {
+                *seen.lock().unwrap() = headers
+                    .get("x-request-id")
+                    .map(|v| v.to_str().unwrap().to_string());
+                Json(chat_completion("Hi"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .add_header(REQUEST_ID, HeaderValue::from_static("client-id-123"))
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await;
+
+    assert_eq!(response.header("x-request-id"), "client-id-123");
+    assert_eq!(seen.lock().unwrap().as_deref(), Some("client-id-123"));
+}
+
+#[tokio::test]
+async fn test_request_id_generated_when_absent() {
+    let server = test_server(&test_config("http://localhost:0"));
+
+    let response = server.get("/").await;
+
+    let request_id = response.header("x-request-id");
+    assert!(!request_id.to_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_error_response_includes_request_id() {
+    let server = test_server(&test_config("http://localhost:0")); // Non-existent backend
+
+    let response = server
+        .post("/api/generate")
+        .add_header(REQUEST_ID, HeaderValue::from_static("failing-request"))
+        .json(&json!({"model": "mistral:latest", "prompt": "Hello", "stream": false}))
+        .await;
+
+    let body: Value = response.json();
+    assert!(body["error"].is_string());
+    assert_eq!(body["request_id"], "failing-request");
+    assert_eq!(response.header("x-request-id"), "failing-request");
+}
@@ -0,0 +1,100 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use mistral_ollama_proxy::metrics::{CACHE_HITS_TOTAL, CACHE_MISSES_TOTAL};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+/// A backend that answers "reply N" for its Nth request, alongside its request count.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || {
+            let calls = calls_clone.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                Json(chat_completion(&format!("reply {n}")))
+            }
+        }),
+    );
+    (spawn_backend(backend).await, calls)
+}
+
+#[tokio::test]
+async fn test_repeated_deterministic_request_served_from_cache() {
+    let (url, calls) = counting_backend().await;
+    let mut config = test_config(&url);
+    config.response_cache_ttl_secs = 60;
+    let server = test_server(&config);
+    let hits_before = CACHE_HITS_TOTAL.with_label_values(&["chat"]).get();
+
+    let request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "options": {"temperature": 0},
+        "stream": false
+    });
+    let first: Value = server.post("/api/chat").json(&request).await.json();
+    let second: Value = server.post("/api/chat").json(&request).await.json();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first["message"]["content"], "reply 1");
+    assert_eq!(second["message"]["content"], "reply 1");
+    assert_eq!(
+        CACHE_HITS_TOTAL.with_label_values(&["chat"]).get(),
+        hits_before + 1.0
+    );
+}
+
+#[tokio::test]
+async fn test_requests_with_different_parameters_miss_cache() {
+    let (url, calls) = counting_backend().await;
+    let mut config = test_config(&url);
+    config.response_cache_ttl_secs = 60;
+    let server = test_server(&config);
+    let misses_before = CACHE_MISSES_TOTAL.with_label_values(&["generate"]).get();
+
+    for seed in [1, 2] {
+        server
+            .post("/api/generate")
+            .json(&json!({
+                "model": "mistral:latest",
+                "prompt": "Hello",
+                "options": {"seed": seed},
+                "stream": false
+            }))
+            .await
+            .assert_status_ok();
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        CACHE_MISSES_TOTAL.with_label_values(&["generate"]).get(),
+        misses_before + 2.0
+    );
+}
+
+#[tokio::test]
+async fn test_sampled_requests_not_cached() {
+    let (url, calls) = counting_backend().await;
+    let mut config = test_config(&url);
+    config.response_cache_ttl_secs = 60;
+    let server = test_server(&config);
+
+    let request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "options": {"temperature": 0.8},
+        "stream": false
+    });
+    server.post("/api/chat").json(&request).await;
+    server.post("/api/chat").json(&request).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,68 @@
+use axum::{http::HeaderMap, routing::get, routing::post, Json, Router};
+use serde_json::json;
+use std::collections::HashMap;
+
+mod common;
+
+use common::{spawn_backend, sse_body, stream_chunk, test_config, test_server};
+use mistral_ollama_proxy::response_headers::header_map;
+
+fn configured_headers() -> HeaderMap {
+    header_map(&HashMap::from([
+        (
+            "Cache-Control".to_string(),
+            "public, max-age=60".to_string(),
+        ),
+        ("X-Served-By".to_string(), "edge-1".to_string()),
+    ]))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_configured_headers_on_tags_response() {
+    let backend = Router::new().route(
+        "/v1/models",
+        get(|| async {
+            Json(json!({
+                "object": "list",
+                "data": [
+                    {"id": "custom-model", "object": "model", "created": 0, "owned_by": "local"}
+                ]
+            }))
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.response_headers = configured_headers();
+    let server = test_server(&config);
+
+    let response = server.get("/api/tags").await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "public, max-age=60");
+    assert_eq!(response.header("x-served-by"), "edge-1");
+    assert_eq!(response.header("content-type"), "application/json");
+}
+
+#[tokio::test]
+async fn test_configured_headers_skip_streamed_responses() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { sse_body(&[stream_chunk("Hi")]) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.response_headers = configured_headers();
+    let server = test_server(&config);
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.header("cache-control"), "no-cache");
+    assert!(response.maybe_header("x-served-by").is_none());
+}
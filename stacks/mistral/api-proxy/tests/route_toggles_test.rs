@@ -0,0 +1,64 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+#[tokio::test]
+async fn test_disabled_generate_route_not_found() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hello there")) }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.enable_generate = false;
+    let server = test_server(&config);
+
+    server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Hi", "stream": false}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": false
+        }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>()["message"]["content"],
+        "Hello there"
+    );
+}
+
+#[tokio::test]
+async fn test_disabled_model_management_routes_not_found() {
+    let mut config = test_config("http://localhost:0");
+    config.enable_chat = false;
+    config.enable_model_management = false;
+    let server = test_server(&config);
+
+    server
+        .post("/api/chat")
+        .json(&json!({"model": "mistral:latest", "messages": []}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .post("/api/pull")
+        .json(&json!({"name": "mistral:latest"}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .delete("/api/delete")
+        .json(&json!({"name": "mistral:latest"}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Routes outside the disabled groups stay up
+    server.get("/api/version").await.assert_status_ok();
+}
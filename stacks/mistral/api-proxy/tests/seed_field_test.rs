@@ -0,0 +1,83 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+/// Sends a seeded chat and returns the body the backend received.
+async fn seeded_request_body(seed_field: Option<&str>) -> Value {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(chat_completion("Hello"))
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    if let Some(seed_field) = seed_field {
+        config.backend_seed_field = seed_field.to_string();
+    }
+    let server = test_server(&config);
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "options": {"seed": 42, "temperature": 0.2},
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    sent
+}
+
+#[tokio::test]
+async fn test_seed_sent_as_random_seed_by_default() {
+    let sent = seeded_request_body(None).await;
+
+    assert_eq!(sent["random_seed"], 42);
+    assert!(sent.get("seed").is_none());
+}
+
+#[tokio::test]
+async fn test_seed_sent_under_configured_field() {
+    let sent = seeded_request_body(Some("seed")).await;
+
+    assert_eq!(sent["seed"], 42);
+    assert!(sent.get("random_seed").is_none());
+    // Other parameters are sent unchanged
+    assert_eq!(sent["temperature"], 0.2);
+}
+
+#[tokio::test]
+async fn test_dry_run_shows_configured_seed_field() {
+    let mut config = test_config("http://localhost:0");
+    config.backend_seed_field = "seed".to_string();
+    let server = test_server(&config);
+
+    let body: Value = server
+        .post("/api/generate")
+        .add_query_param("dry_run", "1")
+        .json(&json!({
+            "model": "mistral:latest",
+            "prompt": "Hi",
+            "options": {"seed": 7},
+            "stream": false
+        }))
+        .await
+        .json();
+
+    assert_eq!(body["seed"], 7);
+    assert!(body.get("random_seed").is_none());
+}
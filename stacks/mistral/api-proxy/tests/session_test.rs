@@ -0,0 +1,180 @@
+use axum::{http::HeaderValue, routing::post, Json, Router};
+use mistral_ollama_proxy::handlers::chat::SESSION_ID_HEADER;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server,
+};
+
+/// A backend that records each request body and answers "reply N", streamed when asked.
+fn capturing_backend(captured: Arc<Mutex<Vec<Value>>>) -> Router {
+    Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured.clone();
+            async move {
+                let streamed = body["stream"] == true;
+                let mut captured = captured.lock().unwrap();
+                captured.push(body);
+                let reply = format!("reply {}", captured.len());
+                if streamed {
+                    sse_body(&[stream_chunk(&reply)])
+                } else {
+                    chat_completion(&reply).to_string()
+                }
+            }
+        }),
+    )
+}
+
+fn chat(content: &str, stream: bool) -> Value {
+    json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": content}],
+        "stream": stream
+    })
+}
+
+fn contents(body: &Value) -> Vec<String> {
+    body["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["content"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_session_history_prepended_to_later_turns() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let backend = capturing_backend(captured.clone());
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+    let session = HeaderValue::from_static("session-1");
+
+    for (content, stream) in [("My name is Sam", false), ("Hi again", true)] {
+        server
+            .post("/api/chat")
+            .add_header(SESSION_ID_HEADER.clone(), session.clone())
+            .json(&chat(content, stream))
+            .await
+            .assert_status_ok();
+    }
+    server
+        .post("/api/chat")
+        .add_header(SESSION_ID_HEADER.clone(), session)
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "What is my name?"}
+            ],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(contents(&captured[0]), ["My name is Sam"]);
+    assert_eq!(
+        contents(&captured[1]),
+        ["My name is Sam", "reply 1", "Hi again"]
+    );
+    // The client's system message stays first, and the streamed reply was recorded too
+    assert_eq!(
+        contents(&captured[2]),
+        [
+            "Be brief.",
+            "My name is Sam",
+            "reply 1",
+            "Hi again",
+            "reply 2",
+            "What is my name?"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_requests_without_session_are_independent() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let backend = capturing_backend(captured.clone());
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .add_header(
+            SESSION_ID_HEADER.clone(),
+            HeaderValue::from_static("session-1"),
+        )
+        .json(&chat("first", false))
+        .await;
+    server.post("/api/chat").json(&chat("second", false)).await;
+    let events = parse_proxy_events(
+        &server
+            .post("/api/chat")
+            .add_header(
+                SESSION_ID_HEADER.clone(),
+                HeaderValue::from_static("session-2"),
+            )
+            .json(&chat("third", true))
+            .await
+            .text(),
+    );
+    assert_eq!(events.last().unwrap()["done"], true);
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(contents(&captured[1]), ["second"]);
+    assert_eq!(contents(&captured[2]), ["third"]);
+}
+
+#[tokio::test]
+async fn test_least_recently_used_session_evicted() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let backend = capturing_backend(captured.clone());
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.session_cache_size = 1;
+    let server = test_server(&config);
+
+    for session in ["a", "b", "a"] {
+        server
+            .post("/api/chat")
+            .add_header(
+                SESSION_ID_HEADER.clone(),
+                HeaderValue::from_str(session).unwrap(),
+            )
+            .json(&chat(session, false))
+            .await
+            .assert_status_ok();
+    }
+
+    // Session `a` was dropped to make room for `b`, so its second turn starts fresh
+    let captured = captured.lock().unwrap();
+    assert_eq!(contents(&captured[2]), ["a"]);
+}
+
+#[tokio::test]
+async fn test_session_keeps_most_recent_messages() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let backend = capturing_backend(captured.clone());
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.max_session_messages = 2;
+    let server = test_server(&config);
+
+    for content in ["one", "two", "three"] {
+        server
+            .post("/api/chat")
+            .add_header(
+                SESSION_ID_HEADER.clone(),
+                HeaderValue::from_static("session-1"),
+            )
+            .json(&chat(content, false))
+            .await
+            .assert_status_ok();
+    }
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(contents(&captured[2]), ["two", "reply 2", "three"]);
+}
@@ -0,0 +1,105 @@
+//! Compares allocations of the streaming line splitter against the previous `String`-based
+//! approach on a synthetic high-throughput stream.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::time::Instant;
+
+use mistral_ollama_proxy::sse::{data_payload, LineBuffer};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+const LINES: usize = 20_000;
+
+/// Network reads of ~4KB, each holding many short token events.
+fn synthetic_stream() -> Vec<Vec<u8>> {
+    let mut body = Vec::new();
+    for i in 0..LINES {
+        body.extend_from_slice(
+            format!("data: {{\"choices\":[{{\"delta\":{{\"content\":\"tok{i}\"}}}}]}}\n\n")
+                .as_bytes(),
+        );
+    }
+    body.chunks(4096).map(<[u8]>::to_vec).collect()
+}
+
+fn count_with_string_buffer(chunks: &[Vec<u8>]) -> usize {
+    let mut buffer = String::new();
+    let mut payload_bytes = 0;
+    for chunk in chunks {
+        buffer.push_str(&String::from_utf8_lossy(chunk));
+        while let Some(line_end) = buffer.find('\n') {
+            let line = buffer.drain(..=line_end).collect::<String>();
+            if let Some(json_str) = line.trim().strip_prefix("data: ") {
+                payload_bytes += json_str.len();
+            }
+        }
+    }
+    payload_bytes
+}
+
+fn count_with_line_buffer(chunks: &[Vec<u8>]) -> usize {
+    let mut buffer = LineBuffer::new();
+    let mut payload_bytes = 0;
+    for chunk in chunks {
+        buffer.extend(chunk);
+        while let Some(line) = buffer.next_line() {
+            if let Some(payload) = data_payload(&line) {
+                payload_bytes += payload.len();
+            }
+        }
+    }
+    payload_bytes
+}
+
+#[test]
+fn test_line_buffer_allocates_less_than_string_buffer() {
+    let chunks = synthetic_stream();
+
+    let before = allocations();
+    let started = Instant::now();
+    let string_payload = count_with_string_buffer(&chunks);
+    let string_elapsed = started.elapsed();
+    let string_allocations = allocations() - before;
+
+    let before = allocations();
+    let started = Instant::now();
+    let bytes_payload = count_with_line_buffer(&chunks);
+    let bytes_elapsed = started.elapsed();
+    let bytes_allocations = allocations() - before;
+
+    assert_eq!(string_payload, bytes_payload);
+    assert!(string_allocations >= LINES * 2);
+    assert!(
+        bytes_allocations * 10 < string_allocations,
+        "expected far fewer allocations, got {bytes_allocations} in {bytes_elapsed:?} vs \
+         {string_allocations} in {string_elapsed:?}"
+    );
+}
@@ -0,0 +1,38 @@
+use axum::{routing::post, Router};
+use serde_json::{json, Value};
+
+use mistral_ollama_proxy::metrics::STREAM_CHANNEL_FULL_TOTAL;
+
+mod common;
+
+use common::{parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+#[tokio::test]
+async fn test_full_channel_counted_when_client_lags_behind() {
+    // Every chunk arrives in one read, so the forwarder outpaces the client draining a
+    // single-slot buffer
+    let events: Vec<Value> = (0..20).map(|i| stream_chunk(&format!("t{i} "))).collect();
+    let body = sse_body(&events);
+    let backend = Router::new().route("/v1/chat/completions", post(move || async move { body }));
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.channel_buffer_size = 1;
+    let server = test_server(&config);
+    let full_before = STREAM_CHANNEL_FULL_TOTAL.with_label_values(&["chat"]).get();
+
+    let events = parse_proxy_events(
+        &server
+            .post("/api/chat")
+            .json(&json!({
+                "model": "mistral:latest",
+                "messages": [{"role": "user", "content": "Count"}],
+                "stream": true
+            }))
+            .await
+            .text(),
+    );
+
+    // Waiting for space delays chunks but never drops them
+    assert_eq!(events.len(), 21);
+    assert_eq!(events.last().unwrap()["done"], true);
+    assert!(STREAM_CHANNEL_FULL_TOTAL.with_label_values(&["chat"]).get() > full_before);
+}
@@ -0,0 +1,135 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use mistral_ollama_proxy::metrics::GENERATE_TOKENS_TOTAL;
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server, usage_chunk,
+};
+
+/// Streams a chat through a backend that reports usage only when asked for it, returning the
+/// request it received and the proxy's done chunk.
+async fn stream_chat(model: &str, supports_stream_usage: bool) -> (Value, Value) {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                let include_usage = body["stream_options"]["include_usage"] == true;
+                *captured.lock().unwrap() = Some(body);
+                let mut events = vec![stream_chunk("Hello"), stream_chunk(" there")];
+                if include_usage {
+                    events.push(usage_chunk(11, 5));
+                }
+                sse_body(&events)
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.backend_supports_stream_usage = supports_stream_usage;
+    let server = test_server(&config);
+
+    let events = parse_proxy_events(
+        &server
+            .post("/api/chat")
+            .json(&json!({
+                "model": model,
+                "messages": [{"role": "user", "content": "Hi"}],
+                "stream": true
+            }))
+            .await
+            .text(),
+    );
+    let sent = captured.lock().unwrap().take().unwrap();
+    (sent, events.last().unwrap().clone())
+}
+
+#[tokio::test]
+async fn test_reported_stream_usage_used_for_counts() {
+    let (sent, done) = stream_chat("stream-usage-reported", true).await;
+
+    assert_eq!(sent["stream_options"]["include_usage"], true);
+    assert_eq!(done["done"], true);
+    assert_eq!(done["prompt_eval_count"], 11);
+    assert_eq!(done["eval_count"], 5);
+    assert!(done.get("token_counts_estimated").is_none());
+    assert_eq!(
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&["stream-usage-reported"])
+            .get(),
+        5.0
+    );
+}
+
+#[tokio::test]
+async fn test_stream_usage_estimated_when_backend_lacks_support() {
+    let (sent, done) = stream_chat("stream-usage-unsupported", false).await;
+
+    assert!(sent.get("stream_options").is_none());
+    assert_eq!(done["done"], true);
+    assert_eq!(done["token_counts_estimated"], true);
+    let eval_count = done["eval_count"].as_f64().unwrap();
+    assert!(eval_count > 0.0);
+    assert_eq!(
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&["stream-usage-unsupported"])
+            .get(),
+        eval_count
+    );
+}
+
+/// Sends a non-streaming chat to a backend answering with `completion`, returning the reply.
+async fn sync_chat(model: &str, completion: Value) -> Value {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move { Json(completion) }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": model,
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": false
+        }))
+        .await
+        .json()
+}
+
+#[tokio::test]
+async fn test_sync_usage_counted_as_generated_tokens() {
+    let reply = sync_chat("sync-usage-reported", chat_completion("Hello there")).await;
+
+    assert_eq!(reply["eval_count"], 5);
+    assert_eq!(
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&["sync-usage-reported"])
+            .get(),
+        5.0
+    );
+}
+
+#[tokio::test]
+async fn test_sync_estimated_usage_counted_as_generated_tokens() {
+    let mut completion = chat_completion("Hello there, how can I help?");
+    completion.as_object_mut().unwrap().remove("usage");
+    let reply = sync_chat("sync-usage-missing", completion).await;
+
+    assert_eq!(reply["token_counts_estimated"], true);
+    let eval_count = reply["eval_count"].as_f64().unwrap();
+    assert!(eval_count > 0.0);
+    assert_eq!(
+        GENERATE_TOKENS_TOTAL
+            .with_label_values(&["sync-usage-missing"])
+            .get(),
+        eval_count
+    );
+}
@@ -0,0 +1,152 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server, usage_chunk,
+};
+
+#[tokio::test]
+async fn test_streaming_requests_usage_and_reports_prompt_eval_count() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::new(Mutex::new(None));
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                sse_body(&[stream_chunk("Hi"), stream_chunk("!"), usage_chunk(7, 2)])
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await;
+
+    let events = parse_proxy_events(&response.text());
+    let done = events.last().unwrap();
+    assert_eq!(done["done"], true);
+    assert_eq!(done["prompt_eval_count"], 7);
+    assert_eq!(done["eval_count"], 2);
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert_eq!(sent["stream_options"]["include_usage"], true);
+}
+
+/// Sends a chat request with the given `stream` field and `Accept` header and reports whether
+/// the proxy answered with a stream.
+async fn responds_with_stream(stream: Option<bool>, accept: Option<&'static str>) -> bool {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|Json(body): Json<Value>| async move {
+            if body["stream"] == true {
+                sse_body(&[stream_chunk("Hi")])
+            } else {
+                chat_completion("Hi").to_string()
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let mut request = json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}]
+    });
+    if let Some(stream) = stream {
+        request["stream"] = json!(stream);
+    }
+    let mut builder = server.post("/api/chat").json(&request);
+    if let Some(accept) = accept {
+        builder = builder.add_header(
+            axum::http::header::ACCEPT,
+            axum::http::HeaderValue::from_static(accept),
+        );
+    }
+
+    let response = builder.await;
+    response.assert_status_ok();
+    response.header(axum::http::header::CONTENT_TYPE) == "text/event-stream"
+}
+
+#[tokio::test]
+async fn test_explicit_stream_true() {
+    assert!(responds_with_stream(Some(true), None).await);
+}
+
+#[tokio::test]
+async fn test_explicit_stream_false_overrides_accept() {
+    assert!(!responds_with_stream(Some(false), Some("text/event-stream")).await);
+}
+
+#[tokio::test]
+async fn test_stream_inferred_from_accept() {
+    assert!(responds_with_stream(None, Some("application/json, text/event-stream;q=0.9")).await);
+    assert!(!responds_with_stream(None, Some("application/json")).await);
+    assert!(!responds_with_stream(None, None).await);
+}
+
+#[tokio::test]
+async fn test_stream_chunks_share_created_at() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async {
+            sse_body(&[
+                stream_chunk("One"),
+                stream_chunk(" two"),
+                stream_chunk(" three"),
+                usage_chunk(3, 3),
+            ])
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/generate")
+        .json(&json!({"model": "mistral:latest", "prompt": "Count", "stream": true}))
+        .await;
+
+    let events = parse_proxy_events(&response.text());
+    assert_eq!(events.len(), 4);
+    assert_eq!(events.last().unwrap()["done"], true);
+    let created_at = events[0]["created_at"].as_str().unwrap();
+    assert!(events.iter().all(|event| event["created_at"] == created_at));
+}
+
+#[tokio::test]
+async fn test_json_reply_to_streaming_request_replayed_as_stream() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { Json(chat_completion("Hello there")) }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let events = parse_proxy_events(&response.text());
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["message"]["content"], "Hello there");
+    assert_eq!(events[0]["done"], false);
+    assert_eq!(events[1]["done"], true);
+    assert_eq!(events[1]["prompt_eval_count"], 10);
+    assert_eq!(events[1]["eval_count"], 5);
+}
@@ -0,0 +1,108 @@
+use axum::http::{HeaderMap, HeaderValue};
+use axum::{routing::post, Json, Router};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::TracerProvider;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tracing::Level;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+use mistral_ollama_proxy::config::LogFormat;
+use mistral_ollama_proxy::logging::build_subscriber;
+
+const CLIENT_TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+#[tokio::test]
+async fn test_spans_exported_and_traceparent_forwarded() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = build_subscriber(LogFormat::Text, Level::INFO, Some(provider.tracer("test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let traceparents: Arc<Mutex<Vec<String>>> = Arc::default();
+    let captured = traceparents.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |headers: HeaderMap, Json(_): Json<Value>| {
+            let captured = captured.clone();
+            async move {
+                if let Some(traceparent) = headers.get("traceparent") {
+                    captured
+                        .lock()
+                        .unwrap()
+                        .push(traceparent.to_str().unwrap().to_string());
+                }
+                chat_completion("Hi").to_string()
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .add_header(
+            "traceparent".parse().unwrap(),
+            HeaderValue::from_str(&format!("00-{CLIENT_TRACE_ID}-00f067aa0ba902b7-01")).unwrap(),
+        )
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    // The backend call continues the client's trace
+    let traceparents = traceparents.lock().unwrap();
+    assert_eq!(traceparents.len(), 1);
+    assert!(traceparents[0].starts_with(&format!("00-{CLIENT_TRACE_ID}-")));
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let backend_span = spans
+        .iter()
+        .find(|span| span.name == "backend_request")
+        .expect("backend_request span exported");
+    assert_eq!(
+        backend_span.span_context.trace_id().to_string(),
+        CLIENT_TRACE_ID
+    );
+}
+
+#[tokio::test]
+async fn test_no_traceparent_without_tracer() {
+    let traceparents: Arc<Mutex<Vec<String>>> = Arc::default();
+    let captured = traceparents.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |headers: HeaderMap, Json(_): Json<Value>| {
+            let captured = captured.clone();
+            async move {
+                if let Some(traceparent) = headers.get("traceparent") {
+                    captured
+                        .lock()
+                        .unwrap()
+                        .push(traceparent.to_str().unwrap().to_string());
+                }
+                chat_completion("Hi").to_string()
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    assert!(traceparents.lock().unwrap().is_empty());
+}
@@ -0,0 +1,112 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server,
+};
+
+fn completion_with_reasoning() -> Value {
+    let mut completion = chat_completion("The answer is 4.");
+    completion["choices"][0]["message"]["reasoning_content"] = json!("2 plus 2 is 4.");
+    completion
+}
+
+fn reasoning_chunk(reasoning: &str) -> Value {
+    let mut chunk = stream_chunk("");
+    chunk["choices"][0]["delta"]["reasoning_content"] = json!(reasoning);
+    chunk
+}
+
+async fn server(with_reasoning: bool) -> axum_test::TestServer {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| async move {
+            match (body["stream"] == true, with_reasoning) {
+                (true, true) => sse_body(&[reasoning_chunk("Adding..."), stream_chunk("4")]),
+                (true, false) => sse_body(&[stream_chunk("4")]),
+                (false, true) => completion_with_reasoning().to_string(),
+                (false, false) => chat_completion("4").to_string(),
+            }
+        }),
+    );
+    test_server(&test_config(&spawn_backend(backend).await))
+}
+
+fn request(endpoint: &str, stream: bool) -> Value {
+    match endpoint {
+        "chat" => json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "2+2?"}],
+            "stream": stream
+        }),
+        _ => json!({"model": "mistral:latest", "prompt": "2+2?", "stream": stream}),
+    }
+}
+
+#[tokio::test]
+async fn test_reasoning_returned_as_thinking() {
+    let server = server(true).await;
+
+    let chat: Value = server
+        .post("/api/chat")
+        .json(&request("chat", false))
+        .await
+        .json();
+    assert_eq!(chat["message"]["content"], "The answer is 4.");
+    assert_eq!(chat["message"]["thinking"], "2 plus 2 is 4.");
+
+    let generate: Value = server
+        .post("/api/generate")
+        .json(&request("generate", false))
+        .await
+        .json();
+    assert_eq!(generate["response"], "The answer is 4.");
+    assert_eq!(generate["thinking"], "2 plus 2 is 4.");
+}
+
+#[tokio::test]
+async fn test_reasoning_streamed_as_thinking() {
+    let server = server(true).await;
+
+    let body = server
+        .post("/api/chat")
+        .json(&request("chat", true))
+        .await
+        .text();
+    let events = parse_proxy_events(&body);
+    assert_eq!(events[0]["message"]["thinking"], "Adding...");
+    assert_eq!(events[0]["message"]["content"], "");
+    assert_eq!(events[1]["message"]["content"], "4");
+    assert!(events[1]["message"].get("thinking").is_none());
+
+    let body = server
+        .post("/api/generate")
+        .json(&request("generate", true))
+        .await
+        .text();
+    let events = parse_proxy_events(&body);
+    assert_eq!(events[0]["thinking"], "Adding...");
+    assert!(events[1].get("thinking").is_none());
+}
+
+#[tokio::test]
+async fn test_thinking_omitted_without_reasoning() {
+    let server = server(false).await;
+
+    let chat: Value = server
+        .post("/api/chat")
+        .json(&request("chat", false))
+        .await
+        .json();
+    assert!(chat["message"].get("thinking").is_none());
+
+    let generate: Value = server
+        .post("/api/generate")
+        .json(&request("generate", false))
+        .await
+        .json();
+    assert!(generate.get("thinking").is_none());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "token-estimation")]
+
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config, test_server};
+
+#[tokio::test]
+async fn test_missing_usage_is_estimated() {
+    let backend = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(|Json(body): Json<Value>| async move {
+            if body["stream"] == true {
+                sse_body(&[stream_chunk("Hello"), stream_chunk(", world!")])
+            } else {
+                json!({
+                    "id": "cmpl-test",
+                    "object": "chat.completion",
+                    "created": 1234567890,
+                    "model": "mistral-7b",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "Hello, world!"},
+                        "finish_reason": "stop"
+                    }]
+                })
+                .to_string()
+            }
+        }),
+    ))
+    .await;
+    let server = test_server(&test_config(&backend));
+    let request = |stream: bool| {
+        json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Say hello to the world."}],
+            "stream": stream
+        })
+    };
+
+    let body: Value = server.post("/api/chat").json(&request(false)).await.json();
+    assert_eq!(body["prompt_eval_count"], 6);
+    assert_eq!(body["eval_count"], 4);
+    assert_eq!(body["token_counts_estimated"], true);
+
+    let events = parse_proxy_events(&server.post("/api/chat").json(&request(true)).await.text());
+    let done = events.last().unwrap();
+    assert_eq!(done["prompt_eval_count"], 6);
+    assert_eq!(done["eval_count"], 4);
+    assert_eq!(done["token_counts_estimated"], true);
+}
@@ -0,0 +1,90 @@
+use axum::{routing::post, Json, Router};
+use serde_json::json;
+use std::time::Duration;
+
+use mistral_ollama_proxy::metrics::TOKENS_PER_SECOND;
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server, usage_chunk,
+};
+
+const GENERATION_TIME: Duration = Duration::from_millis(250);
+
+#[tokio::test]
+async fn test_sync_generate_records_tokens_per_second() {
+    // 20 tokens in at least 250ms is at most 80 tokens/sec
+    let mut completion = chat_completion("Hello there");
+    completion["usage"] = json!({"prompt_tokens": 4, "completion_tokens": 20, "total_tokens": 24});
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move {
+            tokio::time::sleep(GENERATION_TIME).await;
+            Json(completion)
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/generate")
+        .json(&json!({"model": "tps-sync", "prompt": "Hi", "stream": false}))
+        .await
+        .assert_status_ok();
+
+    let histogram = TOKENS_PER_SECOND.with_label_values(&["tps-sync"]);
+    assert_eq!(histogram.get_sample_count(), 1);
+    let rate = histogram.get_sample_sum();
+    assert!(rate > 5.0 && rate <= 80.0, "implausible rate {rate}");
+}
+
+#[tokio::test]
+async fn test_stream_records_tokens_per_second() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async {
+            tokio::time::sleep(GENERATION_TIME).await;
+            sse_body(&[stream_chunk("Hello"), usage_chunk(4, 20)])
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let events = parse_proxy_events(
+        &server
+            .post("/api/generate")
+            .json(&json!({"model": "tps-stream", "prompt": "Hi", "stream": true}))
+            .await
+            .text(),
+    );
+    assert_eq!(events.last().unwrap()["done"], true);
+
+    let histogram = TOKENS_PER_SECOND.with_label_values(&["tps-stream"]);
+    assert_eq!(histogram.get_sample_count(), 1);
+    let rate = histogram.get_sample_sum();
+    assert!(rate > 5.0 && rate <= 80.0, "implausible rate {rate}");
+}
+
+#[tokio::test]
+async fn test_empty_completion_not_recorded() {
+    let mut completion = chat_completion("");
+    completion["usage"] = json!({"prompt_tokens": 4, "completion_tokens": 0, "total_tokens": 4});
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move { Json(completion) }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/generate")
+        .json(&json!({"model": "tps-empty", "prompt": "Hi", "stream": false}))
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        TOKENS_PER_SECOND
+            .with_label_values(&["tps-empty"])
+            .get_sample_count(),
+        0
+    );
+}
@@ -0,0 +1,208 @@
+use axum::{routing::post, Json, Router};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{
+    chat_completion, parse_proxy_events, spawn_backend, sse_body, stream_chunk, test_config,
+    test_server,
+};
+
+#[tokio::test]
+async fn test_tools_forwarded_and_tool_calls_returned() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(json!({
+                    "id": "cmpl-test",
+                    "object": "chat.completion",
+                    "created": 1234567890,
+                    "model": "mistral-7b",
+                    "choices": [{
+                        "index": 0,
+                        "message": {
+                            "role": "assistant",
+                            "content": "",
+                            "tool_calls": [{
+                                "id": "call_1",
+                                "type": "function",
+                                "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                            }]
+                        },
+                        "finish_reason": "tool_calls"
+                    }]
+                }))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let tool = json!({
+        "type": "function",
+        "function": {
+            "name": "get_weather",
+            "description": "Get the weather for a city",
+            "parameters": {
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            }
+        }
+    });
+    let body: Value = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Weather in Paris?"}],
+            "tools": [tool],
+            "tool_choice": "any",
+            "stream": false
+        }))
+        .await
+        .json();
+
+    let tool_calls = body["message"]["tool_calls"].as_array().unwrap();
+    assert_eq!(tool_calls.len(), 1);
+    assert_eq!(tool_calls[0]["function"]["name"], "get_weather");
+    assert_eq!(
+        tool_calls[0]["function"]["arguments"],
+        json!({"city": "Paris"})
+    );
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert_eq!(sent["tools"], json!([tool]));
+    assert_eq!(sent["tool_choice"], "any");
+}
+
+#[tokio::test]
+async fn test_tool_choice_omitted_when_not_sent() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(chat_completion("Hi"))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert!(sent.get("tool_choice").is_none());
+    assert!(sent.get("tools").is_none());
+}
+
+#[tokio::test]
+async fn test_tool_calls_streamed_from_deltas() {
+    let mut tool_chunk = stream_chunk("");
+    tool_chunk["choices"][0]["delta"]["tool_calls"] = json!([{
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+    }]);
+    let body = sse_body(&[tool_chunk]);
+    let backend = Router::new().route("/v1/chat/completions", post(move || async move { body }));
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let body = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Weather in Paris?"}],
+            "tools": [{"type": "function", "function": {"name": "get_weather"}}],
+            "stream": true
+        }))
+        .await
+        .text();
+
+    let events = parse_proxy_events(&body);
+    let tool_calls = &events[0]["message"]["tool_calls"];
+    assert_eq!(tool_calls[0]["function"]["name"], "get_weather");
+    assert_eq!(
+        tool_calls[0]["function"]["arguments"],
+        json!({"city": "Paris"})
+    );
+    assert_eq!(events[1]["done"], true);
+}
+
+#[tokio::test]
+async fn test_null_content_in_history_and_reply() {
+    let captured: Arc<Mutex<Option<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let tool_call = json!({
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+    });
+    let reply_call = tool_call.clone();
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            let tool_call = reply_call.clone();
+            async move {
+                *captured.lock().unwrap() = Some(body);
+                Json(json!({
+                    "id": "cmpl-test",
+                    "object": "chat.completion",
+                    "created": 1234567890,
+                    "model": "mistral-7b",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": null, "tool_calls": [tool_call]},
+                        "finish_reason": "tool_calls"
+                    }]
+                }))
+            }
+        }),
+    );
+    let server = test_server(&test_config(&spawn_backend(backend).await));
+
+    let response = server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": null, "tool_calls": [tool_call]},
+                {"role": "tool", "content": "Sunny"},
+                {"role": "user", "content": "And tomorrow?"}
+            ],
+            "stream": false
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["message"]["content"], "");
+    assert_eq!(
+        body["message"]["tool_calls"][0]["function"]["name"],
+        "get_weather"
+    );
+
+    let sent = captured.lock().unwrap().take().unwrap();
+    assert_eq!(sent["messages"][1]["content"], json!(null));
+    assert_eq!(sent["messages"][1]["tool_calls"][0]["id"], "call_1");
+    assert_eq!(sent["messages"][2]["content"], "Sunny");
+}
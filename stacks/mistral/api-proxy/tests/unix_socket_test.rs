@@ -0,0 +1,47 @@
+#![cfg(unix)]
+
+use axum::Router;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::oneshot;
+
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::listener::{bind_unix, serve_unix};
+use mistral_ollama_proxy::server::build_router;
+use std::sync::Arc;
+
+mod common;
+
+use common::{spawn_backend, test_config};
+
+#[tokio::test]
+async fn test_serves_over_unix_socket() {
+    let config = test_config(&spawn_backend(Router::new()).await);
+    let state = Arc::new(AppState::new(reqwest::Client::new(), &config));
+    let app = build_router(&config, state);
+
+    let path = std::env::temp_dir().join(format!("proxy-test-{}.sock", std::process::id()));
+    // A leftover file from an earlier run must not prevent binding
+    std::fs::write(&path, b"stale").unwrap();
+
+    let listener = bind_unix(&path).unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_unix(listener, app, async {
+        let _ = shutdown_rx.await;
+    }));
+
+    let mut stream = UnixStream::connect(&path).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    assert!(response.contains("Ollama is running"), "{response}");
+
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(!path.exists());
+}
@@ -0,0 +1,57 @@
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::{routing::post, Router};
+use serde_json::json;
+
+mod common;
+
+use common::{spawn_backend, test_config, test_server};
+
+async fn server_with_429(retry_after: Option<&'static str>) -> axum_test::TestServer {
+    let backend = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move || async move {
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, "slow down").into_response();
+            if let Some(retry_after) = retry_after {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_after.parse().unwrap());
+            }
+            response
+        }),
+    ))
+    .await;
+    test_server(&test_config(&backend))
+}
+
+fn chat_request(stream: bool) -> serde_json::Value {
+    json!({
+        "model": "mistral:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": stream
+    })
+}
+
+#[tokio::test]
+async fn test_upstream_429_with_retry_after_is_passed_through() {
+    let server = server_with_429(Some("7")).await;
+
+    for stream in [false, true] {
+        let response = server.post("/api/chat").json(&chat_request(stream)).await;
+
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.header(header::RETRY_AFTER), "7");
+    }
+}
+
+#[tokio::test]
+async fn test_upstream_429_without_retry_after() {
+    let server = server_with_429(None).await;
+
+    for stream in [false, true] {
+        let response = server.post("/api/chat").json(&chat_request(stream)).await;
+
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
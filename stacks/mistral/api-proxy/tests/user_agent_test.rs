@@ -0,0 +1,46 @@
+use axum::http::HeaderMap;
+use axum::{routing::post, Json, Router};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config, test_server};
+
+#[tokio::test]
+async fn test_backend_receives_user_agent_and_forwarded_headers() {
+    let captured: Arc<Mutex<Option<HeaderMap>>> = Arc::default();
+    let captured_clone = captured.clone();
+    let backend = spawn_backend(Router::new().route(
+        "/v1/chat/completions",
+        post(move |headers: HeaderMap| {
+            let captured = captured_clone.clone();
+            async move {
+                *captured.lock().unwrap() = Some(headers);
+                Json(chat_completion("Hi"))
+            }
+        }),
+    ))
+    .await;
+
+    let mut config = test_config(&backend);
+    assert!(config.user_agent.starts_with("mistral-ollama-proxy/"));
+    config.user_agent = "test-proxy/1.2.3".to_string();
+    config.backend_forward_headers = vec![("X-Proxy-Id".to_string(), "edge-1".to_string())];
+    let server = test_server(&config);
+
+    server
+        .post("/api/chat")
+        .json(&json!({
+            "model": "mistral:latest",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": false
+        }))
+        .await
+        .assert_status_ok();
+
+    let headers = captured.lock().unwrap().take().unwrap();
+    assert_eq!(headers["user-agent"], "test-proxy/1.2.3");
+    assert_eq!(headers["x-proxy-id"], "edge-1");
+    assert!(headers.get("authorization").is_none());
+}
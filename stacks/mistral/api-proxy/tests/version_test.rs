@@ -0,0 +1,17 @@
+use serde_json::Value;
+
+mod common;
+
+use common::{test_config, test_server};
+
+#[tokio::test]
+async fn test_version_reports_crate_version_and_backend() {
+    let mut config = test_config("http://127.0.0.1:9");
+    config.backend_kind = "vllm".to_string();
+    let server = test_server(&config);
+
+    let body: Value = server.get("/api/version").await.json();
+
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(body["backend"], "vllm");
+}
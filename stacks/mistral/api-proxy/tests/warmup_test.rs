@@ -0,0 +1,68 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+use mistral_ollama_proxy::handlers::chat::AppState;
+use mistral_ollama_proxy::metrics::MODEL_LOAD_DURATION_SECONDS;
+use mistral_ollama_proxy::warmup::warmup_models;
+
+mod common;
+
+use common::{chat_completion, spawn_backend, test_config};
+
+#[tokio::test]
+async fn test_warmup_requests_each_model() {
+    let captured: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let captured_clone = captured.clone();
+
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let captured = captured_clone.clone();
+            async move {
+                captured.lock().unwrap().push(body);
+                Json(chat_completion("ok"))
+            }
+        }),
+    );
+    let mut config = test_config(&spawn_backend(backend).await);
+    config.readiness_require_warmup = true;
+    let state = Arc::new(AppState::new(reqwest::Client::new(), &config));
+
+    warmup_models(
+        state.clone(),
+        vec![
+            "mixtral:latest".to_string(),
+            "warmup-test-model".to_string(),
+        ],
+    )
+    .await;
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 2);
+    assert_eq!(captured[0]["model"], "mixtral-8x7b");
+    assert_eq!(captured[0]["max_tokens"], 1);
+    assert_eq!(captured[1]["model"], "warmup-test-model");
+
+    let samples = MODEL_LOAD_DURATION_SECONDS
+        .with_label_values(&["warmup-test-model"])
+        .get_sample_count();
+    assert_eq!(samples, 1);
+}
+
+#[tokio::test]
+async fn test_warmup_failure_still_completes() {
+    let backend = Router::new().route(
+        "/v1/chat/completions",
+        post(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+    );
+    let config = test_config(&spawn_backend(backend).await);
+    let state = Arc::new(AppState::new(reqwest::Client::new(), &config));
+
+    warmup_models(state, vec!["failing-warmup-model".to_string()]).await;
+
+    let samples = MODEL_LOAD_DURATION_SECONDS
+        .with_label_values(&["failing-warmup-model"])
+        .get_sample_count();
+    assert_eq!(samples, 0);
+}